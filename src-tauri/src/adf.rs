@@ -0,0 +1,840 @@
+//! Conversion between Jira's Atlassian Document Format (ADF) and plain text, HTML
+//! and Markdown.
+//!
+//! The HTML output is built from escaped text only: no markup from the document is
+//! passed through, and link targets are restricted to `http`, `https` and `mailto`,
+//! so it can be inserted into the webview as-is.
+
+use pulldown_cmark::{CodeBlockKind, Event, HeadingLevel, Options, Parser, Tag, TagEnd};
+use serde_json::{json, Value};
+
+/// Extract plain text from an ADF document.
+pub fn to_plain_text(value: &Value) -> String {
+    let mut parts = Vec::new();
+    collect_plain_text(value, &mut parts);
+    parts.join("")
+}
+
+fn collect_plain_text(value: &Value, parts: &mut Vec<String>) {
+    match value {
+        Value::Object(map) => {
+            let node_type = map.get("type").and_then(|v| v.as_str()).unwrap_or("");
+
+            // Add newlines between block-level nodes
+            if matches!(node_type, "paragraph" | "heading" | "bulletList" | "orderedList" | "listItem" | "codeBlock" | "blockquote")
+                && !parts.is_empty()
+            {
+                let last = parts.last().map(|s| s.as_str()).unwrap_or("");
+                // Keep a list item's first paragraph on the same line as its bullet
+                if !last.is_empty() && !last.ends_with('\n') && last != "- " {
+                    parts.push("\n".to_string());
+                }
+            }
+
+            if node_type == "listItem" {
+                parts.push("- ".to_string());
+            }
+
+            // Text node
+            if node_type == "text" {
+                if let Some(text) = map.get("text").and_then(|v| v.as_str()) {
+                    parts.push(text.to_string());
+                }
+            }
+
+            // Recurse into content
+            if let Some(content) = map.get("content") {
+                collect_plain_text(content, parts);
+            }
+        }
+        Value::Array(arr) => {
+            for item in arr {
+                collect_plain_text(item, parts);
+            }
+        }
+        _ => {}
+    }
+}
+
+// --- Shared helpers ---
+
+fn node_type(node: &Value) -> &str {
+    node.get("type").and_then(|v| v.as_str()).unwrap_or("")
+}
+
+fn children(node: &Value) -> &[Value] {
+    node.get("content")
+        .and_then(|v| v.as_array())
+        .map(|v| v.as_slice())
+        .unwrap_or(&[])
+}
+
+fn attr<'a>(node: &'a Value, name: &str) -> Option<&'a Value> {
+    node.get("attrs").and_then(|a| a.get(name))
+}
+
+fn attr_str<'a>(node: &'a Value, name: &str) -> Option<&'a str> {
+    attr(node, name).and_then(|v| v.as_str())
+}
+
+fn attr_u64(node: &Value, name: &str) -> Option<u64> {
+    attr(node, name).and_then(|v| v.as_u64().or_else(|| v.as_str()?.parse().ok()))
+}
+
+/// Only allow link targets that cannot run script in the webview.
+fn safe_url(url: &str) -> Option<&str> {
+    let url = url.trim();
+    let lower = url.to_ascii_lowercase();
+    if lower.starts_with("http://") || lower.starts_with("https://") || lower.starts_with("mailto:") {
+        Some(url)
+    } else {
+        None
+    }
+}
+
+fn mention_text(node: &Value) -> String {
+    match attr_str(node, "text").filter(|t| !t.is_empty()) {
+        Some(text) if text.starts_with('@') => text.to_string(),
+        Some(text) => format!("@{}", text),
+        None => "@unknown".to_string(),
+    }
+}
+
+fn emoji_text(node: &Value) -> String {
+    attr_str(node, "text")
+        .or_else(|| attr_str(node, "shortName"))
+        .unwrap_or("")
+        .to_string()
+}
+
+/// ADF `date` nodes carry a UTC timestamp in milliseconds.
+fn date_text(node: &Value) -> String {
+    attr_u64(node, "timestamp")
+        .and_then(|ms| chrono::DateTime::from_timestamp_millis(ms as i64))
+        .map(|d| d.format("%Y-%m-%d").to_string())
+        .unwrap_or_default()
+}
+
+fn code_text(node: &Value) -> String {
+    children(node)
+        .iter()
+        .filter_map(|c| c.get("text").and_then(|t| t.as_str()))
+        .collect()
+}
+
+// --- HTML ---
+
+/// Render an ADF document to sanitized HTML.
+pub fn to_html(value: &Value) -> String {
+    let mut out = String::new();
+    match value {
+        Value::Array(nodes) => html_nodes(nodes, &mut out),
+        node => html_node(node, &mut out),
+    }
+    out
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn html_nodes(nodes: &[Value], out: &mut String) {
+    for node in nodes {
+        html_node(node, out);
+    }
+}
+
+fn html_wrap(tag: &str, node: &Value, out: &mut String) {
+    out.push_str(&format!("<{}>", tag));
+    html_nodes(children(node), out);
+    out.push_str(&format!("</{}>", tag));
+}
+
+fn html_link(url: &str, label: &str, out: &mut String) {
+    match safe_url(url) {
+        Some(url) => out.push_str(&format!(
+            "<a href=\"{}\" target=\"_blank\" rel=\"noopener noreferrer\">{}</a>",
+            escape_html(url),
+            escape_html(label)
+        )),
+        None => out.push_str(&escape_html(label)),
+    }
+}
+
+fn html_node(node: &Value, out: &mut String) {
+    match node_type(node) {
+        "text" => html_text(node, out),
+        "paragraph" => html_wrap("p", node, out),
+        "heading" => {
+            let level = attr_u64(node, "level").unwrap_or(1).clamp(1, 6);
+            html_wrap(&format!("h{}", level), node, out);
+        }
+        "bulletList" => html_wrap("ul", node, out),
+        "orderedList" => match attr_u64(node, "order").filter(|&n| n != 1) {
+            Some(start) => {
+                out.push_str(&format!("<ol start=\"{}\">", start));
+                html_nodes(children(node), out);
+                out.push_str("</ol>");
+            }
+            None => html_wrap("ol", node, out),
+        },
+        "listItem" => html_wrap("li", node, out),
+        "blockquote" => html_wrap("blockquote", node, out),
+        "codeBlock" => {
+            match attr_str(node, "language").filter(|l| !l.is_empty()) {
+                Some(lang) => out.push_str(&format!(
+                    "<pre><code class=\"language-{}\">",
+                    escape_html(lang)
+                )),
+                None => out.push_str("<pre><code>"),
+            }
+            out.push_str(&escape_html(&code_text(node)));
+            out.push_str("</code></pre>");
+        }
+        "rule" => out.push_str("<hr>"),
+        "hardBreak" => out.push_str("<br>"),
+        "mention" => out.push_str(&format!(
+            "<span class=\"adf-mention\">{}</span>",
+            escape_html(&mention_text(node))
+        )),
+        "emoji" => out.push_str(&escape_html(&emoji_text(node))),
+        "date" => out.push_str(&format!(
+            "<time class=\"adf-date\">{}</time>",
+            escape_html(&date_text(node))
+        )),
+        "status" => out.push_str(&format!(
+            "<span class=\"adf-status\">{}</span>",
+            escape_html(attr_str(node, "text").unwrap_or(""))
+        )),
+        "inlineCard" | "blockCard" | "embedCard" => {
+            let url = attr_str(node, "url").unwrap_or("");
+            html_link(url, url, out);
+        }
+        "panel" => {
+            let panel_type: String = attr_str(node, "panelType")
+                .unwrap_or("info")
+                .chars()
+                .filter(|c| c.is_ascii_alphanumeric())
+                .collect();
+            out.push_str(&format!("<div class=\"adf-panel adf-panel-{}\">", panel_type));
+            html_nodes(children(node), out);
+            out.push_str("</div>");
+        }
+        "expand" | "nestedExpand" => {
+            out.push_str("<details><summary>");
+            out.push_str(&escape_html(attr_str(node, "title").unwrap_or("")));
+            out.push_str("</summary>");
+            html_nodes(children(node), out);
+            out.push_str("</details>");
+        }
+        "table" => {
+            out.push_str("<table><tbody>");
+            html_nodes(children(node), out);
+            out.push_str("</tbody></table>");
+        }
+        "tableRow" => html_wrap("tr", node, out),
+        "tableHeader" | "tableCell" => {
+            let tag = if node_type(node) == "tableHeader" { "th" } else { "td" };
+            out.push('<');
+            out.push_str(tag);
+            for span in ["colspan", "rowspan"] {
+                if let Some(n) = attr_u64(node, span).filter(|&n| n > 1) {
+                    out.push_str(&format!(" {}=\"{}\"", span, n));
+                }
+            }
+            out.push('>');
+            html_nodes(children(node), out);
+            out.push_str(&format!("</{}>", tag));
+        }
+        "taskList" | "decisionList" => {
+            out.push_str("<ul class=\"adf-task-list\">");
+            html_nodes(children(node), out);
+            out.push_str("</ul>");
+        }
+        "taskItem" => {
+            let checked = if attr_str(node, "state") == Some("DONE") { " checked" } else { "" };
+            out.push_str(&format!("<li><input type=\"checkbox\" disabled{}> ", checked));
+            html_nodes(children(node), out);
+            out.push_str("</li>");
+        }
+        "decisionItem" => html_wrap("li", node, out),
+        // Attachments need an authenticated request, show a placeholder instead.
+        "media" => out.push_str(&format!(
+            "<span class=\"adf-media\">[{}]</span>",
+            escape_html(attr_str(node, "alt").filter(|a| !a.is_empty()).unwrap_or("attachment"))
+        )),
+        _ => html_nodes(children(node), out),
+    }
+}
+
+fn html_text(node: &Value, out: &mut String) {
+    let text = node.get("text").and_then(|v| v.as_str()).unwrap_or("");
+    let mut open = String::new();
+    let mut close = Vec::new();
+
+    for mark in node.get("marks").and_then(|m| m.as_array()).into_iter().flatten() {
+        let tag = match node_type(mark) {
+            "strong" => "strong",
+            "em" => "em",
+            "code" => "code",
+            "strike" => "s",
+            "underline" => "u",
+            "subsup" if attr_str(mark, "type") == Some("sup") => "sup",
+            "subsup" => "sub",
+            "link" => {
+                if let Some(url) = attr_str(mark, "href").and_then(safe_url) {
+                    open.push_str(&format!(
+                        "<a href=\"{}\" target=\"_blank\" rel=\"noopener noreferrer\">",
+                        escape_html(url)
+                    ));
+                    close.push("a");
+                }
+                continue;
+            }
+            _ => continue,
+        };
+        open.push_str(&format!("<{}>", tag));
+        close.push(tag);
+    }
+
+    out.push_str(&open);
+    out.push_str(&escape_html(text));
+    for tag in close.iter().rev() {
+        out.push_str(&format!("</{}>", tag));
+    }
+}
+
+// --- Markdown ---
+
+/// Render an ADF document to CommonMark (with GFM tables and task lists).
+pub fn to_markdown(value: &Value) -> String {
+    match value {
+        Value::Array(nodes) => md_blocks(nodes, "\n\n"),
+        node => md_block(node),
+    }
+}
+
+fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '*' | '_' | '`' | '[' | ']' | '<' | '>' | '~' | '|') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+fn is_inline(node: &Value) -> bool {
+    matches!(
+        node_type(node),
+        "text" | "hardBreak" | "mention" | "emoji" | "date" | "status" | "inlineCard"
+    )
+}
+
+fn md_blocks(nodes: &[Value], separator: &str) -> String {
+    let mut blocks = Vec::new();
+    let mut inline_run: Vec<Value> = Vec::new();
+
+    for node in nodes {
+        if is_inline(node) {
+            inline_run.push(node.clone());
+            continue;
+        }
+        if !inline_run.is_empty() {
+            blocks.push(md_inline(&inline_run));
+            inline_run.clear();
+        }
+        blocks.push(md_block(node));
+    }
+    if !inline_run.is_empty() {
+        blocks.push(md_inline(&inline_run));
+    }
+
+    blocks
+        .into_iter()
+        .filter(|b| !b.is_empty())
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+fn prefix_lines(text: &str, first: &str, rest: &str) -> String {
+    text.lines()
+        .enumerate()
+        .map(|(i, line)| {
+            let prefix = if i == 0 { first } else { rest };
+            if line.is_empty() {
+                prefix.trim_end().to_string()
+            } else {
+                format!("{}{}", prefix, line)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn md_block(node: &Value) -> String {
+    match node_type(node) {
+        "paragraph" => md_inline(children(node)),
+        "heading" => {
+            let level = attr_u64(node, "level").unwrap_or(1).clamp(1, 6) as usize;
+            format!("{} {}", "#".repeat(level), md_inline(children(node)))
+        }
+        "bulletList" => md_list(node, None),
+        "orderedList" => md_list(node, Some(attr_u64(node, "order").unwrap_or(1))),
+        "taskList" => md_list(node, None),
+        "blockquote" | "panel" => prefix_lines(&md_blocks(children(node), "\n\n"), "> ", "> "),
+        "codeBlock" => {
+            let code = code_text(node);
+            // The fence must be longer than any backtick run inside the code.
+            let longest = code
+                .split(|c| c != '`')
+                .map(|run| run.len())
+                .max()
+                .unwrap_or(0);
+            let fence = "`".repeat(longest.max(2) + 1);
+            let lang = attr_str(node, "language").unwrap_or("");
+            format!("{}{}\n{}\n{}", fence, lang, code.trim_end_matches('\n'), fence)
+        }
+        "rule" => "---".to_string(),
+        "table" => md_table(node),
+        "expand" | "nestedExpand" => {
+            let title = attr_str(node, "title").unwrap_or("");
+            let body = md_blocks(children(node), "\n\n");
+            if title.is_empty() {
+                body
+            } else {
+                format!("**{}**\n\n{}", escape_markdown(title), body)
+            }
+        }
+        "blockCard" | "embedCard" => md_card(node),
+        "media" => format!(
+            "[{}]",
+            escape_markdown(attr_str(node, "alt").filter(|a| !a.is_empty()).unwrap_or("attachment"))
+        ),
+        _ if is_inline(node) => md_inline(std::slice::from_ref(node)),
+        _ => md_blocks(children(node), "\n\n"),
+    }
+}
+
+fn md_list(node: &Value, start: Option<u64>) -> String {
+    children(node)
+        .iter()
+        .enumerate()
+        .map(|(i, item)| {
+            let marker = match (start, node_type(item)) {
+                (_, "taskItem") if attr_str(item, "state") == Some("DONE") => "- [x] ".to_string(),
+                (_, "taskItem") => "- [ ] ".to_string(),
+                (Some(n), _) => format!("{}. ", n + i as u64),
+                (None, _) => "- ".to_string(),
+            };
+            let body = md_blocks(children(item), "\n");
+            let indent = " ".repeat(marker.len());
+            prefix_lines(&body, &marker, &indent)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn md_table(node: &Value) -> String {
+    let rows: Vec<(bool, Vec<String>)> = children(node)
+        .iter()
+        .map(|row| {
+            let cells = children(row);
+            let is_header = !cells.is_empty() && cells.iter().all(|c| node_type(c) == "tableHeader");
+            let texts = cells
+                .iter()
+                .map(|c| md_blocks(children(c), " ").replace('\n', " "))
+                .collect();
+            (is_header, texts)
+        })
+        .collect();
+
+    let width = rows.iter().map(|(_, cells)| cells.len()).max().unwrap_or(0);
+    if width == 0 {
+        return String::new();
+    }
+
+    let format_row = |cells: &[String]| {
+        let mut padded = cells.to_vec();
+        padded.resize(width, String::new());
+        format!("| {} |", padded.join(" | "))
+    };
+
+    let mut lines = Vec::new();
+    let mut body = rows.as_slice();
+    match rows.first() {
+        Some((true, header)) => {
+            lines.push(format_row(header));
+            body = &rows[1..];
+        }
+        // GFM tables need a header row, leave it blank when the table has none.
+        _ => lines.push(format_row(&[])),
+    }
+    lines.push(format!("|{}", " --- |".repeat(width)));
+    for (_, cells) in body {
+        lines.push(format_row(cells));
+    }
+    lines.join("\n")
+}
+
+fn md_card(node: &Value) -> String {
+    match attr_str(node, "url").and_then(safe_url) {
+        Some(url) => format!("<{}>", url),
+        None => String::new(),
+    }
+}
+
+fn md_inline(nodes: &[Value]) -> String {
+    let mut out = String::new();
+    for node in nodes {
+        match node_type(node) {
+            "text" => out.push_str(&md_text(node)),
+            "hardBreak" => out.push_str("\\\n"),
+            "mention" => out.push_str(&escape_markdown(&mention_text(node))),
+            "emoji" => out.push_str(&emoji_text(node)),
+            "date" => out.push_str(&date_text(node)),
+            "status" => out.push_str(&format!(
+                "**{}**",
+                escape_markdown(attr_str(node, "text").unwrap_or(""))
+            )),
+            "inlineCard" => out.push_str(&md_card(node)),
+            _ => out.push_str(&md_inline(children(node))),
+        }
+    }
+    out
+}
+
+fn md_text(node: &Value) -> String {
+    let text = node.get("text").and_then(|v| v.as_str()).unwrap_or("");
+    let marks: Vec<&Value> = node
+        .get("marks")
+        .and_then(|m| m.as_array())
+        .map(|m| m.iter().collect())
+        .unwrap_or_default();
+    let has = |name: &str| marks.iter().any(|m| node_type(m) == name);
+
+    let mut rendered = if has("code") {
+        let fence = if text.contains('`') { "``" } else { "`" };
+        let pad = if text.starts_with('`') || text.ends_with('`') { " " } else { "" };
+        format!("{}{}{}{}{}", fence, pad, text, pad, fence)
+    } else {
+        escape_markdown(text)
+    };
+
+    // Emphasis delimiters must hug non-whitespace, so keep surrounding spaces outside.
+    let (lead, core, trail) = split_whitespace_edges(&rendered);
+    if !core.is_empty() {
+        let mut core = core.to_string();
+        if has("em") {
+            core = format!("*{}*", core);
+        }
+        if has("strong") {
+            core = format!("**{}**", core);
+        }
+        if has("strike") {
+            core = format!("~~{}~~", core);
+        }
+        rendered = format!("{}{}{}", lead, core, trail);
+    }
+
+    let href = marks
+        .iter()
+        .find(|m| node_type(m) == "link")
+        .and_then(|m| attr_str(m, "href"))
+        .and_then(safe_url);
+    match href {
+        Some(url) => format!(
+            "[{}]({})",
+            rendered,
+            url.replace('(', "%28").replace(')', "%29").replace(' ', "%20")
+        ),
+        None => rendered,
+    }
+}
+
+fn split_whitespace_edges(text: &str) -> (&str, &str, &str) {
+    let trimmed_start = text.trim_start();
+    let lead = &text[..text.len() - trimmed_start.len()];
+    let core = trimmed_start.trim_end();
+    let trail = &trimmed_start[core.len()..];
+    (lead, core, trail)
+}
+
+// --- Markdown -> ADF ---
+
+/// Prefix for links that should become mentions, e.g. `[@Alex](accountid:5b10ac8d82e05b22cc7d4ef5)`.
+const MENTION_SCHEME: &str = "accountid:";
+
+/// Convert Markdown to an ADF document, for comments and worklog comments.
+///
+/// Supports paragraphs, headings, emphasis, strikethrough, inline code, fenced and
+/// indented code blocks, bullet and ordered lists, blockquotes, rules, links and
+/// mentions. Single line breaks are kept as hard breaks, since notes typed in the
+/// app are not wrapped by hand.
+pub fn from_markdown(markdown: &str) -> Value {
+    let mut builder = AdfBuilder::default();
+    for event in Parser::new_ext(markdown, Options::ENABLE_STRIKETHROUGH) {
+        builder.event(event);
+    }
+    builder.finish()
+}
+
+struct BlockNode {
+    node_type: &'static str,
+    attrs: Option<Value>,
+    content: Vec<Value>,
+    /// Paragraph opened to hold inline content that Markdown put straight into a
+    /// container (tight list items), closed as soon as another block starts.
+    implicit: bool,
+}
+
+impl BlockNode {
+    fn new(node_type: &'static str, attrs: Option<Value>) -> Self {
+        Self {
+            node_type,
+            attrs,
+            content: Vec::new(),
+            implicit: false,
+        }
+    }
+
+    fn into_value(self) -> Value {
+        let mut node = json!({ "type": self.node_type, "content": self.content });
+        if let Some(attrs) = self.attrs {
+            node["attrs"] = attrs;
+        }
+        node
+    }
+}
+
+struct AdfBuilder {
+    stack: Vec<BlockNode>,
+    marks: Vec<Value>,
+    /// Account id and collected label while inside a mention link.
+    mention: Option<(String, String)>,
+}
+
+impl Default for AdfBuilder {
+    fn default() -> Self {
+        Self {
+            stack: vec![BlockNode::new("doc", None)],
+            marks: Vec::new(),
+            mention: None,
+        }
+    }
+}
+
+impl AdfBuilder {
+    fn event(&mut self, event: Event) {
+        match event {
+            Event::Start(tag) => self.start(tag),
+            Event::End(tag) => self.end(tag),
+            Event::Text(text) => self.text(&text),
+            Event::Code(code) => self.code(&code),
+            Event::Html(html) | Event::InlineHtml(html) => self.text(&html),
+            Event::SoftBreak | Event::HardBreak => self.inline(json!({ "type": "hardBreak" })),
+            Event::Rule => self.leaf(json!({ "type": "rule" })),
+            _ => {}
+        }
+    }
+
+    fn start(&mut self, tag: Tag) {
+        match tag {
+            Tag::Paragraph | Tag::HtmlBlock => self.open("paragraph", None),
+            Tag::Heading { level, .. } => {
+                let level = match level {
+                    HeadingLevel::H1 => 1,
+                    HeadingLevel::H2 => 2,
+                    HeadingLevel::H3 => 3,
+                    HeadingLevel::H4 => 4,
+                    HeadingLevel::H5 => 5,
+                    HeadingLevel::H6 => 6,
+                };
+                self.open("heading", Some(json!({ "level": level })));
+            }
+            Tag::BlockQuote(_) => self.open("blockquote", None),
+            Tag::CodeBlock(kind) => {
+                let language = match kind {
+                    CodeBlockKind::Fenced(info) => info.split_whitespace().next().unwrap_or("").to_string(),
+                    CodeBlockKind::Indented => String::new(),
+                };
+                let attrs = (!language.is_empty()).then(|| json!({ "language": language }));
+                self.open("codeBlock", attrs);
+            }
+            Tag::List(Some(start)) => {
+                let attrs = (start != 1).then(|| json!({ "order": start }));
+                self.open("orderedList", attrs);
+            }
+            Tag::List(None) => self.open("bulletList", None),
+            Tag::Item => self.open("listItem", None),
+            Tag::Emphasis => self.marks.push(json!({ "type": "em" })),
+            Tag::Strong => self.marks.push(json!({ "type": "strong" })),
+            Tag::Strikethrough => self.marks.push(json!({ "type": "strike" })),
+            Tag::Superscript => self.marks.push(json!({ "type": "subsup", "attrs": { "type": "sup" } })),
+            Tag::Subscript => self.marks.push(json!({ "type": "subsup", "attrs": { "type": "sub" } })),
+            Tag::Link { dest_url, .. } if dest_url.starts_with(MENTION_SCHEME) => {
+                let id = dest_url[MENTION_SCHEME.len()..].to_string();
+                self.mention = Some((id, String::new()));
+            }
+            Tag::Link { dest_url, .. } | Tag::Image { dest_url, .. } => {
+                self.marks.push(json!({ "type": "link", "attrs": { "href": dest_url.to_string() } }));
+            }
+            _ => {}
+        }
+    }
+
+    fn end(&mut self, tag: TagEnd) {
+        match tag {
+            TagEnd::Paragraph
+            | TagEnd::HtmlBlock
+            | TagEnd::Heading(_)
+            | TagEnd::BlockQuote(_)
+            | TagEnd::CodeBlock
+            | TagEnd::List(_)
+            | TagEnd::Item => self.close(),
+            TagEnd::Emphasis
+            | TagEnd::Strong
+            | TagEnd::Strikethrough
+            | TagEnd::Superscript
+            | TagEnd::Subscript
+            | TagEnd::Image => {
+                self.marks.pop();
+            }
+            TagEnd::Link => match self.mention.take() {
+                Some((id, label)) => {
+                    let label = label.trim_start_matches('@');
+                    self.inline(json!({
+                        "type": "mention",
+                        "attrs": { "id": id, "text": format!("@{}", label) }
+                    }));
+                }
+                None => {
+                    self.marks.pop();
+                }
+            },
+            _ => {}
+        }
+    }
+
+    fn open(&mut self, node_type: &'static str, attrs: Option<Value>) {
+        self.close_implicit();
+        self.stack.push(BlockNode::new(node_type, attrs));
+    }
+
+    fn close(&mut self) {
+        self.close_implicit();
+        self.pop_into_parent();
+    }
+
+    fn close_implicit(&mut self) {
+        if self.stack.last().is_some_and(|b| b.implicit) {
+            self.pop_into_parent();
+        }
+    }
+
+    fn pop_into_parent(&mut self) {
+        if self.stack.len() < 2 {
+            return;
+        }
+        let mut block = self.stack.pop().unwrap();
+        if block.node_type == "codeBlock" {
+            // ADF wants a single text node without the trailing newline
+            let code: String = block
+                .content
+                .iter()
+                .filter_map(|t| t["text"].as_str())
+                .collect();
+            let code = code.trim_end_matches('\n');
+            block.content = if code.is_empty() {
+                Vec::new()
+            } else {
+                vec![json!({ "type": "text", "text": code })]
+            };
+        }
+        if let Some(parent) = self.stack.last_mut() {
+            parent.content.push(block.into_value());
+        }
+    }
+
+    fn leaf(&mut self, node: Value) {
+        self.close_implicit();
+        if let Some(top) = self.stack.last_mut() {
+            top.content.push(node);
+        }
+    }
+
+    fn inline(&mut self, node: Value) {
+        let needs_paragraph = self
+            .stack
+            .last()
+            .is_some_and(|b| matches!(b.node_type, "doc" | "listItem" | "blockquote"));
+        if needs_paragraph {
+            self.stack.push(BlockNode {
+                implicit: true,
+                ..BlockNode::new("paragraph", None)
+            });
+        }
+        if let Some(top) = self.stack.last_mut() {
+            top.content.push(node);
+        }
+    }
+
+    fn text(&mut self, text: &str) {
+        if text.is_empty() {
+            return;
+        }
+        if let Some((_, label)) = self.mention.as_mut() {
+            label.push_str(text);
+            return;
+        }
+        if self.stack.last().is_some_and(|b| b.node_type == "codeBlock") {
+            self.stack.last_mut().unwrap().content.push(json!({ "type": "text", "text": text }));
+            return;
+        }
+        let mut node = json!({ "type": "text", "text": text });
+        if !self.marks.is_empty() {
+            node["marks"] = Value::Array(self.marks.clone());
+        }
+        self.inline(node);
+    }
+
+    fn code(&mut self, code: &str) {
+        if code.is_empty() {
+            return;
+        }
+        if let Some((_, label)) = self.mention.as_mut() {
+            label.push_str(code);
+            return;
+        }
+        // The code mark may only be combined with a link
+        let mut marks: Vec<Value> = self
+            .marks
+            .iter()
+            .filter(|m| node_type(m) == "link")
+            .cloned()
+            .collect();
+        marks.push(json!({ "type": "code" }));
+        self.inline(json!({ "type": "text", "text": code, "marks": marks }));
+    }
+
+    fn finish(mut self) -> Value {
+        while self.stack.len() > 1 {
+            self.close();
+        }
+        let mut doc = self.stack.pop().unwrap().into_value();
+        doc["version"] = json!(1);
+        doc
+    }
+}
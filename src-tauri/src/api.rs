@@ -0,0 +1,303 @@
+use crate::config::ConfigState;
+use crate::ipc::{self, Failure, Request};
+use crate::reminders::notify;
+use crate::store;
+use chrono::{Local, NaiveDate};
+use serde::Deserialize;
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::net::Ipv4Addr;
+use std::path::PathBuf;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+
+const TOKEN_FILE: &str = "api-token";
+const MAX_HEAD_BYTES: usize = 16 * 1024;
+const MAX_BODY_BYTES: usize = 64 * 1024;
+/// How long a client has to send its whole request.
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The parts of an HTTP request the API looks at.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HttpRequest {
+    pub method: String,
+    /// Path and query string.
+    pub target: String,
+    pub authorization: Option<String>,
+    pub body: String,
+}
+
+/// Read one HTTP/1.1 request, headers and body. No more than the size
+/// limits is ever buffered, however long a line is.
+pub async fn read_request<R: AsyncBufRead + Unpin>(reader: &mut R) -> Result<HttpRequest, String> {
+    let mut head = Vec::new();
+    let mut line = String::new();
+    let mut head_reader = (&mut *reader).take(MAX_HEAD_BYTES as u64);
+    loop {
+        line.clear();
+        let read = head_reader.read_line(&mut line).await.map_err(|e| e.to_string())?;
+        if read == 0 && head_reader.limit() == 0 {
+            return Err("Request headers too large".to_string());
+        }
+        if read == 0 {
+            return Err("Incomplete request".to_string());
+        }
+        if line.trim_end().is_empty() {
+            break;
+        }
+        head.push(line.trim_end().to_string());
+    }
+
+    let request_line = head.first().ok_or("Empty request")?;
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return Err(format!("Invalid request line \"{}\"", request_line));
+    };
+
+    let mut authorization = None;
+    let mut content_length = 0;
+    for header in &head[1..] {
+        let Some((name, value)) = header.split_once(':') else {
+            continue;
+        };
+        match name.trim().to_ascii_lowercase().as_str() {
+            "authorization" => authorization = Some(value.trim().to_string()),
+            "content-length" => {
+                content_length = value.trim().parse().map_err(|_| "Invalid Content-Length")?;
+            }
+            _ => {}
+        }
+    }
+    if content_length > MAX_BODY_BYTES {
+        return Err("Request body too large".to_string());
+    }
+
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body).await.map_err(|e| e.to_string())?;
+    Ok(HttpRequest {
+        method: method.to_string(),
+        target: target.to_string(),
+        authorization,
+        body: String::from_utf8(body).map_err(|_| "Request body is not UTF-8")?,
+    })
+}
+
+/// Whether the `Authorization` header carries the install's token.
+pub fn authorized(authorization: Option<&str>, token: &str) -> bool {
+    let Some(given) = authorization.and_then(|a| a.strip_prefix("Bearer ")) else {
+        return false;
+    };
+    // Compared in full so the time taken says nothing about the token
+    given.len() == token.len()
+        && given
+            .bytes()
+            .zip(token.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct TimerBody {
+    issue_key: Option<String>,
+    comment: Option<String>,
+}
+
+fn query_param(query: &str, name: &str) -> Option<String> {
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _)| *key == name)
+        .and_then(|(_, value)| urlencoding::decode(value).ok())
+        .map(|value| value.into_owned())
+}
+
+/// Map a method and target to a request, or an HTTP status and error.
+///
+/// - `GET /timers`
+/// - `POST /timers/{start,pause,resume,stop,log}` with an optional
+///   `{"issue_key": ..., "comment": ...}` body; without an issue key, the
+///   active (or last paused) timer
+/// - `GET /worklogs?from=YYYY-MM-DD&to=YYYY-MM-DD`, today by default
+pub fn route(method: &str, target: &str, body: &str, today: NaiveDate) -> Result<Request, (u16, String)> {
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let bad_request = |e: String| (400, e);
+
+    match (method, path.trim_end_matches('/')) {
+        ("GET", "/timers") => Ok(Request::Status),
+        ("GET", "/worklogs") => Request::report(
+            query_param(query, "from").as_deref(),
+            query_param(query, "to").as_deref(),
+            today,
+        )
+        .map_err(bad_request),
+        ("POST", action) if action.starts_with("/timers/") => {
+            let body: TimerBody = if body.trim().is_empty() {
+                TimerBody::default()
+            } else {
+                serde_json::from_str(body).map_err(|e| bad_request(format!("Invalid body: {}", e)))?
+            };
+            let issue_key = body
+                .issue_key
+                .map(|k| k.trim().to_uppercase())
+                .filter(|k| !k.is_empty());
+
+            match &action["/timers/".len()..] {
+                "start" => Ok(Request::Start {
+                    issue_key: issue_key.ok_or_else(|| bad_request("Missing issue_key".to_string()))?,
+                }),
+                "pause" => Ok(Request::Pause { issue_key }),
+                "resume" => Ok(Request::Resume { issue_key }),
+                "stop" => Ok(Request::Stop { issue_key }),
+                "log" => Ok(Request::Log {
+                    issue_key,
+                    comment: body.comment.filter(|c| !c.trim().is_empty()),
+                }),
+                _ => Err((404, "Not found".to_string())),
+            }
+        }
+        (_, "/timers" | "/worklogs") => Err((405, "Method not allowed".to_string())),
+        _ => Err((404, "Not found".to_string())),
+    }
+}
+
+fn response(status: u16, body: &str) -> String {
+    let reason = match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        408 => "Request Timeout",
+        500 => "Internal Server Error",
+        502 => "Bad Gateway",
+        _ => "Error",
+    };
+    format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason,
+        body.len(),
+        body
+    )
+}
+
+fn error_body(error: &str) -> String {
+    serde_json::json!({ "error": error }).to_string()
+}
+
+pub fn token_path() -> Result<PathBuf, String> {
+    Ok(store::data_dir()?.join(TOKEN_FILE))
+}
+
+/// 32 bytes from the OS's secure random source, hex-encoded.
+pub fn generate_token() -> Result<String, String> {
+    let mut bytes = [0u8; 32];
+    getrandom::getrandom(&mut bytes).map_err(|e| format!("Cannot generate a token: {}", e))?;
+    Ok(bytes.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// The install's token, created on first use and readable only by the user.
+pub fn load_or_create_token() -> Result<String, String> {
+    let path = token_path()?;
+    match fs::read_to_string(&path) {
+        Ok(token) if !token.trim().is_empty() => return Ok(token.trim().to_string()),
+        Err(e) if e.kind() != ErrorKind::NotFound => return Err(e.to_string()),
+        _ => {}
+    }
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    let token = generate_token()?;
+    // Created with its final permissions, so it is never readable by others
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(&path).map_err(|e| e.to_string())?;
+    file.write_all(token.as_bytes()).map_err(|e| e.to_string())?;
+    Ok(token)
+}
+
+/// The status for a request that failed: Jira and network failures are the
+/// upstream's fault, a missing Jira setup the app's, and anything else
+/// (unknown timer, invalid value) the request's.
+pub fn error_status(failure: &Failure) -> u16 {
+    match failure {
+        Failure::Invalid(_) => 400,
+        Failure::NotConfigured(_) => 500,
+        Failure::Upstream(_) => 502,
+    }
+}
+
+async fn handle(app: &AppHandle, token: &str, http: HttpRequest) -> (u16, String) {
+    if !authorized(http.authorization.as_deref(), token) {
+        return (401, error_body("Missing or invalid token"));
+    }
+    let request = match route(&http.method, &http.target, &http.body, Local::now().date_naive()) {
+        Ok(request) => request,
+        Err((status, e)) => return (status, error_body(&e)),
+    };
+
+    match ipc::dispatch_in_app(app, request).await {
+        Ok(reply) => match serde_json::to_string(&reply) {
+            Ok(json) => (200, json),
+            Err(e) => (500, error_body(&e.to_string())),
+        },
+        Err(failure) => (error_status(&failure), error_body(failure.message())),
+    }
+}
+
+async fn serve(app: &AppHandle, token: &str, stream: TcpStream) -> std::io::Result<()> {
+    let (read, mut write) = stream.into_split();
+    // A client that sends nothing does not hold the connection forever
+    let (status, body) = match tokio::time::timeout(READ_TIMEOUT, read_request(&mut BufReader::new(read))).await {
+        Ok(Ok(http)) => handle(app, token, http).await,
+        Ok(Err(e)) => (400, error_body(&e)),
+        Err(_) => (408, error_body("Request timed out")),
+    };
+    write.write_all(response(status, &body).as_bytes()).await?;
+    write.shutdown().await
+}
+
+/// Serve the HTTP API on 127.0.0.1 when `API_PORT` is set.
+pub fn spawn(app: AppHandle) {
+    let port = app
+        .state::<ConfigState>()
+        .config
+        .lock()
+        .map(|c| c.api_port)
+        .unwrap_or(0);
+    if port == 0 {
+        return;
+    }
+    let token = match load_or_create_token() {
+        Ok(token) => token,
+        Err(e) => {
+            notify(&app, &format!("Local API disabled: {}", e));
+            return;
+        }
+    };
+
+    tauri::async_runtime::spawn(async move {
+        let listener = match TcpListener::bind((Ipv4Addr::LOCALHOST, port)).await {
+            Ok(listener) => listener,
+            Err(e) => {
+                notify(&app, &format!("Local API cannot listen on port {}: {}", port, e));
+                return;
+            }
+        };
+        while let Ok((stream, _)) = listener.accept().await {
+            let app = app.clone();
+            let token = token.clone();
+            tauri::async_runtime::spawn(async move {
+                let _ = serve(&app, &token, stream).await;
+            });
+        }
+    });
+}
@@ -0,0 +1,794 @@
+use crate::config::ConfigState;
+use crate::drafts::{self, DraftStore};
+use crate::report::parse_date;
+use crate::store;
+use crate::worklog::WorklogRequest;
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, NaiveDateTime, TimeZone, Utc, Weekday};
+use chrono_tz::Tz;
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::AppHandle;
+
+const IMPORTED_FILE: &str = "calendar-imported.json";
+
+/// Occurrences generated per recurring event at most, so an endless rule
+/// can't stall the import.
+const MAX_OCCURRENCES: usize = 5000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleField {
+    Title,
+    Organizer,
+    Category,
+}
+
+/// Maps an event to `issue_key` when `pattern`, a case-insensitive regex,
+/// matches `field`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CalendarRule {
+    pub field: RuleField,
+    pub pattern: String,
+    pub issue_key: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CalendarConfig {
+    /// Path or URL (`https://`, `webcal://`) of the `.ics` file.
+    #[serde(default)]
+    pub source: String,
+    /// Tried in order, the first match wins.
+    #[serde(default)]
+    pub rules: Vec<CalendarRule>,
+}
+
+impl CalendarConfig {
+    /// Read `ICS_SOURCE` and `ICS_RULES`. Invalid rules are ignored.
+    pub fn from_env() -> Self {
+        Self {
+            source: env::var("ICS_SOURCE").unwrap_or_default(),
+            rules: env::var("ICS_RULES")
+                .ok()
+                .and_then(|v| parse_rules(&v).ok())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+fn compile(pattern: &str) -> Result<Regex, String> {
+    RegexBuilder::new(pattern)
+        .case_insensitive(true)
+        .build()
+        .map_err(|e| format!("Invalid pattern \"{}\": {}", pattern, e))
+}
+
+/// Parse `field:pattern=ISSUE-KEY` rules separated by new lines or `;`, e.g.
+/// `title:stand-?up=OPS-12; organizer:@client\.com=CLI-3`.
+pub fn parse_rules(value: &str) -> Result<Vec<CalendarRule>, String> {
+    let mut rules = Vec::new();
+    for entry in value.split(['\n', ';']).map(str::trim).filter(|e| !e.is_empty()) {
+        let invalid = || format!("Expected field:pattern=ISSUE-KEY in \"{}\"", entry);
+        let (field, rest) = entry.split_once(':').ok_or_else(invalid)?;
+        let field = match field.trim().to_lowercase().as_str() {
+            "title" => RuleField::Title,
+            "organizer" => RuleField::Organizer,
+            "category" => RuleField::Category,
+            other => return Err(format!("Unknown rule field \"{}\"", other)),
+        };
+        let (pattern, issue_key) = rest.rsplit_once('=').ok_or_else(invalid)?;
+        let (pattern, issue_key) = (pattern.trim(), issue_key.trim());
+        if pattern.is_empty() || issue_key.is_empty() {
+            return Err(invalid());
+        }
+        compile(pattern)?;
+        rules.push(CalendarRule {
+            field,
+            pattern: pattern.to_string(),
+            issue_key: issue_key.to_uppercase(),
+        });
+    }
+    Ok(rules)
+}
+
+/// One occurrence of a calendar event.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CalendarEvent {
+    pub uid: String,
+    pub title: String,
+    /// `Name <email>`, or just the email.
+    pub organizer: String,
+    pub categories: Vec<String>,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    /// A `TZID` that is not an IANA name (e.g. a Windows one), read as local
+    /// time so the start may be off.
+    pub unknown_tzid: Option<String>,
+}
+
+struct Property {
+    name: String,
+    params: Vec<(String, String)>,
+    value: String,
+}
+
+impl Property {
+    fn parse(line: &str) -> Option<Self> {
+        let mut in_quotes = false;
+        let colon = line.char_indices().find_map(|(i, c)| {
+            if c == '"' {
+                in_quotes = !in_quotes;
+            }
+            (c == ':' && !in_quotes).then_some(i)
+        })?;
+        let mut head = line[..colon].split(';');
+        let name = head.next()?.trim().to_uppercase();
+        let params = head
+            .filter_map(|p| p.split_once('='))
+            .map(|(k, v)| (k.trim().to_uppercase(), v.trim_matches('"').to_string()))
+            .collect();
+        Some(Self {
+            name,
+            params,
+            value: line[colon + 1..].to_string(),
+        })
+    }
+
+    fn param(&self, name: &str) -> Option<&str> {
+        self.params.iter().find(|(k, _)| k == name).map(|(_, v)| v.as_str())
+    }
+
+    fn text(&self) -> String {
+        let mut text = String::new();
+        let mut chars = self.value.chars();
+        while let Some(c) = chars.next() {
+            if c != '\\' {
+                text.push(c);
+                continue;
+            }
+            match chars.next() {
+                Some('n' | 'N') => text.push('\n'),
+                Some(escaped) => text.push(escaped),
+                None => {}
+            }
+        }
+        text
+    }
+}
+
+/// Continuation lines start with a space or a tab.
+fn unfold(content: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in content.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+/// The time zone an event's wall-clock times are in.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Zone {
+    /// Floating times, and `TZID`s that are not IANA names.
+    Local,
+    Named(Tz),
+}
+
+impl Zone {
+    fn to_utc(self, time: NaiveDateTime) -> DateTime<Utc> {
+        fn resolve<Z: TimeZone>(zone: &Z, time: NaiveDateTime) -> DateTime<Utc> {
+            zone.from_local_datetime(&time)
+                .earliest()
+                .map(|t| t.with_timezone(&Utc))
+                // Skipped by a DST change
+                .unwrap_or_else(|| time.and_utc())
+        }
+        match self {
+            Zone::Local => resolve(&Local, time),
+            Zone::Named(tz) => resolve(&tz, time),
+        }
+    }
+
+    fn wall_clock(self, time: DateTime<Utc>) -> NaiveDateTime {
+        match self {
+            Zone::Local => time.with_timezone(&Local).naive_local(),
+            Zone::Named(tz) => time.with_timezone(&tz).naive_local(),
+        }
+    }
+}
+
+/// A date-time value, `None` for an all-day date. `Z` times are in UTC,
+/// others in their `TZID`'s zone, else in local time.
+fn zoned_time(value: &str, tzid: Option<&str>) -> Option<(NaiveDateTime, Zone)> {
+    let value = value.trim();
+    if let Some(utc) = value.strip_suffix('Z') {
+        let utc = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok()?;
+        return Some((utc, Zone::Named(Tz::UTC)));
+    }
+    let time = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?;
+    let zone = tzid.and_then(|id| id.parse::<Tz>().ok()).map_or(Zone::Local, Zone::Named);
+    Some((time, zone))
+}
+
+/// An instant from a date-time property.
+fn instant(property: &Property, value: &str) -> Option<DateTime<Utc>> {
+    zoned_time(value, property.param("TZID")).map(|(time, zone)| zone.to_utc(time))
+}
+
+/// An RFC 5545 duration such as `PT1H30M` or `P1D`.
+pub fn parse_duration(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    let (negative, value) = match value.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, value.strip_prefix('+').unwrap_or(value)),
+    };
+    let mut total = Duration::zero();
+    let mut number = String::new();
+    let mut in_time = false;
+    for c in value.strip_prefix('P')?.chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'T' => in_time = true,
+            _ => {
+                let n: i64 = number.parse().ok()?;
+                number.clear();
+                total += match (c, in_time) {
+                    ('W', false) => Duration::weeks(n),
+                    ('D', false) => Duration::days(n),
+                    ('H', true) => Duration::hours(n),
+                    ('M', true) => Duration::minutes(n),
+                    ('S', true) => Duration::seconds(n),
+                    _ => return None,
+                };
+            }
+        }
+    }
+    Some(if negative { -total } else { total })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Frequency {
+    Daily,
+    Weekly,
+}
+
+/// `MO`, `TU`... Ordinals such as `1MO` only make sense for monthly rules
+/// and are ignored.
+fn weekday(code: &str) -> Option<Weekday> {
+    match code.trim().to_uppercase().as_str() {
+        "MO" => Some(Weekday::Mon),
+        "TU" => Some(Weekday::Tue),
+        "WE" => Some(Weekday::Wed),
+        "TH" => Some(Weekday::Thu),
+        "FR" => Some(Weekday::Fri),
+        "SA" => Some(Weekday::Sat),
+        "SU" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+struct Recurrence {
+    frequency: Frequency,
+    interval: i64,
+    count: Option<usize>,
+    until: Option<NaiveDateTime>,
+    by_day: Vec<Weekday>,
+}
+
+impl Recurrence {
+    /// Daily and weekly rules; others are not expanded and the event only
+    /// counts once. Occurrences are wall-clock times in `zone`, the start's.
+    fn parse(value: &str, zone: Zone) -> Option<Self> {
+        let mut recurrence = Self {
+            frequency: Frequency::Daily,
+            interval: 1,
+            count: None,
+            until: None,
+            by_day: Vec::new(),
+        };
+        let mut frequency = None;
+        for part in value.split(';') {
+            let (key, value) = part.split_once('=')?;
+            match key.to_uppercase().as_str() {
+                "FREQ" => {
+                    frequency = match value.to_uppercase().as_str() {
+                        "DAILY" => Some(Frequency::Daily),
+                        "WEEKLY" => Some(Frequency::Weekly),
+                        _ => return None,
+                    }
+                }
+                "INTERVAL" => recurrence.interval = value.parse().ok().filter(|i| *i > 0)?,
+                "COUNT" => recurrence.count = value.parse().ok(),
+                "UNTIL" => {
+                    recurrence.until = zoned_time(value, None)
+                        .map(|(time, until_zone)| match until_zone {
+                            // In UTC when the start has a time zone
+                            Zone::Named(_) => zone.wall_clock(until_zone.to_utc(time)),
+                            Zone::Local => time,
+                        })
+                        .or_else(|| {
+                            NaiveDate::parse_from_str(value, "%Y%m%d")
+                                .ok()
+                                .and_then(|d| d.and_hms_opt(23, 59, 59))
+                        })
+                }
+                "BYDAY" => {
+                    recurrence.by_day = value.split(',').filter_map(weekday).collect();
+                    recurrence.by_day.sort_by_key(|d: &Weekday| d.num_days_from_monday());
+                }
+                _ => {}
+            }
+        }
+        recurrence.frequency = frequency?;
+        Some(recurrence)
+    }
+
+    /// Occurrence starts from `start`, before `limit`.
+    fn occurrences(&self, start: NaiveDateTime, limit: NaiveDateTime) -> Vec<NaiveDateTime> {
+        let mut starts = Vec::new();
+        let days = match (self.frequency, self.by_day.is_empty()) {
+            (Frequency::Weekly, false) => self.by_day.clone(),
+            _ => vec![start.weekday()],
+        };
+        let week_start = start.date() - Duration::days(start.weekday().num_days_from_monday() as i64);
+
+        for period in 0..MAX_OCCURRENCES as i64 {
+            let candidates: Vec<NaiveDateTime> = match self.frequency {
+                Frequency::Daily => Some(start + Duration::days(period * self.interval))
+                    // BYDAY narrows a daily rule, e.g. to weekdays
+                    .filter(|t| self.by_day.is_empty() || self.by_day.contains(&t.weekday()))
+                    .into_iter()
+                    .collect(),
+                Frequency::Weekly => days
+                    .iter()
+                    .map(|d| {
+                        let date = week_start
+                            + Duration::weeks(period * self.interval)
+                            + Duration::days(d.num_days_from_monday() as i64);
+                        date.and_time(start.time())
+                    })
+                    .filter(|t| *t >= start)
+                    .collect(),
+            };
+            for t in candidates {
+                let done = t >= limit
+                    || self.until.is_some_and(|until| t > until)
+                    || self.count.is_some_and(|count| starts.len() >= count);
+                if done {
+                    return starts;
+                }
+                starts.push(t);
+            }
+        }
+        starts
+    }
+}
+
+#[derive(Default)]
+struct RawEvent {
+    uid: String,
+    title: String,
+    organizer: String,
+    categories: Vec<String>,
+    /// Wall-clock time in `zone`.
+    start: Option<NaiveDateTime>,
+    zone: Option<Zone>,
+    unknown_tzid: Option<String>,
+    end: Option<DateTime<Utc>>,
+    duration: Option<Duration>,
+    all_day: bool,
+    cancelled: bool,
+    recurrence: Option<String>,
+    exdates: Vec<DateTime<Utc>>,
+    recurrence_id: Option<DateTime<Utc>>,
+}
+
+impl RawEvent {
+    fn apply(&mut self, property: &Property) {
+        let is_date = property.param("VALUE") == Some("DATE") || property.value.trim().len() == 8;
+        match property.name.as_str() {
+            "UID" => self.uid = property.text(),
+            "SUMMARY" => self.title = property.text(),
+            "ORGANIZER" => {
+                let value = property.value.trim();
+                let email = value
+                    .get(..7)
+                    .filter(|p| p.eq_ignore_ascii_case("mailto:"))
+                    .map_or(value, |_| &value[7..]);
+                self.organizer = match property.param("CN") {
+                    Some(name) => format!("{} <{}>", name, email),
+                    None => email.to_string(),
+                };
+            }
+            "CATEGORIES" => self
+                .categories
+                .extend(property.text().split(',').map(|c| c.trim().to_string()).filter(|c| !c.is_empty())),
+            "DTSTART" => {
+                self.all_day = is_date;
+                let tzid = property.param("TZID");
+                if let Some((start, zone)) = zoned_time(&property.value, tzid) {
+                    self.start = Some(start);
+                    self.zone = Some(zone);
+                    self.unknown_tzid = tzid.filter(|_| zone == Zone::Local).map(str::to_string);
+                }
+            }
+            "DTEND" => self.end = instant(property, &property.value),
+            "DURATION" => self.duration = parse_duration(&property.value),
+            "STATUS" => self.cancelled = property.value.trim().eq_ignore_ascii_case("CANCELLED"),
+            "RRULE" => self.recurrence = Some(property.value.trim().to_string()),
+            "EXDATE" => self
+                .exdates
+                .extend(property.value.split(',').filter_map(|v| instant(property, v))),
+            "RECURRENCE-ID" => self.recurrence_id = instant(property, &property.value),
+            _ => {}
+        }
+    }
+}
+
+fn parse_events(content: &str) -> Result<Vec<RawEvent>, String> {
+    let lines = unfold(content);
+    if !lines.iter().any(|l| l.trim().eq_ignore_ascii_case("BEGIN:VCALENDAR")) {
+        return Err("Not an iCalendar file".to_string());
+    }
+
+    let mut events = Vec::new();
+    let mut current: Option<RawEvent> = None;
+    // Alarms and other components nested in an event have their own DTSTART
+    let mut nested = 0;
+    for line in &lines {
+        let Some(property) = Property::parse(line) else {
+            continue;
+        };
+        let component = property.value.trim().to_uppercase();
+        match (property.name.as_str(), component.as_str()) {
+            ("BEGIN", "VEVENT") => current = Some(RawEvent::default()),
+            ("END", "VEVENT") => events.extend(current.take()),
+            ("BEGIN", _) if current.is_some() => nested += 1,
+            ("END", _) if current.is_some() => nested -= 1,
+            _ => {
+                if let (Some(event), 0) = (current.as_mut(), nested) {
+                    event.apply(&property);
+                }
+            }
+        }
+    }
+    Ok(events)
+}
+
+/// Timed events starting between the local dates `from` and `to`
+/// (inclusive), recurring ones expanded. All-day and cancelled events are
+/// left out.
+pub fn events_between(content: &str, from: NaiveDate, to: NaiveDate) -> Result<Vec<CalendarEvent>, String> {
+    let range_start = Zone::Local.to_utc(from.and_time(Default::default()));
+    let range_end = Zone::Local.to_utc((to + Duration::days(1)).and_time(Default::default()));
+    let raw = parse_events(content)?;
+
+    // Occurrences moved or cancelled individually replace the recurring ones
+    let overridden: HashSet<(&str, DateTime<Utc>)> = raw
+        .iter()
+        .filter_map(|e| e.recurrence_id.map(|id| (e.uid.as_str(), id)))
+        .collect();
+
+    let mut events = Vec::new();
+    for event in &raw {
+        let Some(start) = event.start.filter(|_| !event.all_day && !event.cancelled) else {
+            continue;
+        };
+        let zone = event.zone.unwrap_or(Zone::Local);
+        let length = match (event.end, event.duration) {
+            (Some(end), _) => end - zone.to_utc(start),
+            (None, Some(duration)) => duration,
+            (None, None) => Duration::zero(),
+        };
+        if length <= Duration::zero() {
+            continue;
+        }
+
+        let recurrence = event
+            .recurrence
+            .as_deref()
+            .filter(|_| event.recurrence_id.is_none())
+            .and_then(|r| Recurrence::parse(r, zone));
+        let starts: Vec<DateTime<Utc>> = match recurrence {
+            Some(recurrence) => recurrence
+                .occurrences(start, zone.wall_clock(range_end))
+                .into_iter()
+                .map(|t| zone.to_utc(t))
+                .filter(|t| !event.exdates.contains(t) && !overridden.contains(&(event.uid.as_str(), *t)))
+                .collect(),
+            None => vec![zone.to_utc(start)],
+        };
+
+        for t in starts.into_iter().filter(|t| *t >= range_start && *t < range_end) {
+            events.push(CalendarEvent {
+                uid: event.uid.clone(),
+                title: event.title.clone(),
+                organizer: event.organizer.clone(),
+                categories: event.categories.clone(),
+                start: t,
+                end: t + length,
+                unknown_tzid: event.unknown_tzid.clone(),
+            });
+        }
+    }
+    events.sort_by_key(|e| e.start);
+    Ok(events)
+}
+
+/// A worklog suggested from an event, to review before logging.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CalendarProposal {
+    pub uid: String,
+    pub title: String,
+    pub started: DateTime<Utc>,
+    pub seconds: u64,
+    /// From the first matching rule, `None` when no rule matched.
+    pub issue_key: Option<String>,
+    /// See [`CalendarEvent::unknown_tzid`].
+    pub unknown_tzid: Option<String>,
+    /// Already logged or drafted by an earlier import.
+    pub imported: bool,
+}
+
+pub fn propose(events: &[CalendarEvent], rules: &[CalendarRule]) -> Result<Vec<CalendarProposal>, String> {
+    let compiled = rules
+        .iter()
+        .map(|rule| compile(&rule.pattern).map(|regex| (rule, regex)))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(events
+        .iter()
+        .map(|event| {
+            let issue_key = compiled
+                .iter()
+                .find(|(rule, regex)| match rule.field {
+                    RuleField::Title => regex.is_match(&event.title),
+                    RuleField::Organizer => regex.is_match(&event.organizer),
+                    RuleField::Category => event.categories.iter().any(|c| regex.is_match(c)),
+                })
+                .map(|(rule, _)| rule.issue_key.clone());
+            CalendarProposal {
+                uid: event.uid.clone(),
+                title: event.title.clone(),
+                started: event.start,
+                seconds: (event.end - event.start).num_seconds().max(0) as u64,
+                issue_key,
+                unknown_tzid: event.unknown_tzid.clone(),
+                imported: false,
+            }
+        })
+        .collect())
+}
+
+/// An event occurrence that was logged or drafted, so that importing the
+/// same days again does not log it twice.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImportedEvent {
+    pub uid: String,
+    pub started: DateTime<Utc>,
+}
+
+pub fn imported_path() -> Result<PathBuf, String> {
+    Ok(store::data_dir()?.join(IMPORTED_FILE))
+}
+
+#[derive(Default)]
+pub struct ImportedEvents {
+    events: Mutex<Vec<ImportedEvent>>,
+    /// Where events are written on every change, `None` keeps them in memory.
+    path: Option<PathBuf>,
+}
+
+impl ImportedEvents {
+    pub fn new() -> Self {
+        Self {
+            events: Mutex::new(Vec::new()),
+            path: None,
+        }
+    }
+
+    /// Events kept in `path`, which is created on the first change.
+    pub fn open(path: PathBuf) -> Self {
+        let events = match fs::read_to_string(&path) {
+            Ok(json) => serde_json::from_str(&json).unwrap_or_else(|_| {
+                // Set aside rather than overwritten by the next change
+                let _ = fs::rename(&path, path.with_extension("invalid.json"));
+                Vec::new()
+            }),
+            Err(_) => Vec::new(),
+        };
+        Self {
+            events: Mutex::new(events),
+            path: Some(path),
+        }
+    }
+
+    pub fn contains(&self, uid: &str, started: DateTime<Utc>) -> Result<bool, String> {
+        let events = self.events.lock().map_err(|e| e.to_string())?;
+        Ok(events.iter().any(|e| e.uid == uid && e.started == started))
+    }
+
+    /// Record events as imported, or none of them when that can't be saved.
+    pub fn add(&self, imported: Vec<ImportedEvent>) -> Result<(), String> {
+        let mut events = self.events.lock().map_err(|e| e.to_string())?;
+        let before = events.len();
+        events.extend(imported);
+        if let Some(path) = &self.path {
+            let saved = serde_json::to_string_pretty(&*events)
+                .map_err(|e| e.to_string())
+                .and_then(|json| store::write_atomic(path, &json));
+            if let Err(e) = saved {
+                events.truncate(before);
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A reviewed worklog from an event occurrence. `started` is the occurrence's.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CalendarWorklog {
+    pub uid: String,
+    #[serde(flatten)]
+    pub request: WorklogRequest,
+}
+
+impl CalendarWorklog {
+    fn imported(&self) -> ImportedEvent {
+        ImportedEvent {
+            uid: self.uid.clone(),
+            started: self.request.started,
+        }
+    }
+}
+
+async fn read_source(source: &str) -> Result<String, String> {
+    let source = source.trim();
+    if source.is_empty() {
+        return Err("No calendar configured. Please set the ICS source in Settings.".to_string());
+    }
+    let url = match source.strip_prefix("webcal://") {
+        Some(rest) => Some(format!("https://{}", rest)),
+        None if source.starts_with("http://") || source.starts_with("https://") => Some(source.to_string()),
+        None => None,
+    };
+
+    match url {
+        Some(url) => reqwest::get(&url)
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| format!("Calendar request failed: {}", e))?
+            .text()
+            .await
+            .map_err(|e| e.to_string()),
+        None => fs::read_to_string(source).map_err(|e| format!("{}: {}", source, e)),
+    }
+}
+
+#[tauri::command]
+pub fn get_calendar_import(config_state: tauri::State<'_, ConfigState>) -> Result<CalendarConfig, String> {
+    let config = config_state.config.lock().map_err(|e| e.to_string())?;
+    Ok(config.calendar.clone())
+}
+
+/// `rules` in the [`parse_rules`] format.
+#[tauri::command]
+pub fn save_calendar_import(
+    config_state: tauri::State<'_, ConfigState>,
+    source: String,
+    rules: String,
+) -> Result<(), String> {
+    let rules = parse_rules(&rules)?;
+    let mut config = config_state.config.lock().map_err(|e| e.to_string())?;
+    config.calendar = CalendarConfig {
+        source: source.trim().to_string(),
+        rules,
+    };
+    Ok(())
+}
+
+/// Worklogs suggested from the calendar's events between two `YYYY-MM-DD`
+/// dates, the ones imported before marked as such.
+#[tauri::command]
+pub async fn preview_calendar(
+    config_state: tauri::State<'_, ConfigState>,
+    imported: tauri::State<'_, ImportedEvents>,
+    from: String,
+    to: String,
+) -> Result<Vec<CalendarProposal>, String> {
+    let config = config_state
+        .config
+        .lock()
+        .map_err(|e| e.to_string())?
+        .calendar
+        .clone();
+    let (from, to) = (parse_date(&from)?, parse_date(&to)?);
+
+    let content = read_source(&config.source).await?;
+    let events = events_between(&content, from, to)?;
+    let mut proposals = propose(&events, &config.rules)?;
+    for proposal in &mut proposals {
+        proposal.imported = imported.contains(&proposal.uid, proposal.started)?;
+    }
+    Ok(proposals)
+}
+
+/// Log the reviewed worklogs one by one, rounded like timers. Returns each
+/// one's error, `None` when it was logged, so the failed ones can be fixed
+/// and sent again. Occurrences imported before are not logged again.
+#[tauri::command]
+pub async fn log_calendar_worklogs(
+    config_state: tauri::State<'_, ConfigState>,
+    imported: tauri::State<'_, ImportedEvents>,
+    worklogs: Vec<CalendarWorklog>,
+) -> Result<Vec<Option<String>>, String> {
+    let (client, rounding) = {
+        let config = config_state.config.lock().map_err(|e| e.to_string())?;
+        (config.jira_client()?, config.rounding.clone())
+    };
+
+    let mut results = Vec::new();
+    for mut worklog in worklogs {
+        if imported.contains(&worklog.uid, worklog.request.started)? {
+            results.push(Some("Already imported".to_string()));
+            continue;
+        }
+        rounding.apply(std::slice::from_mut(&mut worklog.request));
+        let request = &worklog.request;
+        let result = client
+            .log_worklog(
+                &request.issue_key,
+                request.seconds,
+                Some(request.started),
+                request.comment.as_deref(),
+            )
+            .await;
+        if result.is_ok() {
+            // Logged either way, so a failure to record it is only reported
+            if let Err(e) = imported.add(vec![worklog.imported()]) {
+                results.push(Some(format!("Logged, but not recorded as imported: {}", e)));
+                continue;
+            }
+        }
+        results.push(result.err());
+    }
+    Ok(results)
+}
+
+/// Add the reviewed worklogs as drafts, rounded like timers, leaving out
+/// occurrences imported before. Returns the drafts' ids.
+#[tauri::command]
+pub fn draft_calendar_worklogs(
+    app: AppHandle,
+    config_state: tauri::State<'_, ConfigState>,
+    imported: tauri::State<'_, ImportedEvents>,
+    drafts: tauri::State<'_, DraftStore>,
+    worklogs: Vec<CalendarWorklog>,
+) -> Result<Vec<u32>, String> {
+    let rounding = config_state.config.lock().map_err(|e| e.to_string())?.rounding.clone();
+    let mut new = Vec::new();
+    for worklog in worklogs {
+        if !imported.contains(&worklog.uid, worklog.request.started)? {
+            new.push(worklog);
+        }
+    }
+    let mut requests: Vec<WorklogRequest> = new.iter().map(|w| w.request.clone()).collect();
+    rounding.apply(&mut requests);
+
+    let ids = drafts.add(requests, Utc::now())?;
+    if let Err(e) = imported.add(new.iter().map(CalendarWorklog::imported).collect()) {
+        for id in ids {
+            let _ = drafts.remove(id);
+        }
+        return Err(e);
+    }
+    drafts::emit_changed(&app, &drafts);
+    Ok(ids)
+}
@@ -0,0 +1,223 @@
+use crate::config::ConfigState;
+use crate::drafts::{self, DraftStore};
+use crate::ipc::{self, Core, Reply, Request};
+use crate::reminders::format_hours;
+use crate::store::{self, SavedSession};
+use crate::timer::TimerState;
+use crate::worklog::WorklogQueue;
+use chrono::{Local, NaiveDate, Utc};
+
+pub const USAGE: &str = "Usage: jira-timesheet [command]
+
+Commands:
+  status                        List the timers
+  start <KEY>                   Start a timer on an issue
+  pause [KEY]                   Pause a timer, the active one by default
+  resume [KEY]                  Resume a timer, the last paused by default
+  stop [KEY]                    Stop a timer without logging it
+  log [KEY] [-m <comment>]      Stop a timer and log it to Jira
+  report [FROM [TO]]            Your worklogs between two dates (YYYY-MM-DD), today by default
+  transition <KEY> <STATUS>     Move an issue to another status
+  help                          Show this help
+
+Without a command the app is opened. Commands go to the running app, or
+to the timers it kept when it was last closed.";
+
+fn issue_key(value: &str) -> String {
+    value.trim().to_uppercase()
+}
+
+fn at_most(args: &[String], count: usize) -> Result<(), String> {
+    match args.get(count) {
+        Some(extra) => Err(format!("Unexpected argument \"{}\"", extra)),
+        None => Ok(()),
+    }
+}
+
+/// Parse the arguments after the program name. `today` is the default
+/// report date.
+pub fn parse_args(args: &[String], today: NaiveDate) -> Result<Request, String> {
+    let (command, rest) = args.split_first().ok_or(USAGE)?;
+    let optional_key = || at_most(rest, 1).map(|_| rest.first().map(|k| issue_key(k)));
+    let required_key = || {
+        rest.first()
+            .map(|k| issue_key(k))
+            .ok_or_else(|| format!("Missing issue key for {}", command))
+    };
+
+    match command.as_str() {
+        "status" => at_most(rest, 0).map(|_| Request::Status),
+        "start" => {
+            at_most(rest, 1)?;
+            Ok(Request::Start {
+                issue_key: required_key()?,
+            })
+        }
+        "pause" => Ok(Request::Pause {
+            issue_key: optional_key()?,
+        }),
+        "resume" => Ok(Request::Resume {
+            issue_key: optional_key()?,
+        }),
+        "stop" => Ok(Request::Stop {
+            issue_key: optional_key()?,
+        }),
+        "log" => {
+            let mut key = None;
+            let mut comment = None;
+            let mut args = rest.iter();
+            while let Some(arg) = args.next() {
+                match arg.as_str() {
+                    "-m" | "--message" => {
+                        let text = args.next().ok_or("Missing comment after -m")?;
+                        comment = Some(text.clone()).filter(|c| !c.trim().is_empty());
+                    }
+                    _ if key.is_none() => key = Some(issue_key(arg)),
+                    _ => return Err(format!("Unexpected argument \"{}\"", arg)),
+                }
+            }
+            Ok(Request::Log {
+                issue_key: key,
+                comment,
+            })
+        }
+        "report" => {
+            at_most(rest, 2)?;
+            Request::report(rest.first().map(String::as_str), rest.get(1).map(String::as_str), today)
+        }
+        "transition" => {
+            let issue_key = required_key()?;
+            let status = rest[1..].join(" ");
+            if status.trim().is_empty() {
+                return Err(format!("Missing status for {}", issue_key));
+            }
+            Ok(Request::Transition { issue_key, status })
+        }
+        other => Err(format!("Unknown command \"{}\"\n\n{}", other, USAGE)),
+    }
+}
+
+/// Plain text for the terminal.
+pub fn format_reply(reply: &Reply) -> String {
+    match reply {
+        Reply::Timers { timers } if timers.is_empty() => "No timers".to_string(),
+        Reply::Timers { timers } => timers
+            .iter()
+            .map(|view| {
+                format!(
+                    "{}\t{}\t{}\t{}",
+                    view.timer.issue_key,
+                    format_hours(view.elapsed_seconds),
+                    if view.paused { "paused" } else { "running" },
+                    view.timer.summary
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        Reply::Logged {
+            issue_key,
+            seconds,
+            queued,
+        } => {
+            let mut text = format!("Logged {} on {}", format_hours(*seconds), issue_key);
+            if *queued > 0 {
+                text.push_str(&format!(", {} worklog(s) queued for retry", queued));
+            }
+            text
+        }
+        Reply::Drafted { issue_key, seconds } => {
+            format!("Saved {} on {} as draft", format_hours(*seconds), issue_key)
+        }
+        Reply::Worklogs { entries } if entries.is_empty() => "No worklogs".to_string(),
+        Reply::Worklogs { entries } => {
+            let mut lines: Vec<String> = entries
+                .iter()
+                .map(|e| {
+                    format!(
+                        "{}\t{}\t{}\t{}",
+                        e.date,
+                        e.issue_key,
+                        format_hours(e.time_spent_seconds),
+                        e.summary
+                    )
+                })
+                .collect();
+            let total: u64 = entries.iter().map(|e| e.time_spent_seconds).sum();
+            lines.push(format!("Total\t{}", format_hours(total)));
+            lines.join("\n")
+        }
+        Reply::Transitioned { issue_key, status } => format!("{} moved to {}", issue_key, status),
+    }
+}
+
+/// Without the app, requests run on the saved session, written back after.
+async fn run_offline(request: Request) -> Result<Reply, String> {
+    let path = store::session_path()?;
+    let timers = TimerState::new();
+    let queue = WorklogQueue::new();
+    if let Some(session) = store::load(&path)? {
+        timers.restore(session.timers)?;
+        queue.restore(session.pending)?;
+    }
+    let drafts = DraftStore::open(drafts::drafts_path()?);
+    let config = ConfigState::new().config.into_inner().map_err(|e| e.to_string())?;
+    let changes = request.changes_timers();
+
+    let now = Utc::now();
+    let core = Core {
+        timers: &timers,
+        queue: &queue,
+        drafts: &drafts,
+        config: &config,
+    };
+    let reply = core.dispatch(request, now).await.map_err(String::from);
+    if changes {
+        let session = SavedSession {
+            timers: timers.timers.lock().map_err(|e| e.to_string())?.clone(),
+            pending: queue.list()?,
+            saved_at: now,
+        };
+        store::save(&path, &session)?;
+    }
+    reply
+}
+
+async fn execute(request: Request) -> Result<Reply, String> {
+    match ipc::send(&request).await {
+        Some(reply) => reply,
+        None => run_offline(request).await,
+    }
+}
+
+/// Run a command and return the process exit code.
+pub fn run(args: &[String]) -> i32 {
+    if matches!(args.first().map(String::as_str), Some("help" | "--help" | "-h")) {
+        println!("{}", USAGE);
+        return 0;
+    }
+    let request = match parse_args(args, Local::now().date_naive()) {
+        Ok(request) => request,
+        Err(e) => {
+            eprintln!("{}", e);
+            return 2;
+        }
+    };
+    let runtime = match tokio::runtime::Runtime::new() {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            eprintln!("{}", e);
+            return 1;
+        }
+    };
+
+    match runtime.block_on(execute(request)) {
+        Ok(reply) => {
+            println!("{}", format_reply(&reply));
+            0
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            1
+        }
+    }
+}
@@ -1,3 +1,9 @@
+use crate::calendar::CalendarConfig;
+use crate::git::GitConfig;
+use crate::jira::JiraClient;
+use crate::reminders::ReminderConfig;
+use crate::rounding::RoundingConfig;
+use crate::shortcuts::ShortcutConfig;
 use serde::{Deserialize, Serialize};
 use std::env;
 use std::sync::Mutex;
@@ -7,6 +13,39 @@ pub struct AppConfig {
     pub jira_url: String,
     pub email: String,
     pub api_token: String,
+    /// Minutes without input before running timers get an idle prompt, 0 disables it.
+    #[serde(default = "default_idle_threshold_minutes")]
+    pub idle_threshold_minutes: u64,
+    /// Rounding applied to worklogs before they are sent to Jira.
+    #[serde(default)]
+    pub rounding: RoundingConfig,
+    #[serde(default)]
+    pub reminders: ReminderConfig,
+    #[serde(default)]
+    pub shortcuts: ShortcutConfig,
+    #[serde(default)]
+    pub git: GitConfig,
+    #[serde(default)]
+    pub calendar: CalendarConfig,
+    /// Port of the local HTTP API on 127.0.0.1, 0 disables it.
+    #[serde(default)]
+    pub api_port: u16,
+    /// Keep stopped timers as drafts to review instead of logging them.
+    #[serde(default)]
+    pub draft_mode: bool,
+}
+
+fn default_idle_threshold_minutes() -> u64 {
+    5
+}
+
+impl AppConfig {
+    pub fn jira_client(&self) -> Result<JiraClient, String> {
+        if self.jira_url.is_empty() || self.email.is_empty() || self.api_token.is_empty() {
+            return Err("Jira not configured. Please set URL, email and API token in Settings.".to_string());
+        }
+        Ok(JiraClient::new(&self.jira_url, &self.email, &self.api_token))
+    }
 }
 
 pub struct ConfigState {
@@ -19,6 +58,17 @@ impl ConfigState {
             jira_url: env::var("JIRA_URL").unwrap_or_default(),
             email: env::var("JIRA_EMAIL").unwrap_or_default(),
             api_token: env::var("JIRA_TOKEN").unwrap_or_default(),
+            idle_threshold_minutes: env::var("IDLE_THRESHOLD_MINUTES")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or_else(default_idle_threshold_minutes),
+            rounding: RoundingConfig::from_env(),
+            reminders: ReminderConfig::from_env(),
+            shortcuts: ShortcutConfig::from_env(),
+            git: GitConfig::from_env(),
+            calendar: CalendarConfig::from_env(),
+            api_port: env::var("API_PORT").ok().and_then(|v| v.parse().ok()).unwrap_or(0),
+            draft_mode: env::var("WORKLOG_MODE").is_ok_and(|v| v.trim() == "draft"),
         };
 
         Self {
@@ -27,6 +77,12 @@ impl ConfigState {
     }
 }
 
+impl Default for ConfigState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[tauri::command]
 pub fn get_config(state: tauri::State<'_, ConfigState>) -> Result<AppConfig, String> {
     let config = state.config.lock().map_err(|e| e.to_string())?;
@@ -39,12 +95,18 @@ pub fn save_config(
     jira_url: String,
     email: String,
     api_token: String,
+    idle_threshold_minutes: Option<u64>,
+    draft_mode: Option<bool>,
 ) -> Result<(), String> {
     let mut config = state.config.lock().map_err(|e| e.to_string())?;
-    *config = AppConfig {
-        jira_url,
-        email,
-        api_token,
-    };
+    config.jira_url = jira_url;
+    config.email = email;
+    config.api_token = api_token;
+    if let Some(minutes) = idle_threshold_minutes {
+        config.idle_threshold_minutes = minutes;
+    }
+    if let Some(draft_mode) = draft_mode {
+        config.draft_mode = draft_mode;
+    }
     Ok(())
 }
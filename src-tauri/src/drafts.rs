@@ -0,0 +1,365 @@
+use crate::config::ConfigState;
+use crate::jira::JiraClient;
+use crate::store;
+use crate::worklog::WorklogRequest;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter};
+
+/// Emitted with the drafts whenever they change.
+pub const DRAFTS_CHANGED: &str = "drafts://changed";
+const DRAFTS_FILE: &str = "drafts.json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DraftStatus {
+    Draft,
+    /// Being posted, so no other submit picks it up.
+    Submitting,
+    Submitted,
+    Failed,
+}
+
+/// A worklog kept locally until it is submitted.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Draft {
+    pub id: u32,
+    #[serde(flatten)]
+    pub request: WorklogRequest,
+    pub status: DraftStatus,
+    /// Why the last submit failed.
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub submitted_at: Option<DateTime<Utc>>,
+}
+
+impl Draft {
+    pub fn is_editable(&self) -> bool {
+        matches!(self.status, DraftStatus::Draft | DraftStatus::Failed)
+    }
+}
+
+pub fn drafts_path() -> Result<PathBuf, String> {
+    Ok(store::data_dir()?.join(DRAFTS_FILE))
+}
+
+#[derive(Default)]
+pub struct DraftStore {
+    pub drafts: Mutex<Vec<Draft>>,
+    pub next_id: Mutex<u32>,
+    /// Where drafts are written on every change, `None` keeps them in memory.
+    path: Option<PathBuf>,
+}
+
+impl DraftStore {
+    pub fn new() -> Self {
+        Self {
+            drafts: Mutex::new(Vec::new()),
+            next_id: Mutex::new(1),
+            path: None,
+        }
+    }
+
+    /// Drafts kept in `path`, which is created on the first change.
+    pub fn open(path: PathBuf) -> Self {
+        let mut drafts: Vec<Draft> = match fs::read_to_string(&path) {
+            Ok(json) => serde_json::from_str(&json).unwrap_or_else(|_| {
+                // Set aside rather than overwritten by the next change
+                let _ = fs::rename(&path, path.with_extension("invalid.json"));
+                Vec::new()
+            }),
+            Err(_) => Vec::new(),
+        };
+        // A submit cut short may or may not have reached Jira
+        for draft in drafts.iter_mut().filter(|d| d.status == DraftStatus::Submitting) {
+            draft.status = DraftStatus::Failed;
+            draft.error = Some("Interrupted while submitting, check Jira before submitting again".to_string());
+        }
+        let next_id = drafts.iter().map(|d| d.id).max().unwrap_or(0) + 1;
+
+        Self {
+            drafts: Mutex::new(drafts),
+            next_id: Mutex::new(next_id),
+            path: Some(path),
+        }
+    }
+
+    fn persist(&self, drafts: &[Draft]) -> Result<(), String> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let json = serde_json::to_string_pretty(drafts).map_err(|e| e.to_string())?;
+        store::write_atomic(path, &json)
+    }
+
+    pub fn list(&self) -> Result<Vec<Draft>, String> {
+        let drafts = self.drafts.lock().map_err(|e| e.to_string())?;
+        Ok(drafts.clone())
+    }
+
+    /// Add all the worklogs as drafts, or none of them.
+    pub fn add(&self, requests: Vec<WorklogRequest>, now: DateTime<Utc>) -> Result<Vec<u32>, String> {
+        requests.iter().try_for_each(check)?;
+        let mut drafts = self.drafts.lock().map_err(|e| e.to_string())?;
+        let mut next_id = self.next_id.lock().map_err(|e| e.to_string())?;
+        let before = drafts.len();
+        let mut ids = Vec::new();
+        for request in requests {
+            ids.push(*next_id);
+            drafts.push(Draft {
+                id: *next_id,
+                request,
+                status: DraftStatus::Draft,
+                error: None,
+                created_at: now,
+                submitted_at: None,
+            });
+            *next_id += 1;
+        }
+        if let Err(e) = self.persist(&drafts) {
+            drafts.truncate(before);
+            return Err(e);
+        }
+        Ok(ids)
+    }
+
+    /// Replace a draft's issue, duration, date or comment. A failed draft
+    /// goes back to draft. Left as it was when the change can't be saved.
+    pub fn update(&self, id: u32, request: WorklogRequest) -> Result<(), String> {
+        check(&request)?;
+        let mut drafts = self.drafts.lock().map_err(|e| e.to_string())?;
+        let pos = drafts.iter().position(|d| d.id == id).ok_or("Draft not found")?;
+        if !drafts[pos].is_editable() {
+            return Err("Draft already submitted".to_string());
+        }
+        let previous = drafts[pos].clone();
+        let draft = &mut drafts[pos];
+        draft.request = request;
+        draft.status = DraftStatus::Draft;
+        draft.error = None;
+        if let Err(e) = self.persist(&drafts) {
+            drafts[pos] = previous;
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// Remove a draft, or keep it when the change can't be saved.
+    pub fn remove(&self, id: u32) -> Result<(), String> {
+        let mut drafts = self.drafts.lock().map_err(|e| e.to_string())?;
+        let pos = drafts.iter().position(|d| d.id == id).ok_or("Draft not found")?;
+        if drafts[pos].status == DraftStatus::Submitting {
+            return Err("Draft is being submitted".to_string());
+        }
+        let removed = drafts.remove(pos);
+        if let Err(e) = self.persist(&drafts) {
+            drafts.insert(pos, removed);
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// Remove the submitted drafts, or none of them when that can't be saved.
+    pub fn clear_submitted(&self) -> Result<(), String> {
+        let mut drafts = self.drafts.lock().map_err(|e| e.to_string())?;
+        let previous = drafts.clone();
+        drafts.retain(|d| d.status != DraftStatus::Submitted);
+        if let Err(e) = self.persist(&drafts) {
+            *drafts = previous;
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// Mark the drafts to post (`ids`, or all) as submitting and return them.
+    /// Submitted drafts and ones already being submitted are left out, so
+    /// the same draft is never posted twice. Nothing is claimed when the
+    /// change can't be saved.
+    pub fn claim(&self, ids: Option<&[u32]>) -> Result<Vec<Draft>, String> {
+        let mut drafts = self.drafts.lock().map_err(|e| e.to_string())?;
+        let mut claimed = Vec::new();
+        let mut previous = Vec::new();
+        for draft in drafts.iter_mut() {
+            if draft.is_editable() && ids.is_none_or(|ids| ids.contains(&draft.id)) {
+                previous.push((draft.id, draft.status));
+                draft.status = DraftStatus::Submitting;
+                claimed.push(draft.clone());
+            }
+        }
+        if let Err(e) = self.persist(&drafts) {
+            for draft in drafts.iter_mut() {
+                if let Some((_, status)) = previous.iter().find(|(id, _)| *id == draft.id) {
+                    draft.status = *status;
+                }
+            }
+            return Err(e);
+        }
+        Ok(claimed)
+    }
+
+    /// Give claimed drafts that were not posted back to the user, as drafts.
+    /// Unlike other changes, this one is kept in memory even when it can't be
+    /// saved: rolled back, the drafts would stay stuck as submitting, and on
+    /// disk they are already read back as failed on the next start.
+    pub fn release(&self, ids: &[u32]) -> Result<(), String> {
+        let mut drafts = self.drafts.lock().map_err(|e| e.to_string())?;
+        for draft in drafts
+            .iter_mut()
+            .filter(|d| d.status == DraftStatus::Submitting && ids.contains(&d.id))
+        {
+            draft.status = DraftStatus::Draft;
+        }
+        self.persist(&drafts)
+    }
+
+    /// Record a claimed draft's submit result. Like [`DraftStore::release`],
+    /// it is kept in memory even when it can't be saved, since the worklog
+    /// was posted or not either way.
+    pub fn finish(&self, id: u32, result: Result<(), String>, now: DateTime<Utc>) -> Result<(), String> {
+        let mut drafts = self.drafts.lock().map_err(|e| e.to_string())?;
+        let draft = drafts.iter_mut().find(|d| d.id == id).ok_or("Draft not found")?;
+        match result {
+            Ok(()) => {
+                draft.status = DraftStatus::Submitted;
+                draft.error = None;
+                draft.submitted_at = Some(now);
+            }
+            Err(e) => {
+                draft.status = DraftStatus::Failed;
+                draft.error = Some(e);
+            }
+        }
+        self.persist(&drafts)
+    }
+}
+
+fn check(request: &WorklogRequest) -> Result<(), String> {
+    if request.issue_key.trim().is_empty() {
+        return Err("Draft needs an issue".to_string());
+    }
+    if request.seconds < 60 {
+        return Err("Worklog must be at least 1 minute".to_string());
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SubmitResult {
+    pub id: u32,
+    pub issue_key: String,
+    pub seconds: u64,
+    /// `None` when the worklog was posted.
+    pub error: Option<String>,
+}
+
+/// Post the drafts (`ids`, or all pending ones) one by one, recording each
+/// one's outcome. When an outcome can't be saved, the drafts not posted yet
+/// are given back as drafts rather than posted without a record.
+pub async fn submit(
+    client: &JiraClient,
+    store: &DraftStore,
+    ids: Option<&[u32]>,
+) -> Result<Vec<SubmitResult>, String> {
+    let mut results = Vec::new();
+    let mut claimed = store.claim(ids)?.into_iter();
+    while let Some(draft) = claimed.next() {
+        let request = &draft.request;
+        let result = client
+            .log_worklog(
+                &request.issue_key,
+                request.seconds,
+                Some(request.started),
+                request.comment.as_deref(),
+            )
+            .await;
+        let saved = store.finish(draft.id, result.clone(), Utc::now());
+        results.push(SubmitResult {
+            id: draft.id,
+            issue_key: request.issue_key.clone(),
+            seconds: request.seconds,
+            error: result.err(),
+        });
+
+        if let Err(e) = saved {
+            let rest: Vec<Draft> = claimed.collect();
+            let ids: Vec<u32> = rest.iter().map(|d| d.id).collect();
+            let _ = store.release(&ids);
+            results.extend(rest.into_iter().map(|d| SubmitResult {
+                id: d.id,
+                issue_key: d.request.issue_key,
+                seconds: d.request.seconds,
+                error: Some(format!("Not submitted, drafts cannot be saved: {}", e)),
+            }));
+            break;
+        }
+    }
+    Ok(results)
+}
+
+pub fn emit_changed(app: &AppHandle, store: &DraftStore) {
+    if let Ok(drafts) = store.list() {
+        let _ = app.emit(DRAFTS_CHANGED, drafts);
+    }
+}
+
+#[tauri::command]
+pub fn get_drafts(store: tauri::State<'_, DraftStore>) -> Result<Vec<Draft>, String> {
+    store.list()
+}
+
+#[tauri::command]
+pub fn add_drafts(
+    app: AppHandle,
+    store: tauri::State<'_, DraftStore>,
+    worklogs: Vec<WorklogRequest>,
+) -> Result<Vec<u32>, String> {
+    let ids = store.add(worklogs, Utc::now())?;
+    emit_changed(&app, &store);
+    Ok(ids)
+}
+
+#[tauri::command]
+pub fn update_draft(
+    app: AppHandle,
+    store: tauri::State<'_, DraftStore>,
+    id: u32,
+    worklog: WorklogRequest,
+) -> Result<(), String> {
+    store.update(id, worklog)?;
+    emit_changed(&app, &store);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn delete_draft(app: AppHandle, store: tauri::State<'_, DraftStore>, id: u32) -> Result<(), String> {
+    store.remove(id)?;
+    emit_changed(&app, &store);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn clear_submitted_drafts(app: AppHandle, store: tauri::State<'_, DraftStore>) -> Result<(), String> {
+    store.clear_submitted()?;
+    emit_changed(&app, &store);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn submit_drafts(
+    app: AppHandle,
+    config_state: tauri::State<'_, ConfigState>,
+    store: tauri::State<'_, DraftStore>,
+    ids: Option<Vec<u32>>,
+) -> Result<Vec<SubmitResult>, String> {
+    let client = config_state
+        .config
+        .lock()
+        .map_err(|e| e.to_string())?
+        .jira_client()?;
+    let results = submit(&client, &store, ids.as_deref()).await;
+    emit_changed(&app, &store);
+    results
+}
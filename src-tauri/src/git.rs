@@ -0,0 +1,225 @@
+use crate::config::ConfigState;
+use crate::reminders::notify;
+use crate::timer::{self, TimerState};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+/// Emitted with a [`BranchIssue`] when a watched repository switches to a
+/// branch naming an issue.
+pub const BRANCH_ISSUE: &str = "git://branch-issue";
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GitConfig {
+    /// Repositories whose checked-out branch is watched.
+    #[serde(default)]
+    pub repos: Vec<PathBuf>,
+    /// Start (or resume) a timer on the branch's issue instead of suggesting it.
+    #[serde(default)]
+    pub auto_start: bool,
+}
+
+impl GitConfig {
+    /// Read `GIT_REPOS` (comma-separated paths) and `GIT_AUTO_START`
+    /// (`true` or `1`).
+    pub fn from_env() -> Self {
+        Self {
+            repos: env::var("GIT_REPOS")
+                .map(|v| parse_repos(&v))
+                .unwrap_or_default(),
+            auto_start: env::var("GIT_AUTO_START")
+                .map(|v| matches!(v.trim(), "true" | "1"))
+                .unwrap_or(false),
+        }
+    }
+}
+
+pub fn parse_repos(value: &str) -> Vec<PathBuf> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+        .map(PathBuf::from)
+        .collect()
+}
+
+/// The repository's git directory. In worktrees and submodules `.git` is a
+/// file pointing to it.
+pub fn git_dir(repo: &Path) -> Result<PathBuf, String> {
+    let dot_git = repo.join(".git");
+    if dot_git.is_dir() {
+        return Ok(dot_git);
+    }
+    let content = fs::read_to_string(&dot_git).map_err(|e| format!("{}: {}", dot_git.display(), e))?;
+    let dir = content
+        .trim()
+        .strip_prefix("gitdir:")
+        .ok_or_else(|| format!("{} is not a git repository", repo.display()))?;
+    Ok(repo.join(dir.trim()))
+}
+
+/// The checked-out branch, `None` when the HEAD is detached.
+pub fn read_branch(repo: &Path) -> Result<Option<String>, String> {
+    let head = git_dir(repo)?.join("HEAD");
+    let content = fs::read_to_string(&head).map_err(|e| format!("{}: {}", head.display(), e))?;
+    Ok(content
+        .trim()
+        .strip_prefix("ref: refs/heads/")
+        .map(str::to_string))
+}
+
+/// The first issue key in a branch name, e.g. `PROJ-123` in
+/// `feature/PROJ-123-login`. Keys are uppercase, as Jira writes them, so
+/// words like `utf-8` are not taken for one.
+pub fn issue_key_in(branch: &str) -> Option<String> {
+    let chars: Vec<char> = branch.chars().collect();
+    let in_project = |c: char| c.is_ascii_uppercase() || c.is_ascii_digit();
+    for start in 0..chars.len() {
+        if !chars[start].is_ascii_uppercase() || (start > 0 && chars[start - 1].is_ascii_alphanumeric()) {
+            continue;
+        }
+        let project_end = (start..chars.len())
+            .find(|&i| !in_project(chars[i]))
+            .unwrap_or(chars.len());
+        if project_end - start < 2 || chars.get(project_end) != Some(&'-') {
+            continue;
+        }
+        let number_end = (project_end + 1..chars.len())
+            .find(|&i| !chars[i].is_ascii_digit())
+            .unwrap_or(chars.len());
+        let next_is_alphanumeric = chars.get(number_end).is_some_and(|c| c.is_ascii_alphanumeric());
+        if number_end > project_end + 1 && !next_is_alphanumeric {
+            return Some(chars[start..number_end].iter().collect());
+        }
+    }
+    None
+}
+
+/// A branch switch to another issue: `(repository, branch, issue key)`.
+pub type BranchSwitch = (PathBuf, String, String);
+
+/// Read each repository's branch and return the ones that moved to another
+/// issue since the previous poll. `seen` keeps the last branch per repository;
+/// a repository seen for the first time is only recorded.
+pub fn poll(repos: &[PathBuf], seen: &mut HashMap<PathBuf, Option<String>>) -> Vec<BranchSwitch> {
+    let mut switches = Vec::new();
+    for repo in repos {
+        let branch = read_branch(repo).ok().flatten();
+        let Some(previous) = seen.insert(repo.clone(), branch.clone()) else {
+            continue;
+        };
+        let Some(branch) = branch else {
+            continue;
+        };
+        let Some(issue_key) = issue_key_in(&branch) else {
+            continue;
+        };
+        if previous.as_deref().and_then(issue_key_in).as_ref() != Some(&issue_key) {
+            switches.push((repo.clone(), branch, issue_key));
+        }
+    }
+    switches
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BranchIssue {
+    pub repo: String,
+    pub branch: String,
+    pub issue_key: String,
+    pub summary: String,
+}
+
+async fn on_switch(app: &AppHandle, switch: BranchSwitch, auto_start: bool) -> Result<(), String> {
+    let (repo, branch, issue_key) = switch;
+    let config = app
+        .state::<ConfigState>()
+        .config
+        .lock()
+        .map_err(|e| e.to_string())?
+        .clone();
+    let detail = config.jira_client()?.get_issue_detail(&issue_key).await?;
+
+    if auto_start {
+        let state = app.state::<TimerState>();
+        let now = Utc::now();
+        match state.find_id(&detail.key) {
+            // Already running is fine, the branch just came back
+            Ok(id) => {
+                let _ = state.resume(id, now);
+            }
+            Err(_) => {
+                state.start(detail.key.clone(), detail.summary.clone(), now)?;
+            }
+        }
+        timer::emit_changed(app, &state);
+        notify(app, &format!("Tracking {} (branch {})", detail.key, branch));
+        return Ok(());
+    }
+
+    notify(
+        app,
+        &format!("Branch {}: start a timer on {}?", branch, detail.key),
+    );
+    let _ = app.emit(
+        BRANCH_ISSUE,
+        BranchIssue {
+            repo: repo.display().to_string(),
+            branch,
+            issue_key: detail.key,
+            summary: detail.summary,
+        },
+    );
+    Ok(())
+}
+
+/// Watch the configured repositories' HEAD and suggest, or start, a timer
+/// when a branch names another issue.
+pub fn spawn(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut seen = HashMap::new();
+        loop {
+            let config = app
+                .state::<ConfigState>()
+                .config
+                .lock()
+                .map(|c| c.git.clone())
+                .unwrap_or_default();
+            // Repositories removed from the settings are forgotten
+            seen.retain(|repo, _| config.repos.contains(repo));
+
+            for switch in poll(&config.repos, &mut seen) {
+                if let Err(e) = on_switch(&app, switch, config.auto_start).await {
+                    notify(&app, &e);
+                }
+            }
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    });
+}
+
+#[tauri::command]
+pub fn get_git_watch(config_state: tauri::State<'_, ConfigState>) -> Result<GitConfig, String> {
+    let config = config_state.config.lock().map_err(|e| e.to_string())?;
+    Ok(config.git.clone())
+}
+
+#[tauri::command]
+pub fn save_git_watch(
+    config_state: tauri::State<'_, ConfigState>,
+    repos: String,
+    auto_start: bool,
+) -> Result<(), String> {
+    let repos = parse_repos(&repos);
+    if let Some(repo) = repos.iter().find(|r| git_dir(r).is_err()) {
+        return Err(format!("{} is not a git repository", repo.display()));
+    }
+    let mut config = config_state.config.lock().map_err(|e| e.to_string())?;
+    config.git = GitConfig { repos, auto_start };
+    Ok(())
+}
@@ -0,0 +1,332 @@
+use crate::config::ConfigState;
+use crate::timer::{self, Timer, TimerState};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+
+/// Emitted when an idle period was recorded on running timers and needs an answer.
+pub const IDLE_DETECTED: &str = "idle://detected";
+
+const POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IdleSample {
+    /// Seconds since the last keyboard or mouse input.
+    pub idle_seconds: u64,
+    pub locked: bool,
+}
+
+/// Where the watcher reads idle time from, so it can be faked in tests.
+pub trait IdleSource: Send {
+    /// `None` when the desktop exposes no idle information.
+    fn sample(&mut self) -> Option<IdleSample>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct IdlePeriod {
+    pub started_at: DateTime<Utc>,
+    pub ended_at: DateTime<Utc>,
+}
+
+/// An idle period as seen by one timer. The same `id` is shared by every timer
+/// that was running during the period.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct IdleSpan {
+    pub id: u32,
+    pub started_at: DateTime<Utc>,
+    pub ended_at: DateTime<Utc>,
+    /// Part of the period the timer was running, i.e. what a discard removes.
+    pub seconds: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolution: Option<IdleResolution>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "action", rename_all = "lowercase")]
+pub enum IdleResolution {
+    Keep,
+    Discard,
+    /// Move the idle time to another running timer.
+    Reassign { timer_id: u32 },
+}
+
+/// Turns periodic idle samples into finished idle periods.
+#[derive(Debug, Default)]
+pub struct IdleTracker {
+    idle_since: Option<DateTime<Utc>>,
+}
+
+impl IdleTracker {
+    /// Returns the period once the user is back, if it lasted at least `threshold_seconds`.
+    pub fn observe(
+        &mut self,
+        now: DateTime<Utc>,
+        sample: IdleSample,
+        threshold_seconds: u64,
+    ) -> Option<IdlePeriod> {
+        let last_input = now - Duration::seconds(sample.idle_seconds as i64);
+
+        if sample.locked || sample.idle_seconds >= threshold_seconds {
+            // Keep the earliest start: a locked session may report idle time from 0
+            self.idle_since = Some(self.idle_since.map_or(last_input, |s| s.min(last_input)));
+            return None;
+        }
+
+        let started_at = self.idle_since.take()?;
+        let ended_at = last_input.max(started_at);
+        if ((ended_at - started_at).num_seconds() as u64) < threshold_seconds {
+            return None;
+        }
+        Some(IdlePeriod {
+            started_at,
+            ended_at,
+        })
+    }
+}
+
+/// Attach the period to every timer that was running during it. Returns false
+/// when no timer was affected.
+pub fn record_idle(timers: &mut [Timer], id: u32, period: IdlePeriod) -> bool {
+    let mut recorded = false;
+    for timer in timers.iter_mut() {
+        let seconds = timer.running_between(period.started_at, period.ended_at);
+        if seconds == 0 {
+            continue;
+        }
+        timer.idle_spans.push(IdleSpan {
+            id,
+            started_at: period.started_at,
+            ended_at: period.ended_at,
+            seconds,
+            resolution: None,
+        });
+        recorded = true;
+    }
+    recorded
+}
+
+/// Apply the user's answer to an idle period on all timers that recorded it.
+pub fn resolve_idle(
+    timers: &mut [Timer],
+    span_id: u32,
+    resolution: &IdleResolution,
+    now: DateTime<Utc>,
+) -> Result<(), String> {
+    let is_pending = |t: &Timer| {
+        t.idle_spans
+            .iter()
+            .any(|s| s.id == span_id && s.resolution.is_none())
+    };
+    if !timers.iter().any(is_pending) {
+        return Err("Idle period not found".to_string());
+    }
+
+    let target_id = match resolution {
+        IdleResolution::Reassign { timer_id } => {
+            if !timers.iter().any(|t| t.id == *timer_id) {
+                return Err("Timer not found".to_string());
+            }
+            Some(*timer_id)
+        }
+        _ => None,
+    };
+
+    let mut moved_seconds = 0;
+    let mut target_affected = false;
+    for timer in timers.iter_mut().filter(|t| is_pending(t)) {
+        let span = timer
+            .idle_spans
+            .iter_mut()
+            .find(|s| s.id == span_id && s.resolution.is_none())
+            .expect("filtered on pending span");
+        span.resolution = Some(resolution.clone());
+        let seconds = span.seconds;
+
+        let remove = match target_id {
+            Some(id) if id == timer.id => {
+                target_affected = true;
+                false
+            }
+            Some(_) => true,
+            None => *resolution == IdleResolution::Discard,
+        };
+        if remove {
+            let elapsed = timer.elapsed_at(now);
+            let removed = seconds.min(elapsed);
+            timer.adjust_elapsed(elapsed - removed, now);
+            moved_seconds = moved_seconds.max(removed);
+        }
+    }
+
+    // The target already counted the period if it was running during it
+    if let Some(target_id) = target_id.filter(|_| !target_affected) {
+        let target = timers
+            .iter_mut()
+            .find(|t| t.id == target_id)
+            .expect("checked above");
+        let elapsed = target.elapsed_at(now);
+        target.adjust_elapsed(elapsed + moved_seconds, now);
+    }
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn resolve_idle_span(
+    app: AppHandle,
+    state: tauri::State<'_, TimerState>,
+    span_id: u32,
+    resolution: IdleResolution,
+) -> Result<(), String> {
+    let mut timers = state.timers.lock().map_err(|e| e.to_string())?;
+    resolve_idle(&mut timers, span_id, &resolution, Utc::now())?;
+    drop(timers);
+
+    timer::emit_changed(&app, &state);
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+mod dbus {
+    use super::{IdleSample, IdleSource};
+    use zbus::blocking::Connection;
+    use zbus::zvariant::OwnedValue;
+
+    /// Idle time from the session bus (GNOME Mutter, then the freedesktop
+    /// screensaver used by KDE and others) and the lock state from logind.
+    pub struct DbusIdleSource {
+        session: Connection,
+        system: Option<Connection>,
+    }
+
+    impl DbusIdleSource {
+        pub fn connect() -> Result<Self, String> {
+            let session = Connection::session().map_err(|e| e.to_string())?;
+            Ok(Self {
+                session,
+                system: Connection::system().ok(),
+            })
+        }
+
+        fn idle_millis(&self) -> Option<u64> {
+            if let Ok(reply) = self.session.call_method(
+                Some("org.gnome.Mutter.IdleMonitor"),
+                "/org/gnome/Mutter/IdleMonitor/Core",
+                Some("org.gnome.Mutter.IdleMonitor"),
+                "GetIdletime",
+                &(),
+            ) {
+                if let Ok(millis) = reply.body().deserialize::<u64>() {
+                    return Some(millis);
+                }
+            }
+
+            let reply = self
+                .session
+                .call_method(
+                    Some("org.freedesktop.ScreenSaver"),
+                    "/org/freedesktop/ScreenSaver",
+                    Some("org.freedesktop.ScreenSaver"),
+                    "GetSessionIdleTime",
+                    &(),
+                )
+                .ok()?;
+            reply.body().deserialize::<u32>().ok().map(u64::from)
+        }
+
+        fn locked(&self) -> bool {
+            let Some(system) = &self.system else {
+                return false;
+            };
+            system
+                .call_method(
+                    Some("org.freedesktop.login1"),
+                    "/org/freedesktop/login1/session/auto",
+                    Some("org.freedesktop.DBus.Properties"),
+                    "Get",
+                    &("org.freedesktop.login1.Session", "LockedHint"),
+                )
+                .ok()
+                .and_then(|reply| reply.body().deserialize::<OwnedValue>().ok())
+                .and_then(|value| bool::try_from(value).ok())
+                .unwrap_or(false)
+        }
+    }
+
+    impl IdleSource for DbusIdleSource {
+        fn sample(&mut self) -> Option<IdleSample> {
+            let idle_millis = self.idle_millis();
+            let locked = self.locked();
+            if idle_millis.is_none() && !locked {
+                return None;
+            }
+            Some(IdleSample {
+                idle_seconds: idle_millis.unwrap_or(0) / 1000,
+                locked,
+            })
+        }
+    }
+}
+
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn idle_threshold_seconds(app: &AppHandle) -> u64 {
+    let config_state = app.state::<ConfigState>();
+    let threshold = config_state
+        .config
+        .lock()
+        .map(|c| c.idle_threshold_minutes)
+        .unwrap_or(0);
+    threshold * 60
+}
+
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn watch(app: &AppHandle, mut source: impl IdleSource) {
+    let mut tracker = IdleTracker::default();
+    loop {
+        std::thread::sleep(POLL_INTERVAL);
+
+        let threshold = idle_threshold_seconds(app);
+        if threshold == 0 {
+            tracker = IdleTracker::default();
+            continue;
+        }
+        let Some(sample) = source.sample() else {
+            continue;
+        };
+        let Some(period) = tracker.observe(Utc::now(), sample, threshold) else {
+            continue;
+        };
+
+        let state = app.state::<TimerState>();
+        let recorded = {
+            let (Ok(mut timers), Ok(mut next_id)) = (state.timers.lock(), state.next_id.lock())
+            else {
+                continue;
+            };
+            let recorded = record_idle(&mut timers, *next_id, period);
+            if recorded {
+                *next_id += 1;
+            }
+            recorded
+        };
+
+        if recorded {
+            timer::emit_changed(app, &state);
+            crate::show_main_window(app);
+            let _ = app.emit(IDLE_DETECTED, period);
+        }
+    }
+}
+
+/// Start the idle watcher on its own thread. Does nothing without a session
+/// bus, and on platforms other than Linux for now.
+pub fn spawn(app: AppHandle) {
+    #[cfg(target_os = "linux")]
+    std::thread::spawn(move || {
+        if let Ok(source) = dbus::DbusIdleSource::connect() {
+            watch(&app, source);
+        }
+    });
+    #[cfg(not(target_os = "linux"))]
+    let _ = app;
+}
@@ -0,0 +1,398 @@
+use crate::config::{AppConfig, ConfigState};
+use crate::drafts::{self, DraftStore};
+use crate::jira::{JiraClient, TimesheetEntry};
+use crate::report::parse_date;
+use crate::store;
+use crate::timer::{self, TimerState, TimerView};
+use crate::worklog::{self, WorklogQueue};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager};
+
+const SOCKET_DIR: &str = "ipc";
+const SOCKET_FILE: &str = "ipc.sock";
+
+/// A command from the CLI, sent to the app as one JSON object per line.
+/// Timers are designated by issue key; without one, the active timer.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "lowercase")]
+pub enum Request {
+    Status,
+    Start {
+        issue_key: String,
+    },
+    Pause {
+        issue_key: Option<String>,
+    },
+    /// Without an issue key, the timer paused last.
+    Resume {
+        issue_key: Option<String>,
+    },
+    /// Stop without logging.
+    Stop {
+        issue_key: Option<String>,
+    },
+    Log {
+        issue_key: Option<String>,
+        comment: Option<String>,
+    },
+    /// The user's worklogs between two `YYYY-MM-DD` dates.
+    Report {
+        from: String,
+        to: String,
+    },
+    Transition {
+        issue_key: String,
+        status: String,
+    },
+}
+
+impl Request {
+    /// A report between two `YYYY-MM-DD` dates, both `today` by default and
+    /// `to` defaulting to `from`.
+    pub fn report(from: Option<&str>, to: Option<&str>, today: NaiveDate) -> Result<Self, String> {
+        let from = from.map(parse_date).transpose()?.unwrap_or(today);
+        let to = to.map(parse_date).transpose()?.unwrap_or(from);
+        if to < from {
+            return Err(format!("{} is before {}", to, from));
+        }
+        Ok(Request::Report {
+            from: from.format("%Y-%m-%d").to_string(),
+            to: to.format("%Y-%m-%d").to_string(),
+        })
+    }
+
+    /// Whether the request can change timers or queued worklogs.
+    pub fn changes_timers(&self) -> bool {
+        !matches!(
+            self,
+            Request::Status | Request::Report { .. } | Request::Transition { .. }
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "reply", rename_all = "lowercase")]
+pub enum Reply {
+    /// The timers after the request.
+    Timers { timers: Vec<TimerView> },
+    Logged {
+        issue_key: String,
+        seconds: u64,
+        queued: usize,
+    },
+    /// The timer was kept as drafts, in draft mode.
+    Drafted { issue_key: String, seconds: u64 },
+    Worklogs { entries: Vec<TimesheetEntry> },
+    Transitioned { issue_key: String, status: String },
+}
+
+/// Why a request failed, so the HTTP API can tell whose fault it is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Failure {
+    /// The request itself: unknown timer, invalid value, too short to log.
+    Invalid(String),
+    /// Jira is not set up.
+    NotConfigured(String),
+    /// Jira, or the network to it, failed.
+    Upstream(String),
+}
+
+impl Failure {
+    pub fn message(&self) -> &str {
+        match self {
+            Failure::Invalid(e) | Failure::NotConfigured(e) | Failure::Upstream(e) => e,
+        }
+    }
+}
+
+impl From<String> for Failure {
+    fn from(error: String) -> Self {
+        Failure::Invalid(error)
+    }
+}
+
+impl From<Failure> for String {
+    fn from(failure: Failure) -> Self {
+        failure.message().to_string()
+    }
+}
+
+fn jira_client(config: &AppConfig) -> Result<JiraClient, Failure> {
+    config.jira_client().map_err(Failure::NotConfigured)
+}
+
+/// What requests run against: the app's state, or the session kept on disk
+/// when the app is closed.
+pub struct Core<'a> {
+    pub timers: &'a TimerState,
+    pub queue: &'a WorklogQueue,
+    pub drafts: &'a DraftStore,
+    pub config: &'a AppConfig,
+}
+
+impl Core<'_> {
+    pub async fn dispatch(&self, request: Request, now: DateTime<Utc>) -> Result<Reply, Failure> {
+        match request {
+            Request::Status => {}
+            Request::Start { issue_key } => {
+                let client = jira_client(self.config)?;
+                let detail = client
+                    .get_issue_detail(&issue_key.trim().to_uppercase())
+                    .await
+                    .map_err(Failure::Upstream)?;
+                self.timers.start(detail.key, detail.summary, now)?;
+            }
+            Request::Pause { issue_key } => {
+                let id = self.timer_id(issue_key.as_deref(), self.timers.active_id()?, "No running timer")?;
+                self.timers.pause(id, now)?;
+            }
+            Request::Resume { issue_key } => {
+                let id = self.timer_id(issue_key.as_deref(), self.timers.last_paused_id()?, "No paused timer")?;
+                self.timers.resume(id, now)?;
+            }
+            Request::Stop { issue_key } => {
+                let id = self.timer_id(issue_key.as_deref(), self.timers.active_id()?, "No running timer")?;
+                let timer = self.timers.stop(id, now)?;
+                self.timers.record_history(&timer, None, now);
+            }
+            Request::Log { issue_key, comment } => {
+                let id = self.timer_id(issue_key.as_deref(), self.timers.active_id()?, "No running timer")?;
+                return self.log(id, comment.as_deref(), now).await;
+            }
+            Request::Report { from, to } => {
+                let client = jira_client(self.config)?;
+                let entries = client.get_my_worklogs(&from, &to).await.map_err(Failure::Upstream)?;
+                return Ok(Reply::Worklogs { entries });
+            }
+            Request::Transition { issue_key, status } => {
+                return self.transition(&issue_key.trim().to_uppercase(), status.trim()).await;
+            }
+        }
+        Ok(Reply::Timers {
+            timers: self.timers.snapshot(now)?,
+        })
+    }
+
+    fn timer_id(&self, issue_key: Option<&str>, fallback: Option<u32>, missing: &str) -> Result<u32, String> {
+        match issue_key {
+            Some(issue_key) => self.timers.find_id(issue_key),
+            None => fallback.ok_or_else(|| missing.to_string()),
+        }
+    }
+
+    async fn log(&self, timer_id: u32, comment: Option<&str>, now: DateTime<Utc>) -> Result<Reply, Failure> {
+        if self.config.draft_mode {
+            return Ok(self.draft(timer_id, comment, now)?);
+        }
+        // Checked before stopping, so a timer too short to log keeps running
+        let requests = self.timer_requests(timer_id, comment, now)?;
+        let seconds = requests.iter().map(|r| r.seconds).sum();
+        let client = jira_client(self.config)?;
+        let timer = self.timers.stop(timer_id, now)?;
+
+        match worklog::log_all(&client, self.queue, requests).await {
+            Ok(outcome) => {
+                self.timers.record_history(&timer, Some(seconds), now);
+                Ok(Reply::Logged {
+                    issue_key: timer.issue_key,
+                    seconds,
+                    queued: outcome.queued,
+                })
+            }
+            Err(e) => {
+                // Nothing was logged, so the timer goes back (paused)
+                self.timers.restore(vec![timer])?;
+                Err(Failure::Upstream(e))
+            }
+        }
+    }
+
+    fn draft(&self, timer_id: u32, comment: Option<&str>, now: DateTime<Utc>) -> Result<Reply, String> {
+        let requests = self.timer_requests(timer_id, comment, now)?;
+        let seconds = requests.iter().map(|r| r.seconds).sum();
+        // Drafts first, so a timer is never stopped without its time saved
+        let ids = self.drafts.add(requests, now)?;
+        let timer = match self.timers.stop(timer_id, now) {
+            Ok(timer) => timer,
+            Err(e) => {
+                for id in ids {
+                    let _ = self.drafts.remove(id);
+                }
+                return Err(e);
+            }
+        };
+        self.timers.record_history(&timer, None, now);
+        Ok(Reply::Drafted {
+            issue_key: timer.issue_key,
+            seconds,
+        })
+    }
+
+    fn timer_requests(
+        &self,
+        timer_id: u32,
+        comment: Option<&str>,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<worklog::WorklogRequest>, String> {
+        let timer = self.timers.get(timer_id)?;
+        worklog::timer_requests(&self.config.rounding, &timer, false, comment, now)
+    }
+
+    async fn transition(&self, issue_key: &str, status: &str) -> Result<Reply, Failure> {
+        let client = jira_client(self.config)?;
+        let transition = client
+            .get_transitions(issue_key)
+            .await
+            .map_err(Failure::Upstream)?
+            .into_iter()
+            .find(|t| t.name.eq_ignore_ascii_case(status))
+            .ok_or_else(|| format!("No transition \"{}\" for {}", status, issue_key))?;
+        if transition.fields.iter().any(|f| f.required) {
+            return Err(Failure::Invalid(format!(
+                "Transition \"{}\" has required fields, use the app",
+                transition.name
+            )));
+        }
+
+        client
+            .transition_issue(issue_key, &transition.id, None, None)
+            .await
+            .map_err(Failure::Upstream)?;
+        Ok(Reply::Transitioned {
+            issue_key: issue_key.to_string(),
+            status: transition.name,
+        })
+    }
+}
+
+/// The socket sits in its own directory, private to the user, so nobody else
+/// can connect to it even before it exists.
+pub fn socket_path() -> Result<PathBuf, String> {
+    Ok(store::data_dir()?.join(SOCKET_DIR).join(SOCKET_FILE))
+}
+
+/// Run a request against the app's state and refresh its views.
+pub(crate) async fn dispatch_in_app(app: &AppHandle, request: Request) -> Result<Reply, Failure> {
+    let timers = app.state::<TimerState>();
+    let queue = app.state::<WorklogQueue>();
+    let drafts = app.state::<DraftStore>();
+    let config = app
+        .state::<ConfigState>()
+        .config
+        .lock()
+        .map_err(|e| e.to_string())?
+        .clone();
+    let changes = request.changes_timers();
+
+    let core = Core {
+        timers: &timers,
+        queue: &queue,
+        drafts: &drafts,
+        config: &config,
+    };
+    let reply = core.dispatch(request, Utc::now()).await;
+    if changes {
+        timer::emit_changed(app, &timers);
+    }
+    if matches!(reply, Ok(Reply::Drafted { .. })) {
+        drafts::emit_changed(app, &drafts);
+    }
+    reply
+}
+
+#[cfg(unix)]
+mod socket {
+    use super::{dispatch_in_app, socket_path, Reply, Request};
+    use std::fs;
+    use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+    use tauri::AppHandle;
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::{UnixListener, UnixStream};
+
+    pub fn spawn(app: AppHandle) {
+        tauri::async_runtime::spawn(async move {
+            let Ok(path) = socket_path() else {
+                return;
+            };
+            let Some(dir) = path.parent() else {
+                return;
+            };
+            // Bound only once the directory is private, as the socket itself
+            // gets the umask's permissions
+            let _ = fs::DirBuilder::new().recursive(true).mode(0o700).create(dir);
+            if fs::set_permissions(dir, fs::Permissions::from_mode(0o700)).is_err() {
+                return;
+            }
+            // Left over when the app did not quit cleanly
+            let _ = fs::remove_file(&path);
+            let Ok(listener) = UnixListener::bind(&path) else {
+                return;
+            };
+            let _ = fs::set_permissions(&path, fs::Permissions::from_mode(0o600));
+
+            while let Ok((stream, _)) = listener.accept().await {
+                let app = app.clone();
+                tauri::async_runtime::spawn(async move {
+                    let _ = serve(&app, stream).await;
+                });
+            }
+        });
+    }
+
+    async fn serve(app: &AppHandle, stream: UnixStream) -> std::io::Result<()> {
+        let (read, mut write) = stream.into_split();
+        let mut lines = BufReader::new(read).lines();
+        while let Some(line) = lines.next_line().await? {
+            let reply = match serde_json::from_str::<Request>(&line) {
+                Ok(request) => dispatch_in_app(app, request).await.map_err(String::from),
+                Err(e) => Err(format!("Invalid request: {}", e)),
+            };
+            let mut json = serde_json::to_string(&reply)?;
+            json.push('\n');
+            write.write_all(json.as_bytes()).await?;
+        }
+        Ok(())
+    }
+
+    pub async fn send(request: &Request) -> Option<Result<Reply, String>> {
+        let stream = UnixStream::connect(socket_path().ok()?).await.ok()?;
+        Some(exchange(stream, request).await)
+    }
+
+    async fn exchange(stream: UnixStream, request: &Request) -> Result<Reply, String> {
+        let (read, mut write) = stream.into_split();
+        let mut json = serde_json::to_string(request).map_err(|e| e.to_string())?;
+        json.push('\n');
+        write.write_all(json.as_bytes()).await.map_err(|e| e.to_string())?;
+
+        let line = BufReader::new(read)
+            .lines()
+            .next_line()
+            .await
+            .map_err(|e| e.to_string())?
+            .ok_or("The app closed the connection")?;
+        serde_json::from_str::<Result<Reply, String>>(&line).map_err(|e| e.to_string())?
+    }
+}
+
+/// Listen for CLI requests while the app runs.
+pub fn spawn(app: AppHandle) {
+    #[cfg(unix)]
+    socket::spawn(app);
+    // Without Unix sockets the CLI only works on the saved session
+    #[cfg(not(unix))]
+    let _ = app;
+}
+
+/// Send a request to the running app, `None` when it is not running.
+pub async fn send(request: &Request) -> Option<Result<Reply, String>> {
+    #[cfg(unix)]
+    return socket::send(request).await;
+    #[cfg(not(unix))]
+    {
+        let _ = request;
+        None
+    }
+}
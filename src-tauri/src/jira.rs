@@ -1,5 +1,6 @@
+use crate::adf;
 use base64::Engine;
-use chrono::NaiveDate;
+use chrono::{DateTime, Local, NaiveDate, Utc};
 use reqwest::header::{HeaderMap, AUTHORIZATION, CONTENT_TYPE};
 use serde::{Deserialize, Serialize};
 
@@ -68,6 +69,9 @@ pub struct JiraTicketDetail {
     pub summary: String,
     pub status: String,
     pub description: String,
+    /// Sanitized HTML rendering of the description, safe to insert into the page.
+    pub description_html: String,
+    pub description_markdown: String,
     pub priority: String,
     pub assignee: String,
     pub reporter: String,
@@ -84,6 +88,27 @@ pub struct JiraTicketDetail {
 pub struct JiraTransition {
     pub id: String,
     pub name: String,
+    pub has_screen: bool,
+    /// Fields shown on the transition screen, required ones first.
+    pub fields: Vec<JiraTransitionField>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JiraTransitionField {
+    pub key: String,
+    pub name: String,
+    pub required: bool,
+    /// Jira schema type, e.g. "resolution", "option", "string", "number", "array".
+    pub field_type: String,
+    /// Element type when `field_type` is "array".
+    pub items_type: Option<String>,
+    pub allowed_values: Vec<JiraAllowedValue>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JiraAllowedValue {
+    pub id: String,
+    pub name: String,
 }
 
 #[derive(Debug, Deserialize)]
@@ -95,6 +120,44 @@ struct TransitionsResponse {
 struct TransitionValue {
     id: String,
     name: String,
+    #[serde(default, rename = "hasScreen")]
+    has_screen: bool,
+    #[serde(default)]
+    fields: std::collections::HashMap<String, TransitionFieldValue>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TransitionFieldValue {
+    #[serde(default)]
+    required: bool,
+    #[serde(default)]
+    name: String,
+    schema: Option<FieldSchema>,
+    #[serde(default, rename = "allowedValues")]
+    allowed_values: Vec<AllowedValue>,
+}
+
+#[derive(Debug, Deserialize)]
+struct FieldSchema {
+    #[serde(rename = "type")]
+    field_type: String,
+    items: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AllowedValue {
+    id: String,
+    // Resolutions and priorities have a name, custom field options a value.
+    name: Option<String>,
+    value: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ErrorResponse {
+    #[serde(default, rename = "errorMessages")]
+    error_messages: Vec<String>,
+    #[serde(default)]
+    errors: std::collections::HashMap<String, String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -136,6 +199,62 @@ struct IssueTypeField {
     name: String,
 }
 
+// --- Comment structs ---
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JiraComment {
+    pub id: String,
+    pub author: String,
+    pub created: String,
+    pub updated: String,
+    pub body: String,
+    /// Sanitized HTML rendering of the body, safe to insert into the page.
+    pub body_html: String,
+    pub visibility: Option<JiraCommentVisibility>,
+}
+
+/// Restriction of a comment to a project role or group.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JiraCommentVisibility {
+    /// "role" or "group"
+    pub kind: String,
+    pub value: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JiraCommentPage {
+    pub comments: Vec<JiraComment>,
+    pub start_at: u32,
+    pub total: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct CommentsResponse {
+    #[serde(default, rename = "startAt")]
+    start_at: u32,
+    #[serde(default)]
+    total: u32,
+    comments: Vec<CommentValue>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CommentValue {
+    id: String,
+    author: Option<UserField>,
+    body: Option<serde_json::Value>,
+    created: Option<String>,
+    updated: Option<String>,
+    visibility: Option<VisibilityValue>,
+}
+
+#[derive(Debug, Deserialize)]
+struct VisibilityValue {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    value: String,
+}
+
 // --- Worklog / Timesheet structs ---
 
 #[derive(Debug, Deserialize)]
@@ -146,6 +265,7 @@ struct MyselfResponse {
 
 #[derive(Debug, Deserialize)]
 struct WorklogSearchResponse {
+    total: Option<u32>,
     issues: Vec<WorklogIssue>,
 }
 
@@ -159,6 +279,37 @@ struct WorklogIssue {
 struct WorklogIssueFields {
     summary: String,
     worklog: Option<WorklogContainer>,
+    // Only requested for reports
+    project: Option<ReportProjectField>,
+    issuetype: Option<NamedField>,
+    parent: Option<ReportParentField>,
+    #[serde(default)]
+    components: Vec<NamedField>,
+    #[serde(default)]
+    labels: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct NamedField {
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReportProjectField {
+    key: String,
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReportParentField {
+    key: String,
+    fields: Option<ReportParentFields>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReportParentFields {
+    #[serde(default)]
+    summary: String,
 }
 
 #[derive(Debug, Deserialize)]
@@ -194,6 +345,25 @@ pub struct TimesheetEntry {
     pub summary: String,
     pub date: String,
     pub time_spent_seconds: u64,
+    /// When the worklog starts, `None` if Jira sent an unreadable date.
+    #[serde(default)]
+    pub started: Option<DateTime<Utc>>,
+}
+
+/// One of the user's worklogs with the issue fields reports group by.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReportWorklog {
+    pub issue_key: String,
+    pub summary: String,
+    pub date: String,
+    pub seconds: u64,
+    pub project_key: String,
+    pub project_name: String,
+    pub issue_type: String,
+    /// The epic, or the parent issue of a sub-task: `(key, summary)`.
+    pub parent: Option<(String, String)>,
+    pub components: Vec<String>,
+    pub labels: Vec<String>,
 }
 
 pub struct JiraClient {
@@ -204,12 +374,18 @@ pub struct JiraClient {
 
 impl JiraClient {
     pub fn new(base_url: &str, email: &str, api_token: &str) -> Self {
+        Self::with_http_client(reqwest::Client::new(), base_url, email, api_token)
+    }
+
+    /// Build a client on top of an existing `reqwest::Client`, e.g. one configured
+    /// with a proxy or timeouts, or pointed at a local stub server in tests.
+    pub fn with_http_client(client: reqwest::Client, base_url: &str, email: &str, api_token: &str) -> Self {
         let credentials = format!("{}:{}", email, api_token);
         let encoded = base64::engine::general_purpose::STANDARD.encode(credentials);
         let auth_header = format!("Basic {}", encoded);
 
         Self {
-            client: reqwest::Client::new(),
+            client,
             base_url: base_url.trim_end_matches('/').to_string(),
             auth_header,
         }
@@ -307,7 +483,7 @@ impl JiraClient {
 
     pub async fn get_transitions(&self, issue_key: &str) -> Result<Vec<JiraTransition>, String> {
         let url = format!(
-            "{}/rest/api/3/issue/{}/transitions",
+            "{}/rest/api/3/issue/{}/transitions?expand=transitions.fields",
             self.base_url, issue_key
         );
 
@@ -333,24 +509,65 @@ impl JiraClient {
         let transitions = result
             .transitions
             .into_iter()
-            .map(|t| JiraTransition {
-                id: t.id,
-                name: t.name,
+            .map(|t| {
+                let mut fields: Vec<JiraTransitionField> = t
+                    .fields
+                    .into_iter()
+                    .map(|(key, f)| JiraTransitionField {
+                        name: if f.name.is_empty() { key.clone() } else { f.name },
+                        key,
+                        required: f.required,
+                        field_type: f.schema.as_ref().map(|s| s.field_type.clone()).unwrap_or_default(),
+                        items_type: f.schema.and_then(|s| s.items),
+                        allowed_values: f
+                            .allowed_values
+                            .into_iter()
+                            .map(|v| JiraAllowedValue {
+                                name: v.name.or(v.value).unwrap_or_else(|| v.id.clone()),
+                                id: v.id,
+                            })
+                            .collect(),
+                    })
+                    .collect();
+                fields.sort_by(|a, b| b.required.cmp(&a.required).then(a.name.cmp(&b.name)));
+
+                JiraTransition {
+                    id: t.id,
+                    name: t.name,
+                    has_screen: t.has_screen,
+                    fields,
+                }
             })
             .collect();
 
         Ok(transitions)
     }
 
-    pub async fn transition_issue(&self, issue_key: &str, transition_id: &str) -> Result<(), String> {
+    /// Apply a transition. `fields` holds values for the transition screen in Jira's
+    /// format (e.g. `{"resolution": {"id": "10000"}}`), `comment` is Markdown.
+    pub async fn transition_issue(
+        &self,
+        issue_key: &str,
+        transition_id: &str,
+        fields: Option<&serde_json::Map<String, serde_json::Value>>,
+        comment: Option<&str>,
+    ) -> Result<(), String> {
         let url = format!(
             "{}/rest/api/3/issue/{}/transitions",
             self.base_url, issue_key
         );
 
-        let body = serde_json::json!({
+        let mut body = serde_json::json!({
             "transition": { "id": transition_id }
         });
+        if let Some(fields) = fields.filter(|f| !f.is_empty()) {
+            body["fields"] = serde_json::Value::Object(fields.clone());
+        }
+        if let Some(comment) = comment.filter(|c| !c.trim().is_empty()) {
+            body["update"] = serde_json::json!({
+                "comment": [{ "add": { "body": adf::from_markdown(comment) } }]
+            });
+        }
 
         let response = self
             .client
@@ -364,7 +581,7 @@ impl JiraClient {
         if !response.status().is_success() {
             let status = response.status();
             let body = response.text().await.unwrap_or_default();
-            return Err(format!("Transition error {}: {}", status, body));
+            return Err(format!("Transition error {}: {}", status, describe_error_body(&body)));
         }
 
         Ok(())
@@ -395,11 +612,7 @@ impl JiraClient {
             .await
             .map_err(|e| format!("Parse error: {}", e))?;
 
-        let description = issue
-            .fields
-            .description
-            .map(|d| extract_adf_text(&d))
-            .unwrap_or_default();
+        let description = issue.fields.description.as_ref();
 
         let time_tracking = issue.fields.timetracking.as_ref();
 
@@ -407,7 +620,9 @@ impl JiraClient {
             key: issue.key,
             summary: issue.fields.summary,
             status: issue.fields.status.name,
-            description,
+            description: description.map(adf::to_plain_text).unwrap_or_default(),
+            description_html: description.map(adf::to_html).unwrap_or_default(),
+            description_markdown: description.map(adf::to_markdown).unwrap_or_default(),
             priority: issue.fields.priority.map(|p| p.name).unwrap_or_default(),
             assignee: issue.fields.assignee.map(|a| a.display_name).unwrap_or_default(),
             reporter: issue.fields.reporter.map(|r| r.display_name).unwrap_or_default(),
@@ -421,6 +636,61 @@ impl JiraClient {
         })
     }
 
+    /// Fetch one page of comments, newest first.
+    pub async fn get_issue_comments(
+        &self,
+        issue_key: &str,
+        start_at: u32,
+        max_results: u32,
+    ) -> Result<JiraCommentPage, String> {
+        let url = format!(
+            "{}/rest/api/3/issue/{}/comment?orderBy=-created&startAt={}&maxResults={}",
+            self.base_url, issue_key, start_at, max_results
+        );
+
+        let response = self
+            .client
+            .get(&url)
+            .headers(self.headers())
+            .send()
+            .await
+            .map_err(|e| format!("Request failed: {}", e))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(format!("Jira API error {}: {}", status, body));
+        }
+
+        let result: CommentsResponse = response
+            .json()
+            .await
+            .map_err(|e| format!("Parse error: {}", e))?;
+
+        let comments = result
+            .comments
+            .into_iter()
+            .map(|c| JiraComment {
+                id: c.id,
+                author: c.author.map(|a| a.display_name).unwrap_or_default(),
+                created: c.created.unwrap_or_default(),
+                updated: c.updated.unwrap_or_default(),
+                body: c.body.as_ref().map(adf::to_plain_text).unwrap_or_default(),
+                body_html: c.body.as_ref().map(adf::to_html).unwrap_or_default(),
+                visibility: c.visibility.map(|v| JiraCommentVisibility {
+                    kind: v.kind,
+                    value: v.value,
+                }),
+            })
+            .collect();
+
+        Ok(JiraCommentPage {
+            comments,
+            start_at: result.start_at,
+            total: result.total,
+        })
+    }
+
     async fn get_myself(&self) -> Result<String, String> {
         let url = format!("{}/rest/api/3/myself", self.base_url);
 
@@ -474,7 +744,14 @@ impl JiraClient {
         Ok(result.worklogs)
     }
 
-    pub async fn get_my_worklogs(&self, start_date: &str, end_date: &str) -> Result<Vec<TimesheetEntry>, String> {
+    /// The issues the user logged time on between two dates, each with the
+    /// user's worklogs dated in that range.
+    async fn my_worklog_issues(
+        &self,
+        start_date: &str,
+        end_date: &str,
+        fields: &str,
+    ) -> Result<Vec<(WorklogIssue, Vec<WorklogEntry>)>, String> {
         let account_id = self.get_myself().await?;
 
         let jql = format!(
@@ -489,9 +766,10 @@ impl JiraClient {
 
         loop {
             let url = format!(
-                "{}/rest/api/3/search?jql={}&fields=summary,worklog&maxResults={}&startAt={}",
+                "{}/rest/api/3/search?jql={}&fields={}&maxResults={}&startAt={}",
                 self.base_url,
                 urlencoding::encode(&jql),
+                fields,
                 page_size,
                 start_at
             );
@@ -515,13 +793,20 @@ impl JiraClient {
                 .await
                 .map_err(|e| format!("Parse error: {}", e))?;
 
+            // Jira may cap maxResults below what we asked for, so advance by what
+            // was actually returned and stop on the reported total. Without one,
+            // keep going while pages are full.
             let fetched = search.issues.len() as u32;
             all_issues.extend(search.issues);
+            start_at += fetched;
 
-            if fetched < page_size {
+            let done = match search.total {
+                Some(total) => start_at >= total,
+                None => fetched < page_size,
+            };
+            if fetched == 0 || done {
                 break;
             }
-            start_at += page_size;
         }
 
         let start = NaiveDate::parse_from_str(start_date, "%Y-%m-%d")
@@ -534,7 +819,7 @@ impl JiraClient {
             .and_utc()
             .timestamp_millis();
 
-        let mut entries: Vec<TimesheetEntry> = Vec::new();
+        let mut result = Vec::new();
 
         for issue in all_issues {
             let worklogs = if let Some(ref container) = issue.fields.worklog {
@@ -547,21 +832,33 @@ impl JiraClient {
                 self.get_issue_worklogs(&issue.key, started_after_ms).await?
             };
 
+            let mine: Vec<WorklogEntry> = worklogs
+                .into_iter()
+                .filter(|w| w.author.account_id == account_id)
+                .filter(|w| {
+                    NaiveDate::parse_from_str(&extract_date_from_started(&w.started), "%Y-%m-%d")
+                        .is_ok_and(|d| d >= start && d <= end)
+                })
+                .collect();
+            result.push((issue, mine));
+        }
+
+        Ok(result)
+    }
+
+    pub async fn get_my_worklogs(&self, start_date: &str, end_date: &str) -> Result<Vec<TimesheetEntry>, String> {
+        let issues = self.my_worklog_issues(start_date, end_date, "summary,worklog").await?;
+
+        let mut entries: Vec<TimesheetEntry> = Vec::new();
+        for (issue, worklogs) in issues {
             for worklog in worklogs {
-                if worklog.author.account_id != account_id {
-                    continue;
-                }
-                let date = extract_date_from_started(&worklog.started);
-                if let Ok(d) = NaiveDate::parse_from_str(&date, "%Y-%m-%d") {
-                    if d >= start && d <= end {
-                        entries.push(TimesheetEntry {
-                            issue_key: issue.key.clone(),
-                            summary: issue.fields.summary.clone(),
-                            date,
-                            time_spent_seconds: worklog.time_spent_seconds,
-                        });
-                    }
-                }
+                entries.push(TimesheetEntry {
+                    issue_key: issue.key.clone(),
+                    summary: issue.fields.summary.clone(),
+                    date: extract_date_from_started(&worklog.started),
+                    time_spent_seconds: worklog.time_spent_seconds,
+                    started: parse_started(&worklog.started),
+                });
             }
         }
 
@@ -570,15 +867,105 @@ impl JiraClient {
         Ok(entries)
     }
 
-    pub async fn log_worklog(&self, issue_key: &str, seconds: u64) -> Result<(), String> {
+    /// The user's worklogs between two dates with each issue's project,
+    /// type, parent, components and labels.
+    pub async fn get_report_worklogs(&self, start_date: &str, end_date: &str) -> Result<Vec<ReportWorklog>, String> {
+        let issues = self
+            .my_worklog_issues(
+                start_date,
+                end_date,
+                "summary,worklog,project,issuetype,parent,components,labels",
+            )
+            .await?;
+
+        let mut worklogs: Vec<ReportWorklog> = Vec::new();
+        for (issue, entries) in issues {
+            let fields = &issue.fields;
+            for entry in entries {
+                worklogs.push(ReportWorklog {
+                    issue_key: issue.key.clone(),
+                    summary: fields.summary.clone(),
+                    date: extract_date_from_started(&entry.started),
+                    seconds: entry.time_spent_seconds,
+                    project_key: fields.project.as_ref().map_or_else(String::new, |p| p.key.clone()),
+                    project_name: fields.project.as_ref().map_or_else(String::new, |p| p.name.clone()),
+                    issue_type: fields.issuetype.as_ref().map_or_else(String::new, |t| t.name.clone()),
+                    parent: fields.parent.as_ref().map(|p| {
+                        let summary = p.fields.as_ref().map_or_else(String::new, |f| f.summary.clone());
+                        (p.key.clone(), summary)
+                    }),
+                    components: fields.components.iter().map(|c| c.name.clone()).collect(),
+                    labels: fields.labels.clone(),
+                });
+            }
+        }
+
+        worklogs.sort_by(|a, b| a.date.cmp(&b.date).then(a.issue_key.cmp(&b.issue_key)));
+
+        Ok(worklogs)
+    }
+
+    /// Post a comment on an issue. `markdown` is converted to ADF.
+    pub async fn add_comment(&self, issue_key: &str, markdown: &str) -> Result<(), String> {
+        if markdown.trim().is_empty() {
+            return Err("Comment is empty".to_string());
+        }
+
         let url = format!(
-            "{}/rest/api/3/issue/{}/worklog",
+            "{}/rest/api/3/issue/{}/comment",
             self.base_url, issue_key
         );
 
         let body = serde_json::json!({
+            "body": adf::from_markdown(markdown)
+        });
+
+        let response = self
+            .client
+            .post(&url)
+            .headers(self.headers())
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| format!("Request failed: {}", e))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(format!("Comment error {}: {}", status, body));
+        }
+
+        Ok(())
+    }
+
+    /// Log time on an issue. `comment` is Markdown and is converted to ADF.
+    pub async fn log_worklog(
+        &self,
+        issue_key: &str,
+        seconds: u64,
+        started: Option<DateTime<Utc>>,
+        comment: Option<&str>,
+    ) -> Result<(), String> {
+        let url = format!(
+            "{}/rest/api/3/issue/{}/worklog",
+            self.base_url, issue_key
+        );
+
+        let mut body = serde_json::json!({
             "timeSpentSeconds": seconds
         });
+        if let Some(started) = started {
+            // Local offset, so the worklog lands on the day the user worked it
+            body["started"] = serde_json::Value::String(
+                started
+                    .with_timezone(&Local)
+                    .format("%Y-%m-%dT%H:%M:%S%.3f%z")
+                    .to_string(),
+            );
+        }
+        if let Some(comment) = comment.filter(|c| !c.trim().is_empty()) {
+            body["comment"] = adf::from_markdown(comment);
+        }
 
         let response = self
             .client
@@ -604,49 +991,31 @@ fn extract_date_from_started(started: &str) -> String {
     started.chars().take(10).collect()
 }
 
-/// Extract plain text from Jira's Atlassian Document Format (ADF) JSON.
-fn extract_adf_text(value: &serde_json::Value) -> String {
-    let mut parts = Vec::new();
-    collect_adf_text(value, &mut parts);
-    parts.join("")
-}
-
-fn collect_adf_text(value: &serde_json::Value, parts: &mut Vec<String>) {
-    match value {
-        serde_json::Value::Object(map) => {
-            let node_type = map.get("type").and_then(|v| v.as_str()).unwrap_or("");
-
-            // Add newlines between block-level nodes
-            if matches!(node_type, "paragraph" | "heading" | "bulletList" | "orderedList" | "listItem" | "codeBlock" | "blockquote") {
-                if !parts.is_empty() {
-                    let last = parts.last().map(|s| s.as_str()).unwrap_or("");
-                    if !last.is_empty() && !last.ends_with('\n') {
-                        parts.push("\n".to_string());
-                    }
-                }
-            }
-
-            if node_type == "listItem" {
-                parts.push("- ".to_string());
-            }
-
-            // Text node
-            if node_type == "text" {
-                if let Some(text) = map.get("text").and_then(|v| v.as_str()) {
-                    parts.push(text.to_string());
-                }
-            }
-
-            // Recurse into content
-            if let Some(content) = map.get("content") {
-                collect_adf_text(content, parts);
-            }
-        }
-        serde_json::Value::Array(arr) => {
-            for item in arr {
-                collect_adf_text(item, parts);
-            }
-        }
-        _ => {}
+/// Jira's `2024-01-15T09:00:00.000+0100`.
+fn parse_started(started: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_str(started, "%Y-%m-%dT%H:%M:%S%.f%z")
+        .ok()
+        .map(|d| d.with_timezone(&Utc))
+}
+
+/// Turn a Jira error body into a readable message, e.g. "resolution: Resolution is required.".
+/// Bodies that are not Jira error JSON are returned unchanged.
+fn describe_error_body(body: &str) -> String {
+    let Ok(error) = serde_json::from_str::<ErrorResponse>(body) else {
+        return body.to_string();
+    };
+
+    let mut field_errors: Vec<String> = error
+        .errors
+        .into_iter()
+        .map(|(field, message)| format!("{}: {}", field, message))
+        .collect();
+    field_errors.sort();
+
+    let messages: Vec<String> = error.error_messages.into_iter().chain(field_errors).collect();
+    if messages.is_empty() {
+        body.to_string()
+    } else {
+        messages.join("; ")
     }
 }
@@ -1,23 +1,65 @@
-mod config;
-mod jira;
-mod timer;
+pub mod adf;
+pub mod api;
+pub mod calendar;
+pub mod cli;
+pub mod config;
+pub mod drafts;
+pub mod git;
+pub mod idle;
+pub mod ipc;
+pub mod jira;
+pub mod overlap;
+pub mod reminders;
+pub mod report;
+pub mod rounding;
+pub mod shortcuts;
+pub mod store;
+pub mod templates;
+pub mod ticker;
+pub mod timer;
+pub mod tray;
+pub mod worklog;
 
+use calendar::{
+    ImportedEvents, draft_calendar_worklogs, get_calendar_import, log_calendar_worklogs, preview_calendar,
+    save_calendar_import,
+};
 use config::{ConfigState, get_config, save_config};
-use jira::{JiraClient, JiraProject, JiraTicketDetail, JiraTransition, TimesheetEntry};
-use timer::{HistoryEntry, TimerState, get_history, get_timers, pause_timer, resume_timer, set_timer_elapsed, start_timer, stop_timer};
+use drafts::{DraftStore, add_drafts, clear_submitted_drafts, delete_draft, get_drafts, submit_drafts, update_draft};
+use git::{get_git_watch, save_git_watch};
+use idle::resolve_idle_span;
+use overlap::{check_drafts, check_timer, check_worklogs};
+use reminders::{get_reminders, save_reminders};
+use report::get_report;
+use rounding::{RoundingConfig, get_rounding, save_rounding};
+use shortcuts::{get_shortcuts, save_shortcuts};
+use templates::{TemplateStore, apply_template, delete_template, get_templates, save_template};
+use ticker::TickerState;
+use worklog::{Allocation, LogOutcome, PendingWorklog, WorklogQueue};
+use jira::{JiraClient, JiraCommentPage, JiraProject, JiraTicketDetail, JiraTransition, TimesheetEntry};
+use timer::{TimerState, get_history, get_timeline, get_timer_policy, get_timers, pause_timer, reassign_timer, resume_timer, set_timer_elapsed, set_timer_policy, start_timer, stop_timer};
 
-use tauri::{
-    Manager,
-    menu::{Menu, MenuItem},
-    tray::TrayIconBuilder,
-};
+use tauri::{Emitter, Manager};
+
+const COMMENTS_PAGE_SIZE: u32 = 20;
+
+/// Emitted when quitting would drop timers or queued worklogs, so the
+/// frontend can ask whether to log, keep or discard them.
+const QUIT_REQUESTED: &str = "app://quit-requested";
 
 fn build_client(config_state: &tauri::State<'_, ConfigState>) -> Result<JiraClient, String> {
     let config = config_state.config.lock().map_err(|e| e.to_string())?;
-    if config.jira_url.is_empty() || config.email.is_empty() || config.api_token.is_empty() {
-        return Err("Jira not configured. Please set URL, email and API token in Settings.".to_string());
-    }
-    Ok(JiraClient::new(&config.jira_url, &config.email, &config.api_token))
+    config.jira_client()
+}
+
+fn rounding_config(config_state: &tauri::State<'_, ConfigState>) -> Result<RoundingConfig, String> {
+    let config = config_state.config.lock().map_err(|e| e.to_string())?;
+    Ok(config.rounding.clone())
+}
+
+fn draft_mode(config_state: &tauri::State<'_, ConfigState>) -> Result<bool, String> {
+    let config = config_state.config.lock().map_err(|e| e.to_string())?;
+    Ok(config.draft_mode)
 }
 
 #[tauri::command]
@@ -60,9 +102,13 @@ async fn transition_issue(
     config_state: tauri::State<'_, ConfigState>,
     issue_key: String,
     transition_id: String,
+    fields: Option<serde_json::Map<String, serde_json::Value>>,
+    comment: Option<String>,
 ) -> Result<(), String> {
     let client = build_client(&config_state)?;
-    client.transition_issue(&issue_key, &transition_id).await
+    client
+        .transition_issue(&issue_key, &transition_id, fields.as_ref(), comment.as_deref())
+        .await
 }
 
 #[tauri::command]
@@ -75,134 +121,473 @@ async fn get_my_worklogs(
     client.get_my_worklogs(&start_date, &end_date).await
 }
 
-fn record_history(timer_state: &tauri::State<'_, TimerState>, timer: &timer::Timer, logged: bool) {
-    if let Ok(mut history) = timer_state.history.lock() {
-        history.push(HistoryEntry {
-            issue_key: timer.issue_key.clone(),
-            summary: timer.summary.clone(),
-            elapsed_seconds: timer.elapsed_seconds,
-            logged,
-            stopped_at: chrono::Utc::now(),
-        });
-    }
+#[tauri::command]
+async fn get_issue_comments(
+    config_state: tauri::State<'_, ConfigState>,
+    issue_key: String,
+    start_at: Option<u32>,
+) -> Result<JiraCommentPage, String> {
+    let client = build_client(&config_state)?;
+    client
+        .get_issue_comments(&issue_key, start_at.unwrap_or(0), COMMENTS_PAGE_SIZE)
+        .await
+}
+
+#[tauri::command]
+async fn add_comment(
+    config_state: tauri::State<'_, ConfigState>,
+    issue_key: String,
+    markdown: String,
+) -> Result<(), String> {
+    let client = build_client(&config_state)?;
+    client.add_comment(&issue_key, &markdown).await
+}
+
+fn record_history(timer_state: &tauri::State<'_, TimerState>, timer: &timer::Timer, logged_seconds: Option<u64>) {
+    timer_state.record_history(timer, logged_seconds, chrono::Utc::now());
 }
 
 #[tauri::command]
 async fn discard_timer(
+    app: tauri::AppHandle,
     timer_state: tauri::State<'_, TimerState>,
     timer_id: u32,
 ) -> Result<(), String> {
-    let timer = stop_timer(timer_state.clone(), timer_id)?;
-    record_history(&timer_state, &timer, false);
+    let timer = stop_timer(app, timer_state.clone(), timer_id)?;
+    record_history(&timer_state, &timer, None);
     Ok(())
 }
 
 #[tauri::command]
 async fn stop_and_log(
+    app: tauri::AppHandle,
     timer_state: tauri::State<'_, TimerState>,
     config_state: tauri::State<'_, ConfigState>,
+    queue: tauri::State<'_, WorklogQueue>,
+    drafts: tauri::State<'_, DraftStore>,
     timer_id: u32,
+    comment: Option<String>,
+    per_segment: Option<bool>,
 ) -> Result<u64, String> {
-    let timer = stop_timer(timer_state.clone(), timer_id)?;
+    let rounding = rounding_config(&config_state)?;
+    let now = chrono::Utc::now();
+    let timer = timer_state
+        .timers
+        .lock()
+        .map_err(|e| e.to_string())?
+        .iter()
+        .find(|t| t.id == timer_id)
+        .cloned()
+        .ok_or("Timer not found")?;
+    // Checked before stopping, so a timer too short to log keeps running
+    let requests =
+        worklog::timer_requests(&rounding, &timer, per_segment.unwrap_or(false), comment.as_deref(), now)?;
+    let logged_seconds = requests.iter().map(|r| r.seconds).sum();
 
-    if timer.elapsed_seconds < 60 {
-        return Err("Worklog must be at least 1 minute".to_string());
+    if draft_mode(&config_state)? {
+        // Drafts first, so a timer is never stopped without its time saved
+        let ids = drafts.add(requests, now)?;
+        let timer = match stop_timer(app.clone(), timer_state.clone(), timer_id) {
+            Ok(timer) => timer,
+            Err(e) => {
+                for id in ids {
+                    let _ = drafts.remove(id);
+                }
+                return Err(e);
+            }
+        };
+        drafts::emit_changed(&app, &drafts);
+        record_history(&timer_state, &timer, None);
+        return Ok(logged_seconds);
     }
 
     let client = build_client(&config_state)?;
+    let timer = stop_timer(app.clone(), timer_state.clone(), timer_id)?;
 
-    client
-        .log_worklog(&timer.issue_key, timer.elapsed_seconds)
-        .await?;
+    let outcome = match worklog::log_all(&client, &queue, requests).await {
+        Ok(outcome) => outcome,
+        Err(e) => {
+            // Nothing was logged, so the timer goes back (paused)
+            timer_state.restore(vec![timer])?;
+            timer::emit_changed(&app, &timer_state);
+            return Err(e);
+        }
+    };
+
+    record_history(&timer_state, &timer, Some(logged_seconds));
+
+    if outcome.queued > 0 {
+        return Err(format!(
+            "Logged {} worklog(s), {} queued for retry",
+            outcome.logged, outcome.queued
+        ));
+    }
+    Ok(logged_seconds)
+}
+
+#[tauri::command]
+async fn split_and_log(
+    app: tauri::AppHandle,
+    timer_state: tauri::State<'_, TimerState>,
+    config_state: tauri::State<'_, ConfigState>,
+    queue: tauri::State<'_, WorklogQueue>,
+    drafts: tauri::State<'_, DraftStore>,
+    timer_id: u32,
+    allocations: Vec<Allocation>,
+    comment: Option<String>,
+) -> Result<LogOutcome, String> {
+    let now = chrono::Utc::now();
+    let parts = {
+        let timers = timer_state.timers.lock().map_err(|e| e.to_string())?;
+        let timer = timers
+            .iter()
+            .find(|t| t.id == timer_id)
+            .ok_or("Timer not found")?;
+        timer.worklog_parts(true, now)
+    };
+
+    let mut requests = worklog::split_worklogs(&allocations, &parts, comment.as_deref())?;
+    rounding_config(&config_state)?.apply(&mut requests);
+    let logged_seconds = requests.iter().map(|r| r.seconds).sum();
+
+    if draft_mode(&config_state)? {
+        let count = requests.len();
+        let ids = drafts.add(requests, now)?;
+        let timer = match stop_timer(app.clone(), timer_state.clone(), timer_id) {
+            Ok(timer) => timer,
+            Err(e) => {
+                for id in ids {
+                    let _ = drafts.remove(id);
+                }
+                return Err(e);
+            }
+        };
+        drafts::emit_changed(&app, &drafts);
+        record_history(&timer_state, &timer, None);
+        return Ok(LogOutcome { logged: count, queued: 0 });
+    }
+
+    let client = build_client(&config_state)?;
+
+    // The timer is only stopped once at least one worklog went through, so a
+    // failure on the first one leaves everything as it was
+    let outcome = worklog::log_all(&client, &queue, requests).await?;
+
+    // Already logged, so a timer discarded meanwhile is not an error
+    if let Ok(timer) = stop_timer(app, timer_state.clone(), timer_id) {
+        record_history(&timer_state, &timer, Some(logged_seconds));
+    }
+
+    Ok(outcome)
+}
+
+/// Stop and log a timer outside of the frontend (tray, shortcuts), reporting
+/// the outcome with a notification.
+pub(crate) fn spawn_stop_and_log(app: &tauri::AppHandle, timer_id: u32) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let draft_mode = app.state::<ConfigState>().config.lock().is_ok_and(|c| c.draft_mode);
+        let logged = stop_and_log(
+            app.clone(),
+            app.state(),
+            app.state(),
+            app.state(),
+            app.state(),
+            timer_id,
+            None,
+            None,
+        )
+        .await;
+        let body = match logged {
+            Ok(seconds) if draft_mode => format!("Saved {} as draft", reminders::format_hours(seconds)),
+            Ok(seconds) => format!("Logged {} to Jira", reminders::format_hours(seconds)),
+            Err(e) => e,
+        };
+        reminders::notify(&app, &body);
+    });
+}
 
-    record_history(&timer_state, &timer, true);
+#[tauri::command]
+fn get_pending_worklogs(queue: tauri::State<'_, WorklogQueue>) -> Result<Vec<PendingWorklog>, String> {
+    queue.list()
+}
+
+#[tauri::command]
+fn drop_pending_worklog(queue: tauri::State<'_, WorklogQueue>, id: u32) -> Result<(), String> {
+    queue.remove(id)
+}
+
+#[tauri::command]
+async fn retry_pending_worklogs(
+    config_state: tauri::State<'_, ConfigState>,
+    queue: tauri::State<'_, WorklogQueue>,
+) -> Result<LogOutcome, String> {
+    let client = build_client(&config_state)?;
+    worklog::retry_all(&client, &queue).await
+}
+
+#[derive(Clone, serde::Serialize)]
+struct QuitSummary {
+    timers: usize,
+    running: usize,
+    pending: usize,
+}
+
+#[derive(Debug, Clone, Copy, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum QuitAction {
+    /// Log every timer and retry queued worklogs; what fails is queued and
+    /// timers under a minute are kept. In draft mode the timers become
+    /// drafts instead.
+    Log,
+    /// Keep timers (paused) and queued worklogs for the next launch.
+    Keep,
+    /// Drop the timers. Queued worklogs are still kept.
+    Discard,
+}
+
+/// Quit right away when nothing would be lost, otherwise ask the frontend.
+fn request_quit(app: &tauri::AppHandle) {
+    let timer_state = app.state::<TimerState>();
+    let queue = app.state::<WorklogQueue>();
+    let (timers, running) = timer_state
+        .timers
+        .lock()
+        .map(|t| (t.len(), t.iter().filter(|t| t.is_running()).count()))
+        .unwrap_or_default();
+    let pending = queue.pending.lock().map(|p| p.len()).unwrap_or_default();
 
-    Ok(timer.elapsed_seconds)
+    if timers == 0 && pending == 0 {
+        app.exit(0);
+        return;
+    }
+    show_main_window(app);
+    let _ = app.emit(QUIT_REQUESTED, QuitSummary { timers, running, pending });
+}
+
+#[tauri::command]
+async fn quit_app(
+    app: tauri::AppHandle,
+    timer_state: tauri::State<'_, TimerState>,
+    config_state: tauri::State<'_, ConfigState>,
+    queue: tauri::State<'_, WorklogQueue>,
+    drafts: tauri::State<'_, DraftStore>,
+    action: QuitAction,
+) -> Result<(), String> {
+    let now = chrono::Utc::now();
+    match action {
+        QuitAction::Log => {
+            let rounding = rounding_config(&config_state)?;
+            let snapshot = timer_state.timers.lock().map_err(|e| e.to_string())?.clone();
+            // A timer too short to log is kept (paused) for the next launch
+            // rather than dropped
+            let mut to_log = Vec::new();
+            let mut kept = 0;
+            for timer in snapshot {
+                match worklog::timer_requests(&rounding, &timer, false, None, now) {
+                    Ok(requests) => to_log.push((timer.id, requests)),
+                    Err(_) => kept += 1,
+                }
+            }
+
+            if draft_mode(&config_state)? {
+                // Drafts are added before any timer stops, so a failure keeps them all
+                drafts.add(to_log.iter().flat_map(|(_, r)| r.clone()).collect(), now)?;
+                for (timer_id, _) in &to_log {
+                    let timer = timer_state.stop(*timer_id, now)?;
+                    record_history(&timer_state, &timer, None);
+                }
+            } else {
+                let client = build_client(&config_state);
+                if let Ok(client) = &client {
+                    worklog::retry_all(client, &queue).await?;
+                }
+
+                for (timer_id, requests) in to_log {
+                    let timer = timer_state.stop(timer_id, now)?;
+                    let result = match &client {
+                        Ok(client) => worklog::log_all(client, &queue, requests.clone()).await,
+                        Err(e) => Err(e.clone()),
+                    };
+                    // Only what reached Jira counts as logged, the rest is queued
+                    let logged = match result {
+                        Ok(outcome) => outcome.logged,
+                        Err(e) => {
+                            for request in &requests {
+                                queue.push(request.clone(), &e)?;
+                            }
+                            0
+                        }
+                    };
+                    let logged_seconds: u64 = requests.iter().take(logged).map(|r| r.seconds).sum();
+                    record_history(&timer_state, &timer, (logged > 0).then_some(logged_seconds));
+                }
+            }
+
+            timer_state.pause_all(now)?;
+            if kept > 0 {
+                reminders::notify(
+                    &app,
+                    &format!("{} timer(s) under a minute kept for the next launch", kept),
+                );
+            }
+        }
+        QuitAction::Keep => timer_state.pause_all(now)?,
+        QuitAction::Discard => {
+            // Only the timers: queued worklogs are older failures, kept for retry
+            for timer in timer_state.stop_all(now)? {
+                record_history(&timer_state, &timer, None);
+            }
+        }
+    }
+
+    let timers = timer_state.timers.lock().map_err(|e| e.to_string())?.clone();
+    let session = store::SavedSession {
+        timers,
+        pending: queue.list()?,
+        saved_at: now,
+    };
+    store::save(&store::session_path()?, &session)?;
+    app.exit(0);
+    Ok(())
+}
+
+/// Bring back the timers and worklogs kept when the app last quit. The file
+/// is removed once loaded, so nothing is restored (and logged) twice.
+fn restore_session(app: &tauri::AppHandle) -> Result<(), String> {
+    let path = store::session_path()?;
+    let Some(session) = store::load(&path)? else {
+        return Ok(());
+    };
+
+    let timer_state = app.state::<TimerState>();
+    timer_state.restore(session.timers)?;
+    timer_state.split_at_midnight(chrono::Utc::now())?;
+    app.state::<WorklogQueue>().restore(session.pending)?;
+    store::clear(&path)
+}
+
+pub(crate) fn show_main_window(app: &tauri::AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.show();
+        let _ = window.set_focus();
+        ticker::set_visible(app, true);
+    }
+}
+
+fn toggle_main_window(app: &tauri::AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        if window.is_visible().unwrap_or(false) {
+            let _ = window.hide();
+            ticker::set_visible(app, false);
+        } else {
+            show_main_window(app);
+        }
+    }
 }
 
 pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_sql::Builder::new().build())
+        .plugin(tauri_plugin_notification::init())
         .manage(TimerState::new())
         .manage(ConfigState::new())
+        .manage(TickerState::new())
+        .manage(WorklogQueue::new())
+        // Unreadable data dir: drafts still work, they just don't outlive the app
+        .manage(drafts::drafts_path().map_or_else(|_| DraftStore::new(), DraftStore::open))
+        .manage(templates::templates_path().map_or_else(|_| TemplateStore::new(), TemplateStore::open))
+        .manage(calendar::imported_path().map_or_else(|_| ImportedEvents::new(), ImportedEvents::open))
         .invoke_handler(tauri::generate_handler![
             list_projects,
             search_tickets,
             get_issue_detail,
+            get_issue_comments,
             get_transitions,
             transition_issue,
             get_my_worklogs,
+            get_report,
+            add_comment,
             start_timer,
             pause_timer,
             resume_timer,
             stop_timer,
             set_timer_elapsed,
+            reassign_timer,
+            resolve_idle_span,
             discard_timer,
             get_timers,
+            get_timer_policy,
+            set_timer_policy,
             get_history,
+            get_timeline,
             stop_and_log,
+            split_and_log,
+            get_pending_worklogs,
+            retry_pending_worklogs,
+            drop_pending_worklog,
+            get_drafts,
+            add_drafts,
+            update_draft,
+            delete_draft,
+            clear_submitted_drafts,
+            submit_drafts,
+            check_worklogs,
+            check_timer,
+            check_drafts,
+            get_templates,
+            save_template,
+            delete_template,
+            apply_template,
             get_config,
             save_config,
+            get_rounding,
+            save_rounding,
+            get_reminders,
+            save_reminders,
+            get_shortcuts,
+            save_shortcuts,
+            get_git_watch,
+            save_git_watch,
+            get_calendar_import,
+            save_calendar_import,
+            preview_calendar,
+            log_calendar_worklogs,
+            draft_calendar_worklogs,
+            quit_app,
         ])
         .setup(|app| {
-            // System tray
-            let show = MenuItem::with_id(app, "show", "Show", true, None::<&str>)?;
-            let quit = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
-            let menu = Menu::with_items(app, &[&show, &quit])?;
-
-            let _tray = TrayIconBuilder::new()
-                .menu(&menu)
-                .tooltip("Jira Timesheet")
-                .on_menu_event(|app, event| match event.id.as_ref() {
-                    "show" => {
-                        if let Some(window) = app.get_webview_window("main") {
-                            let _ = window.show();
-                            let _ = window.set_focus();
-                        }
-                    }
-                    "quit" => {
-                        app.exit(0);
-                    }
-                    _ => {}
-                })
-                .on_tray_icon_event(|tray, event| {
-                    if let tauri::tray::TrayIconEvent::Click { .. } = event {
-                        if let Some(window) = tray.app_handle().get_webview_window("main") {
-                            let _ = window.show();
-                            let _ = window.set_focus();
-                        }
-                    }
-                })
-                .build(app)?;
+            // A broken session file must not keep the app from starting
+            let _ = restore_session(app.handle());
+
+            tray::create(app.handle())?;
 
-            // Global shortcut Ctrl+Shift+T
             #[cfg(desktop)]
-            {
-                use tauri_plugin_global_shortcut::{Code, Modifiers, ShortcutState};
-
-                app.handle().plugin(
-                    tauri_plugin_global_shortcut::Builder::new()
-                        .with_shortcuts(["ctrl+shift+t"])?
-                        .with_handler(|app, shortcut, event| {
-                            if event.state == ShortcutState::Pressed
-                                && shortcut.matches(Modifiers::CONTROL | Modifiers::SHIFT, Code::KeyT)
-                            {
-                                if let Some(window) = app.get_webview_window("main") {
-                                    if window.is_visible().unwrap_or(false) {
-                                        let _ = window.hide();
-                                    } else {
-                                        let _ = window.show();
-                                        let _ = window.set_focus();
-                                    }
-                                }
-                            }
-                        })
-                        .build(),
-                )?;
-            }
+            shortcuts::init(app.handle())?;
+
+            ticker::spawn(app.handle().clone());
+            idle::spawn(app.handle().clone());
+            reminders::spawn(app.handle().clone());
+            templates::spawn(app.handle().clone());
+            timer::spawn_rollover(app.handle().clone());
+            ipc::spawn(app.handle().clone());
+            api::spawn(app.handle().clone());
+            git::spawn(app.handle().clone());
 
             Ok(())
         })
+        .on_window_event(|window, event| {
+            match event {
+                // Catch the window coming back from being minimized
+                tauri::WindowEvent::Focused(true) => ticker::set_visible(window.app_handle(), true),
+                // Closing the window quits, so it goes through the same check as the tray
+                tauri::WindowEvent::CloseRequested { api, .. } => {
+                    api.prevent_close();
+                    request_quit(window.app_handle());
+                }
+                _ => {}
+            }
+        })
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
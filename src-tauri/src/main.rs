@@ -1,5 +1,10 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 fn main() {
-    jira_timesheet::run();
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.is_empty() {
+        jira_timesheet::run();
+    } else {
+        std::process::exit(jira_timesheet::cli::run(&args));
+    }
 }
@@ -0,0 +1,234 @@
+use crate::config::ConfigState;
+use crate::drafts::{DraftStatus, DraftStore};
+use crate::jira::TimesheetEntry;
+use crate::timer::TimerState;
+use crate::worklog::{self, WorklogQueue, WorklogRequest};
+use chrono::{DateTime, Duration, Local, NaiveDate, Utc};
+use serde::Serialize;
+
+/// Worklogs starting within this many seconds of each other, on the same
+/// issue and for the same time, are the same worklog.
+const DUPLICATE_TOLERANCE_SECONDS: i64 = 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConflictKind {
+    /// Same issue, start and duration.
+    Duplicate,
+    /// The intervals share some time.
+    Overlap,
+}
+
+/// What a checked worklog conflicts with.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "source", rename_all = "lowercase")]
+pub enum ConflictWith {
+    /// A worklog already in Jira.
+    Logged { summary: String },
+    /// Another worklog of the same check, by index.
+    Checked { other_index: usize },
+    /// A worklog queued for retry, a draft or another timer's time.
+    Pending,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Conflict {
+    /// Index of the checked worklog.
+    pub index: usize,
+    pub kind: ConflictKind,
+    #[serde(flatten)]
+    pub with: ConflictWith,
+    /// The other worklog.
+    pub issue_key: String,
+    pub started: DateTime<Utc>,
+    pub seconds: u64,
+    /// Time both worklogs cover.
+    pub overlap_seconds: u64,
+}
+
+/// Start and end of a worklog.
+fn interval(started: DateTime<Utc>, seconds: u64) -> (DateTime<Utc>, DateTime<Utc>) {
+    (started, started + Duration::seconds(seconds as i64))
+}
+
+fn compare(
+    index: usize,
+    worklog: &WorklogRequest,
+    with: ConflictWith,
+    issue_key: &str,
+    started: DateTime<Utc>,
+    seconds: u64,
+) -> Option<Conflict> {
+    let (start, end) = interval(worklog.started, worklog.seconds);
+    let (other_start, other_end) = interval(started, seconds);
+    let overlap_seconds = (end.min(other_end) - start.max(other_start)).num_seconds();
+    if overlap_seconds <= 0 {
+        return None;
+    }
+
+    let duplicate = worklog.issue_key.eq_ignore_ascii_case(issue_key)
+        && worklog.seconds == seconds
+        && (worklog.started - started).num_seconds().abs() < DUPLICATE_TOLERANCE_SECONDS;
+    Some(Conflict {
+        index,
+        kind: if duplicate {
+            ConflictKind::Duplicate
+        } else {
+            ConflictKind::Overlap
+        },
+        with,
+        issue_key: issue_key.to_string(),
+        started,
+        seconds,
+        overlap_seconds: overlap_seconds as u64,
+    })
+}
+
+/// Compare each worklog about to be logged with the user's worklogs in Jira,
+/// the other worklogs being checked and `pending` ones (queued or drafts).
+/// Two checked worklogs that conflict are reported once, on the later one.
+pub fn find_conflicts(
+    worklogs: &[WorklogRequest],
+    logged: &[TimesheetEntry],
+    pending: &[WorklogRequest],
+) -> Vec<Conflict> {
+    let mut conflicts = Vec::new();
+    for (index, worklog) in worklogs.iter().enumerate() {
+        for entry in logged {
+            let Some(started) = entry.started else {
+                continue;
+            };
+            let with = ConflictWith::Logged {
+                summary: entry.summary.clone(),
+            };
+            conflicts.extend(compare(index, worklog, with, &entry.issue_key, started, entry.time_spent_seconds));
+        }
+        for (other_index, other) in worklogs.iter().enumerate().take(index) {
+            let with = ConflictWith::Checked { other_index };
+            conflicts.extend(compare(index, worklog, with, &other.issue_key, other.started, other.seconds));
+        }
+        for other in pending {
+            conflicts.extend(compare(
+                index,
+                worklog,
+                ConflictWith::Pending,
+                &other.issue_key,
+                other.started,
+                other.seconds,
+            ));
+        }
+    }
+    conflicts
+}
+
+/// The local days the worklogs fall on, with a day on each side since Jira
+/// dates worklogs in the author's time zone.
+pub fn days_to_fetch(worklogs: &[WorklogRequest]) -> Option<(NaiveDate, NaiveDate)> {
+    let first = worklogs.iter().map(|w| w.started).min()?;
+    let last = worklogs
+        .iter()
+        .map(|w| interval(w.started, w.seconds).1)
+        .max()?;
+    let from = first.with_timezone(&Local).date_naive().pred_opt()?;
+    let to = last.with_timezone(&Local).date_naive().succ_opt()?;
+    Some((from, to))
+}
+
+async fn check(
+    config_state: &ConfigState,
+    worklogs: &[WorklogRequest],
+    pending: &[WorklogRequest],
+) -> Result<Vec<Conflict>, String> {
+    let Some((from, to)) = days_to_fetch(worklogs) else {
+        return Ok(Vec::new());
+    };
+    let client = config_state
+        .config
+        .lock()
+        .map_err(|e| e.to_string())?
+        .jira_client()?;
+    let logged = client
+        .get_my_worklogs(&from.format("%Y-%m-%d").to_string(), &to.format("%Y-%m-%d").to_string())
+        .await?;
+    Ok(find_conflicts(worklogs, &logged, pending))
+}
+
+fn queued(queue: &WorklogQueue) -> Result<Vec<WorklogRequest>, String> {
+    Ok(queue.list()?.into_iter().map(|p| p.request).collect())
+}
+
+/// Queued worklogs and drafts not submitted yet.
+fn pending(queue: &WorklogQueue, drafts: &DraftStore) -> Result<Vec<WorklogRequest>, String> {
+    let mut pending = queued(queue)?;
+    pending.extend(
+        drafts
+            .list()?
+            .into_iter()
+            .filter(|d| d.status != DraftStatus::Submitted)
+            .map(|d| d.request),
+    );
+    Ok(pending)
+}
+
+/// Check worklogs before logging them, against Jira, queued worklogs and
+/// drafts not submitted yet.
+#[tauri::command]
+pub async fn check_worklogs(
+    config_state: tauri::State<'_, ConfigState>,
+    queue: tauri::State<'_, WorklogQueue>,
+    drafts: tauri::State<'_, DraftStore>,
+    worklogs: Vec<WorklogRequest>,
+) -> Result<Vec<Conflict>, String> {
+    let pending = pending(&queue, &drafts)?;
+    check(&config_state, &worklogs, &pending).await
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TimerCheck {
+    /// The worklogs stopping the timer would log, as checked.
+    pub worklogs: Vec<WorklogRequest>,
+    pub conflicts: Vec<Conflict>,
+}
+
+/// Check the worklogs a stop and log would send for a timer, against Jira,
+/// queued worklogs, drafts and the other timers' time.
+#[tauri::command]
+pub async fn check_timer(
+    config_state: tauri::State<'_, ConfigState>,
+    timer_state: tauri::State<'_, TimerState>,
+    queue: tauri::State<'_, WorklogQueue>,
+    drafts: tauri::State<'_, DraftStore>,
+    timer_id: u32,
+    per_segment: Option<bool>,
+) -> Result<TimerCheck, String> {
+    let rounding = config_state.config.lock().map_err(|e| e.to_string())?.rounding.clone();
+    let now = Utc::now();
+    let mut pending = pending(&queue, &drafts)?;
+    let worklogs = {
+        let timers = timer_state.timers.lock().map_err(|e| e.to_string())?;
+        let timer = timers.iter().find(|t| t.id == timer_id).ok_or("Timer not found")?;
+        for other in timers.iter().filter(|t| t.id != timer_id) {
+            pending.extend(worklog::timer_worklogs(other, false, None, now));
+        }
+        worklog::timer_requests(&rounding, timer, per_segment.unwrap_or(false), None, now)?
+    };
+    let conflicts = check(&config_state, &worklogs, &pending).await?;
+    Ok(TimerCheck { worklogs, conflicts })
+}
+
+/// Check the drafts a submit would post. Conflicts are indexed among the
+/// drafts still to submit (draft or failed), in `get_drafts` order.
+#[tauri::command]
+pub async fn check_drafts(
+    config_state: tauri::State<'_, ConfigState>,
+    queue: tauri::State<'_, WorklogQueue>,
+    drafts: tauri::State<'_, DraftStore>,
+) -> Result<Vec<Conflict>, String> {
+    let worklogs: Vec<WorklogRequest> = drafts
+        .list()?
+        .into_iter()
+        .filter(|d| d.is_editable())
+        .map(|d| d.request)
+        .collect();
+    check(&config_state, &worklogs, &queued(&queue)?).await
+}
@@ -0,0 +1,232 @@
+use crate::config::ConfigState;
+use crate::timer::{Timer, TimerState};
+use chrono::{DateTime, Datelike, Local, NaiveDate, NaiveTime, Utc, Weekday};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::env;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_notification::NotificationExt;
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReminderConfig {
+    /// Hours expected to be logged per working day, 0 disables the end-of-day reminder.
+    #[serde(default = "default_daily_target_hours")]
+    pub daily_target_hours: f64,
+    /// Local time of the end-of-day reminder.
+    #[serde(default = "default_end_of_day")]
+    pub end_of_day: NaiveTime,
+    #[serde(default = "default_working_days")]
+    pub working_days: Vec<Weekday>,
+    /// Remind when a timer has been running this long without a pause, 0 disables it.
+    #[serde(default = "default_long_timer_hours")]
+    pub long_timer_hours: u64,
+}
+
+fn default_daily_target_hours() -> f64 {
+    7.0
+}
+
+fn default_end_of_day() -> NaiveTime {
+    NaiveTime::from_hms_opt(17, 30, 0).expect("valid time")
+}
+
+fn default_working_days() -> Vec<Weekday> {
+    vec![
+        Weekday::Mon,
+        Weekday::Tue,
+        Weekday::Wed,
+        Weekday::Thu,
+        Weekday::Fri,
+    ]
+}
+
+fn default_long_timer_hours() -> u64 {
+    3
+}
+
+impl Default for ReminderConfig {
+    fn default() -> Self {
+        Self {
+            daily_target_hours: default_daily_target_hours(),
+            end_of_day: default_end_of_day(),
+            working_days: default_working_days(),
+            long_timer_hours: default_long_timer_hours(),
+        }
+    }
+}
+
+impl ReminderConfig {
+    /// Read `DAILY_TARGET_HOURS`, `END_OF_DAY_REMINDER` (e.g. `17:30`),
+    /// `WORKING_DAYS` (e.g. `mon,tue,wed,thu,fri`) and `LONG_TIMER_HOURS`.
+    /// Missing or invalid values keep their default.
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            daily_target_hours: env::var("DAILY_TARGET_HOURS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.daily_target_hours),
+            end_of_day: env::var("END_OF_DAY_REMINDER")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.end_of_day),
+            working_days: env::var("WORKING_DAYS")
+                .ok()
+                .and_then(|v| v.split(',').map(|d| d.trim().parse().ok()).collect())
+                .unwrap_or(defaults.working_days),
+            long_timer_hours: env::var("LONG_TIMER_HOURS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.long_timer_hours),
+        }
+    }
+
+    /// Whether the end-of-day reminder should fire, given the day it last fired.
+    pub fn daily_reminder_due(&self, now: DateTime<Local>, last_sent: Option<NaiveDate>) -> bool {
+        self.daily_target_hours > 0.0
+            && self.working_days.contains(&now.weekday())
+            && now.time() >= self.end_of_day
+            && last_sent != Some(now.date_naive())
+    }
+}
+
+/// "5h12", or "7h" for whole hours.
+pub(crate) fn format_hours(seconds: u64) -> String {
+    let hours = seconds / 3600;
+    let minutes = (seconds % 3600) / 60;
+    if minutes == 0 {
+        format!("{}h", hours)
+    } else {
+        format!("{}h{:02}", hours, minutes)
+    }
+}
+
+pub fn daily_message(logged_seconds: u64, target_hours: f64) -> String {
+    let target_seconds = (target_hours * 3600.0).round() as u64;
+    format!(
+        "You've logged {} of {} today",
+        format_hours(logged_seconds),
+        format_hours(target_seconds)
+    )
+}
+
+/// Running timers whose current segment reached `hours`, as `(timer id, segment
+/// start, issue key)`. The segment start lets each uninterrupted run be reported once.
+pub fn long_running_timers(
+    timers: &[Timer],
+    hours: u64,
+    now: DateTime<Utc>,
+) -> Vec<(u32, DateTime<Utc>, String)> {
+    if hours == 0 {
+        return Vec::new();
+    }
+    timers
+        .iter()
+        .filter_map(|t| {
+            let segment = t.segments.last().filter(|s| s.end.is_none())?;
+            (segment.seconds_at(now) >= hours * 3600).then(|| (t.id, segment.start, t.issue_key.clone()))
+        })
+        .collect()
+}
+
+pub(crate) fn notify(app: &AppHandle, body: &str) {
+    let _ = app
+        .notification()
+        .builder()
+        .title("Jira Timesheet")
+        .body(body)
+        .show();
+}
+
+pub(crate) fn local_midnight(now: DateTime<Local>) -> DateTime<Utc> {
+    now.date_naive()
+        .and_hms_opt(0, 0, 0)
+        .and_then(|t| t.and_local_timezone(Local).earliest())
+        .map_or(now.with_timezone(&Utc), |t| t.with_timezone(&Utc))
+}
+
+/// Today's time logged in Jira plus what active timers tracked today.
+async fn logged_today(app: &AppHandle, now: DateTime<Local>) -> Result<u64, String> {
+    let today = now.format("%Y-%m-%d").to_string();
+    let client = crate::build_client(&app.state::<ConfigState>())?;
+    let logged: u64 = client
+        .get_my_worklogs(&today, &today)
+        .await?
+        .iter()
+        .map(|e| e.time_spent_seconds)
+        .sum();
+
+    let midnight = local_midnight(now);
+    let now = now.with_timezone(&Utc);
+    let timer_state = app.state::<TimerState>();
+    let timers = timer_state.timers.lock().map_err(|e| e.to_string())?;
+    let unlogged: u64 = timers.iter().map(|t| t.running_between(midnight, now)).sum();
+
+    Ok(logged + unlogged)
+}
+
+fn reminder_config(app: &AppHandle) -> ReminderConfig {
+    let config_state = app.state::<ConfigState>();
+    let config = config_state.config.lock();
+    config.map(|c| c.reminders.clone()).unwrap_or_default()
+}
+
+/// Check once a minute for the end-of-day reminder and long-running timers.
+pub fn spawn(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut last_daily: Option<NaiveDate> = None;
+        let mut reported_runs: HashSet<(u32, DateTime<Utc>)> = HashSet::new();
+
+        loop {
+            tokio::time::sleep(CHECK_INTERVAL).await;
+            let config = reminder_config(&app);
+            let now = Local::now();
+
+            if config.daily_reminder_due(now, last_daily) {
+                // On failure (offline, not configured) try again next minute
+                if let Ok(seconds) = logged_today(&app, now).await {
+                    notify(&app, &daily_message(seconds, config.daily_target_hours));
+                    last_daily = Some(now.date_naive());
+                }
+            }
+
+            let long_runs = {
+                let timer_state = app.state::<TimerState>();
+                let Ok(timers) = timer_state.timers.lock() else {
+                    continue;
+                };
+                long_running_timers(&timers, config.long_timer_hours, now.with_timezone(&Utc))
+            };
+            for (id, start, issue_key) in long_runs {
+                if reported_runs.insert((id, start)) {
+                    notify(
+                        &app,
+                        &format!(
+                            "Timer on {} has been running for over {}h",
+                            issue_key, config.long_timer_hours
+                        ),
+                    );
+                }
+            }
+        }
+    });
+}
+
+#[tauri::command]
+pub fn get_reminders(config_state: tauri::State<'_, ConfigState>) -> Result<ReminderConfig, String> {
+    let config = config_state.config.lock().map_err(|e| e.to_string())?;
+    Ok(config.reminders.clone())
+}
+
+#[tauri::command]
+pub fn save_reminders(
+    config_state: tauri::State<'_, ConfigState>,
+    reminders: ReminderConfig,
+) -> Result<(), String> {
+    let mut config = config_state.config.lock().map_err(|e| e.to_string())?;
+    config.reminders = reminders;
+    Ok(())
+}
@@ -0,0 +1,153 @@
+use crate::config::ConfigState;
+use crate::jira::ReportWorklog;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+
+/// Label of the group for worklogs without a value, e.g. no component.
+pub const NONE_LABEL: &str = "(none)";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Dimension {
+    Project,
+    /// The epic, or the parent issue of a sub-task.
+    Parent,
+    Component,
+    Label,
+    IssueType,
+}
+
+impl Dimension {
+    pub const ALL: [Dimension; 5] = [
+        Dimension::Project,
+        Dimension::Parent,
+        Dimension::Component,
+        Dimension::Label,
+        Dimension::IssueType,
+    ];
+
+    /// The groups a worklog counts in, as `(key, label)`. Components and
+    /// labels can have several values, the worklog then counts in each.
+    fn groups(self, worklog: &ReportWorklog) -> Vec<(String, String)> {
+        let named = |values: &[String]| -> Vec<(String, String)> {
+            values.iter().map(|v| (v.clone(), v.clone())).collect()
+        };
+        let groups = match self {
+            Dimension::Project if !worklog.project_key.is_empty() => {
+                vec![(worklog.project_key.clone(), worklog.project_name.clone())]
+            }
+            Dimension::Parent => worklog.parent.iter().cloned().collect(),
+            Dimension::Component => named(&worklog.components),
+            Dimension::Label => named(&worklog.labels),
+            Dimension::IssueType if !worklog.issue_type.is_empty() => {
+                vec![(worklog.issue_type.clone(), worklog.issue_type.clone())]
+            }
+            _ => Vec::new(),
+        };
+        if groups.is_empty() {
+            return vec![(String::new(), NONE_LABEL.to_string())];
+        }
+        groups
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ReportRow {
+    /// Project or parent issue key, component, label or issue type; empty
+    /// for the `(none)` group.
+    pub key: String,
+    pub label: String,
+    pub seconds: u64,
+    /// Share of the report's total, 0 to 100.
+    pub percent: f64,
+    /// Distinct issues logged on.
+    pub issues: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Breakdown {
+    pub dimension: Dimension,
+    /// Largest first. For components and labels the rows can add up to more
+    /// than the total when issues have several.
+    pub rows: Vec<ReportRow>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Report {
+    pub from: String,
+    pub to: String,
+    pub total_seconds: u64,
+    pub issues: usize,
+    pub breakdowns: Vec<Breakdown>,
+}
+
+/// A `YYYY-MM-DD` date, as typed in the app, the CLI or API queries.
+pub fn parse_date(value: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map_err(|_| format!("Invalid date \"{}\", expected YYYY-MM-DD", value))
+}
+
+/// Time per group of `dimension`.
+pub fn aggregate(worklogs: &[ReportWorklog], dimension: Dimension) -> Vec<ReportRow> {
+    let total: u64 = worklogs.iter().map(|w| w.seconds).sum();
+    let mut groups: BTreeMap<String, (String, u64, HashSet<&str>)> = BTreeMap::new();
+    for worklog in worklogs {
+        for (key, label) in dimension.groups(worklog) {
+            let group = groups.entry(key).or_insert_with(|| (label, 0, HashSet::new()));
+            group.1 += worklog.seconds;
+            group.2.insert(&worklog.issue_key);
+        }
+    }
+
+    let mut rows: Vec<ReportRow> = groups
+        .into_iter()
+        .map(|(key, (label, seconds, issues))| ReportRow {
+            key,
+            label,
+            seconds,
+            percent: if total == 0 {
+                0.0
+            } else {
+                seconds as f64 * 100.0 / total as f64
+            },
+            issues: issues.len(),
+        })
+        .collect();
+    rows.sort_by(|a, b| b.seconds.cmp(&a.seconds).then_with(|| a.key.cmp(&b.key)));
+    rows
+}
+
+pub fn build(from: &str, to: &str, worklogs: &[ReportWorklog], dimensions: &[Dimension]) -> Report {
+    Report {
+        from: from.to_string(),
+        to: to.to_string(),
+        total_seconds: worklogs.iter().map(|w| w.seconds).sum(),
+        issues: worklogs.iter().map(|w| w.issue_key.as_str()).collect::<HashSet<_>>().len(),
+        breakdowns: dimensions
+            .iter()
+            .map(|&dimension| Breakdown {
+                dimension,
+                rows: aggregate(worklogs, dimension),
+            })
+            .collect(),
+    }
+}
+
+/// The user's time between two `YYYY-MM-DD` dates broken down by
+/// `dimensions`, all of them by default.
+#[tauri::command]
+pub async fn get_report(
+    config_state: tauri::State<'_, ConfigState>,
+    from: String,
+    to: String,
+    dimensions: Option<Vec<Dimension>>,
+) -> Result<Report, String> {
+    if parse_date(&to)? < parse_date(&from)? {
+        return Err(format!("{} is before {}", to, from));
+    }
+    let client = crate::build_client(&config_state)?;
+    let worklogs = client.get_report_worklogs(&from, &to).await?;
+    let dimensions = dimensions.unwrap_or_else(|| Dimension::ALL.to_vec());
+    Ok(build(&from, &to, &worklogs, &dimensions))
+}
@@ -0,0 +1,155 @@
+use crate::config::ConfigState;
+use crate::worklog::WorklogRequest;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::env;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RoundingMode {
+    /// Log the raw time.
+    #[default]
+    None,
+    Up,
+    Nearest,
+    Down,
+}
+
+/// How a worklog's duration is rounded before it is sent to Jira.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RoundingRule {
+    #[serde(default)]
+    pub mode: RoundingMode,
+    #[serde(default = "default_increment_minutes")]
+    pub increment_minutes: u32,
+    /// Smallest billable duration, applied after rounding. 0 for none.
+    #[serde(default)]
+    pub minimum_minutes: u32,
+}
+
+fn default_increment_minutes() -> u32 {
+    15
+}
+
+impl Default for RoundingRule {
+    fn default() -> Self {
+        Self {
+            mode: RoundingMode::None,
+            increment_minutes: default_increment_minutes(),
+            minimum_minutes: 0,
+        }
+    }
+}
+
+impl RoundingRule {
+    pub fn apply(&self, seconds: u64) -> u64 {
+        if seconds == 0 {
+            return 0;
+        }
+
+        let increment = u64::from(self.increment_minutes.max(1)) * 60;
+        let rounded = match self.mode {
+            RoundingMode::None => seconds,
+            RoundingMode::Up => seconds.div_ceil(increment) * increment,
+            RoundingMode::Nearest => (seconds + increment / 2) / increment * increment,
+            RoundingMode::Down => seconds / increment * increment,
+        };
+        rounded.max(u64::from(self.minimum_minutes) * 60)
+    }
+
+    /// Parse `mode[:increment[:minimum]]`, e.g. `up:15:30`.
+    fn parse(value: &str) -> Result<Self, String> {
+        let mut parts = value.trim().split(':');
+        let mode = match parts.next().unwrap_or_default().trim() {
+            "none" => RoundingMode::None,
+            "up" => RoundingMode::Up,
+            "nearest" => RoundingMode::Nearest,
+            "down" => RoundingMode::Down,
+            other => return Err(format!("Unknown rounding mode \"{}\"", other)),
+        };
+        let mut minutes = |default: u32| -> Result<u32, String> {
+            parts.next().map_or(Ok(default), |m| {
+                m.trim()
+                    .parse()
+                    .map_err(|_| format!("Invalid minutes \"{}\" in \"{}\"", m, value))
+            })
+        };
+        Ok(Self {
+            mode,
+            increment_minutes: minutes(default_increment_minutes())?,
+            minimum_minutes: minutes(0)?,
+        })
+    }
+}
+
+/// A default rule plus per-project overrides, keyed by project key.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RoundingConfig {
+    #[serde(default)]
+    pub default: RoundingRule,
+    #[serde(default)]
+    pub projects: BTreeMap<String, RoundingRule>,
+}
+
+impl RoundingConfig {
+    /// Read `ROUNDING` (e.g. `up:15:30`) and `ROUNDING_PROJECTS`
+    /// (e.g. `OPS=nearest:15, INT=none`). Invalid values are ignored.
+    pub fn from_env() -> Self {
+        let default = env::var("ROUNDING")
+            .ok()
+            .and_then(|v| RoundingRule::parse(&v).ok())
+            .unwrap_or_default();
+        let projects = env::var("ROUNDING_PROJECTS")
+            .ok()
+            .and_then(|v| parse_projects(&v).ok())
+            .unwrap_or_default();
+        Self { default, projects }
+    }
+
+    /// The rule for an issue, from its project key ("OPS" for "OPS-12" or
+    /// "ops-12").
+    pub fn rule_for(&self, issue_key: &str) -> &RoundingRule {
+        let project = issue_key.split('-').next().unwrap_or(issue_key).to_ascii_uppercase();
+        self.projects.get(&project).unwrap_or(&self.default)
+    }
+
+    /// Round each worklog with its project's rule.
+    pub fn apply(&self, requests: &mut [WorklogRequest]) {
+        for request in requests {
+            request.seconds = self.rule_for(&request.issue_key).apply(request.seconds);
+        }
+    }
+}
+
+/// Parse per-project overrides: comma-separated `PROJECT=mode[:increment[:minimum]]`.
+pub fn parse_projects(value: &str) -> Result<BTreeMap<String, RoundingRule>, String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            let (project, rule) = entry
+                .split_once('=')
+                .ok_or_else(|| format!("Expected PROJECT=mode in \"{}\"", entry))?;
+            Ok((project.trim().to_uppercase(), RoundingRule::parse(rule)?))
+        })
+        .collect()
+}
+
+#[tauri::command]
+pub fn get_rounding(config_state: tauri::State<'_, ConfigState>) -> Result<RoundingConfig, String> {
+    let config = config_state.config.lock().map_err(|e| e.to_string())?;
+    Ok(config.rounding.clone())
+}
+
+#[tauri::command]
+pub fn save_rounding(
+    config_state: tauri::State<'_, ConfigState>,
+    default: RoundingRule,
+    projects: String,
+) -> Result<(), String> {
+    let projects = parse_projects(&projects)?;
+    let mut config = config_state.config.lock().map_err(|e| e.to_string())?;
+    config.rounding = RoundingConfig { default, projects };
+    Ok(())
+}
@@ -0,0 +1,247 @@
+use crate::config::ConfigState;
+use crate::reminders::notify;
+use crate::timer::{self, TimerState};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::env;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+
+/// Emitted when the quick search shortcut is pressed, once the window is shown.
+pub const QUICK_SEARCH: &str = "shortcuts://quick-search";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ShortcutAction {
+    ToggleWindow,
+    /// Pause the running timers, or resume the ones paused last.
+    PauseResumeAll,
+    /// Stop and log the timer started or resumed last.
+    LogActive,
+    /// Start a timer on the last stopped ticket.
+    RestartLast,
+    QuickSearch,
+}
+
+const ACTIONS: [ShortcutAction; 5] = [
+    ShortcutAction::ToggleWindow,
+    ShortcutAction::PauseResumeAll,
+    ShortcutAction::LogActive,
+    ShortcutAction::RestartLast,
+    ShortcutAction::QuickSearch,
+];
+
+impl ShortcutAction {
+    pub fn name(self) -> &'static str {
+        match self {
+            ShortcutAction::ToggleWindow => "toggle_window",
+            ShortcutAction::PauseResumeAll => "pause_resume_all",
+            ShortcutAction::LogActive => "log_active",
+            ShortcutAction::RestartLast => "restart_last",
+            ShortcutAction::QuickSearch => "quick_search",
+        }
+    }
+
+    fn parse(name: &str) -> Result<Self, String> {
+        ACTIONS
+            .into_iter()
+            .find(|a| a.name() == name)
+            .ok_or_else(|| format!("Unknown shortcut action \"{}\"", name))
+    }
+}
+
+/// Global shortcuts by action, e.g. `ctrl+shift+t` for `toggle_window`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShortcutConfig {
+    #[serde(default)]
+    pub bindings: BTreeMap<ShortcutAction, String>,
+}
+
+impl Default for ShortcutConfig {
+    fn default() -> Self {
+        Self {
+            bindings: BTreeMap::from([(ShortcutAction::ToggleWindow, "ctrl+shift+t".to_string())]),
+        }
+    }
+}
+
+impl ShortcutConfig {
+    /// Read `SHORTCUTS` (e.g. `toggle_window=ctrl+shift+t, log_active=ctrl+shift+l`),
+    /// which replaces the defaults. Invalid values are ignored.
+    pub fn from_env() -> Self {
+        env::var("SHORTCUTS")
+            .ok()
+            .and_then(|v| parse_bindings(&v).ok())
+            .map(|bindings| Self { bindings })
+            .unwrap_or_default()
+    }
+
+    /// Fails when a shortcut is invalid or bound to several actions, in
+    /// whatever order its modifiers are written.
+    pub fn check(&self) -> Result<(), String> {
+        let mut seen: Vec<(Shortcut, ShortcutAction)> = Vec::new();
+        for (action, shortcut) in &self.bindings {
+            let parsed: Shortcut = shortcut
+                .parse()
+                .map_err(|e| format!("Invalid shortcut \"{}\" for {}: {}", shortcut, action.name(), e))?;
+            if let Some((_, other)) = seen.iter().find(|(s, _)| *s == parsed) {
+                return Err(format!(
+                    "{} is bound to both {} and {}",
+                    shortcut,
+                    other.name(),
+                    action.name()
+                ));
+            }
+            seen.push((parsed, *action));
+        }
+        Ok(())
+    }
+}
+
+/// Parse comma-separated `action=shortcut` bindings. Empty shortcuts are left out.
+pub fn parse_bindings(value: &str) -> Result<BTreeMap<ShortcutAction, String>, String> {
+    let mut bindings = BTreeMap::new();
+    for entry in value.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+        let (action, shortcut) = entry
+            .split_once('=')
+            .ok_or_else(|| format!("Expected action=shortcut in \"{}\"", entry))?;
+        let action = ShortcutAction::parse(action.trim())?;
+        let shortcut = shortcut.trim().to_lowercase();
+        if !shortcut.is_empty() {
+            bindings.insert(action, shortcut);
+        }
+    }
+    Ok(bindings)
+}
+
+/// A shortcut that could not be registered, usually because another
+/// application already uses it.
+#[derive(Debug, Clone, Serialize)]
+pub struct ShortcutConflict {
+    pub action: ShortcutAction,
+    pub shortcut: String,
+    pub error: String,
+}
+
+/// Replace the registered shortcuts with `config`'s. The ones that fail are
+/// returned, the others stay registered.
+pub fn register(app: &AppHandle, config: &ShortcutConfig) -> Vec<ShortcutConflict> {
+    let global_shortcut = app.global_shortcut();
+    let _ = global_shortcut.unregister_all();
+
+    config
+        .bindings
+        .iter()
+        .filter_map(|(action, shortcut)| {
+            let action = *action;
+            global_shortcut
+                .on_shortcut(shortcut.as_str(), move |app, _shortcut, event| {
+                    if event.state == ShortcutState::Pressed {
+                        run(app, action);
+                    }
+                })
+                .err()
+                .map(|e| ShortcutConflict {
+                    action,
+                    shortcut: shortcut.clone(),
+                    error: e.to_string(),
+                })
+        })
+        .collect()
+}
+
+fn run(app: &AppHandle, action: ShortcutAction) {
+    let state = app.state::<TimerState>();
+    let now = Utc::now();
+    let result = match action {
+        ShortcutAction::ToggleWindow => {
+            crate::toggle_main_window(app);
+            return;
+        }
+        ShortcutAction::QuickSearch => {
+            crate::show_main_window(app);
+            let _ = app.emit(QUICK_SEARCH, ());
+            return;
+        }
+        ShortcutAction::LogActive => {
+            match state.active_id() {
+                Ok(Some(id)) => crate::spawn_stop_and_log(app, id),
+                Ok(None) => notify(app, "No running timer to log"),
+                Err(e) => notify(app, &e),
+            }
+            return;
+        }
+        ShortcutAction::PauseResumeAll => state.pause_or_resume_all(now),
+        ShortcutAction::RestartLast => {
+            let last = state
+                .history
+                .lock()
+                .ok()
+                .and_then(|h| h.last().map(|e| (e.issue_key.clone(), e.summary.clone())));
+            match last {
+                Some((issue_key, summary)) => state.start(issue_key, summary, now).map(|_| ()),
+                None => Err("No stopped timer to restart".to_string()),
+            }
+        }
+    };
+
+    match result {
+        Ok(()) => timer::emit_changed(app, &state),
+        Err(e) => notify(app, &e),
+    }
+}
+
+/// Register the configured shortcuts at startup, with a notification for
+/// the ones that are taken.
+pub fn init(app: &AppHandle) -> tauri::Result<()> {
+    app.plugin(tauri_plugin_global_shortcut::Builder::new().build())?;
+
+    let config = app
+        .state::<ConfigState>()
+        .config
+        .lock()
+        .map(|c| c.shortcuts.clone())
+        .unwrap_or_default();
+    for conflict in register(app, &config) {
+        notify(
+            app,
+            &format!(
+                "Shortcut {} for {} is not available: {}",
+                conflict.shortcut,
+                conflict.action.name(),
+                conflict.error
+            ),
+        );
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_shortcuts(config_state: tauri::State<'_, ConfigState>) -> Result<ShortcutConfig, String> {
+    let config = config_state.config.lock().map_err(|e| e.to_string())?;
+    Ok(config.shortcuts.clone())
+}
+
+/// Save and register new bindings, returning the ones that could not be
+/// registered so they can be changed.
+#[tauri::command]
+pub fn save_shortcuts(
+    app: AppHandle,
+    config_state: tauri::State<'_, ConfigState>,
+    bindings: BTreeMap<ShortcutAction, String>,
+) -> Result<Vec<ShortcutConflict>, String> {
+    let shortcuts = ShortcutConfig {
+        bindings: bindings
+            .into_iter()
+            .map(|(action, shortcut)| (action, shortcut.trim().to_lowercase()))
+            .filter(|(_, shortcut)| !shortcut.is_empty())
+            .collect(),
+    };
+    shortcuts.check()?;
+
+    let conflicts = register(&app, &shortcuts);
+    let mut config = config_state.config.lock().map_err(|e| e.to_string())?;
+    config.shortcuts = shortcuts;
+    Ok(conflicts)
+}
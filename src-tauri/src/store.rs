@@ -0,0 +1,91 @@
+use crate::timer::Timer;
+use crate::worklog::PendingWorklog;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+/// The bundle identifier from `tauri.conf.json`, which names the data directory.
+const APP_IDENTIFIER: &str = "com.rdt.jira-timesheet";
+const SESSION_FILE: &str = "session.json";
+
+/// Timers and queued worklogs kept on disk when quitting, restored on the
+/// next launch.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedSession {
+    #[serde(default)]
+    pub timers: Vec<Timer>,
+    #[serde(default)]
+    pub pending: Vec<PendingWorklog>,
+    pub saved_at: DateTime<Utc>,
+}
+
+impl SavedSession {
+    pub fn is_empty(&self) -> bool {
+        self.timers.is_empty() && self.pending.is_empty()
+    }
+}
+
+/// The app's data directory, the same as Tauri's `app_data_dir`, but also
+/// known to the command line without a running app.
+pub fn data_dir() -> Result<PathBuf, String> {
+    let var = |name: &str| env::var_os(name).filter(|v| !v.is_empty()).map(PathBuf::from);
+    let home = || var("HOME").ok_or("HOME is not set");
+
+    let base = if cfg!(target_os = "macos") {
+        home()?.join("Library/Application Support")
+    } else if cfg!(windows) {
+        var("APPDATA").ok_or("APPDATA is not set")?
+    } else {
+        match var("XDG_DATA_HOME").filter(|p| p.is_absolute()) {
+            Some(dir) => dir,
+            None => home()?.join(".local/share"),
+        }
+    };
+    Ok(base.join(APP_IDENTIFIER))
+}
+
+/// Where the session is kept, in the app's data directory.
+pub fn session_path() -> Result<PathBuf, String> {
+    Ok(data_dir()?.join(SESSION_FILE))
+}
+
+/// Write the session, or remove the file when there is nothing to keep.
+pub fn save(path: &Path, session: &SavedSession) -> Result<(), String> {
+    if session.is_empty() {
+        return clear(path);
+    }
+
+    let json = serde_json::to_string_pretty(session).map_err(|e| e.to_string())?;
+    write_atomic(path, &json)
+}
+
+/// Write a file aside then rename it, so a crash never leaves half a file.
+pub fn write_atomic(path: &Path, contents: &str) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, contents).map_err(|e| e.to_string())?;
+    fs::rename(&tmp, path).map_err(|e| e.to_string())
+}
+
+/// The saved session, `None` if there is none.
+pub fn load(path: &Path) -> Result<Option<SavedSession>, String> {
+    match fs::read_to_string(path) {
+        Ok(json) => serde_json::from_str(&json)
+            .map(Some)
+            .map_err(|e| format!("Invalid session file {}: {}", path.display(), e)),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+pub fn clear(path: &Path) -> Result<(), String> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != ErrorKind::NotFound => Err(e.to_string()),
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,379 @@
+use crate::config::ConfigState;
+use crate::drafts::{self, DraftStore};
+use crate::reminders::{format_hours, notify};
+use crate::store;
+use crate::worklog::{WorklogQueue, WorklogRequest};
+use chrono::{DateTime, Datelike, Local, NaiveDate, NaiveTime, Utc, Weekday};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+const TEMPLATES_FILE: &str = "templates.json";
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+const WEEKDAYS: [Weekday; 5] = [Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Thu, Weekday::Fri];
+
+/// When a template's worklog takes place.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Schedule {
+    pub days: Vec<Weekday>,
+    /// Local start time.
+    pub at: NaiveTime,
+}
+
+impl Schedule {
+    /// The start on `date`, `None` when the schedule skips that day.
+    pub fn start_on(&self, date: NaiveDate) -> Option<DateTime<Utc>> {
+        if !self.days.contains(&date.weekday()) {
+            return None;
+        }
+        date.and_time(self.at)
+            .and_local_timezone(Local)
+            .earliest()
+            .map(|t| t.with_timezone(&Utc))
+    }
+}
+
+/// `weekdays 09:30`, `daily 14:00` or days like `mon,thu 16:00`.
+pub fn parse_schedule(value: &str) -> Result<Schedule, String> {
+    let invalid = || format!("Invalid schedule \"{}\", expected e.g. \"weekdays 09:30\"", value.trim());
+    let (days, at) = value.trim().rsplit_once(' ').ok_or_else(invalid)?;
+    let at = NaiveTime::parse_from_str(at, "%H:%M").map_err(|_| invalid())?;
+    let days = match days.trim().to_lowercase().as_str() {
+        "daily" | "every day" => vec![
+            Weekday::Mon,
+            Weekday::Tue,
+            Weekday::Wed,
+            Weekday::Thu,
+            Weekday::Fri,
+            Weekday::Sat,
+            Weekday::Sun,
+        ],
+        "weekdays" => WEEKDAYS.to_vec(),
+        days => days
+            .split(',')
+            .map(|d| d.trim().parse::<Weekday>().map_err(|_| invalid()))
+            .collect::<Result<Vec<_>, _>>()?,
+    };
+    Ok(Schedule { days, at })
+}
+
+/// A schedule that may end with the worklog's duration, as in
+/// `weekdays 09:30 15 min`.
+pub fn parse_schedule_with_duration(value: &str) -> Result<(Schedule, Option<u64>), String> {
+    let words: Vec<&str> = value.split_whitespace().collect();
+    let time = words
+        .iter()
+        .rposition(|w| NaiveTime::parse_from_str(w, "%H:%M").is_ok());
+    match time {
+        Some(i) if i + 1 < words.len() => Ok((
+            parse_schedule(&words[..=i].join(" "))?,
+            Some(parse_duration(&words[i + 1..].join(" "))?),
+        )),
+        _ => Ok((parse_schedule(value)?, None)),
+    }
+}
+
+/// `15 min`, `45m`, `1h30` or `2h`, in seconds.
+pub fn parse_duration(value: &str) -> Result<u64, String> {
+    let compact = value.trim().to_lowercase().replace(' ', "").replace("min", "m");
+    let pattern = Regex::new(r"^(?:(\d+)h)?(?:(\d+)m?)?$").expect("valid regex");
+    let seconds = pattern
+        .captures(&compact)
+        .filter(|c| c.get(1).is_some() || c.get(2).is_some())
+        .map(|c| {
+            let part = |i| c.get(i).map_or(0, |m| m.as_str().parse::<u64>().unwrap_or(0));
+            part(1) * 3600 + part(2) * 60
+        })
+        .ok_or_else(|| format!("Invalid duration \"{}\", expected e.g. \"15 min\" or \"1h30\"", value.trim()))?;
+    if seconds < 60 {
+        return Err("Worklog must be at least 1 minute".to_string());
+    }
+    Ok(seconds)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ApplyMode {
+    /// Add a draft to review and submit.
+    Draft,
+    /// Log to Jira right away.
+    Log,
+}
+
+/// A worklog that comes back every time, e.g. the daily stand-up.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Template {
+    pub name: String,
+    pub issue_key: String,
+    pub seconds: u64,
+    #[serde(default)]
+    pub comment: Option<String>,
+    #[serde(default)]
+    pub schedule: Option<Schedule>,
+    /// Applied by itself at the scheduled time, only by hand when `None`.
+    #[serde(default)]
+    pub auto: Option<ApplyMode>,
+    /// Last day it was applied automatically.
+    #[serde(default)]
+    pub last_applied: Option<NaiveDate>,
+}
+
+impl Template {
+    /// The worklog for this template: at `started` if given, else at today's
+    /// scheduled time, else ending `now`.
+    pub fn request(&self, started: Option<DateTime<Utc>>, now: DateTime<Utc>) -> WorklogRequest {
+        let scheduled = || {
+            self.schedule
+                .as_ref()
+                .and_then(|s| s.start_on(now.with_timezone(&Local).date_naive()))
+        };
+        WorklogRequest {
+            issue_key: self.issue_key.clone(),
+            seconds: self.seconds,
+            started: started
+                .or_else(scheduled)
+                .unwrap_or(now - chrono::Duration::seconds(self.seconds as i64)),
+            comment: self.comment.clone(),
+        }
+    }
+
+    /// Whether it should be applied by itself now: on a scheduled day, once
+    /// the start time has passed, and not yet today. Days the app was not
+    /// running are not caught up.
+    pub fn is_due(&self, now: DateTime<Local>) -> bool {
+        let today = now.date_naive();
+        self.auto.is_some()
+            && self.last_applied != Some(today)
+            && self
+                .schedule
+                .as_ref()
+                .is_some_and(|s| s.days.contains(&today.weekday()) && now.time() >= s.at)
+    }
+}
+
+pub fn templates_path() -> Result<PathBuf, String> {
+    Ok(store::data_dir()?.join(TEMPLATES_FILE))
+}
+
+#[derive(Default)]
+pub struct TemplateStore {
+    pub templates: Mutex<Vec<Template>>,
+    /// Where templates are written on every change, `None` keeps them in memory.
+    path: Option<PathBuf>,
+}
+
+impl TemplateStore {
+    pub fn new() -> Self {
+        Self {
+            templates: Mutex::new(Vec::new()),
+            path: None,
+        }
+    }
+
+    /// Templates kept in `path`, which is created on the first change.
+    pub fn open(path: PathBuf) -> Self {
+        let templates = match fs::read_to_string(&path) {
+            Ok(json) => serde_json::from_str(&json).unwrap_or_else(|_| {
+                // Set aside rather than overwritten by the next change
+                let _ = fs::rename(&path, path.with_extension("invalid.json"));
+                Vec::new()
+            }),
+            Err(_) => Vec::new(),
+        };
+        Self {
+            templates: Mutex::new(templates),
+            path: Some(path),
+        }
+    }
+
+    fn persist(&self, templates: &[Template]) -> Result<(), String> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let json = serde_json::to_string_pretty(templates).map_err(|e| e.to_string())?;
+        store::write_atomic(path, &json)
+    }
+
+    pub fn list(&self) -> Result<Vec<Template>, String> {
+        let templates = self.templates.lock().map_err(|e| e.to_string())?;
+        Ok(templates.clone())
+    }
+
+    pub fn get(&self, name: &str) -> Result<Template, String> {
+        let templates = self.templates.lock().map_err(|e| e.to_string())?;
+        templates
+            .iter()
+            .find(|t| t.name.eq_ignore_ascii_case(name))
+            .cloned()
+            .ok_or_else(|| format!("No template \"{}\"", name))
+    }
+
+    /// Add a template, or replace the one with the same name. It is first
+    /// applied automatically at its next scheduled time, so saving it after
+    /// today's time does not apply it right away.
+    pub fn save(&self, mut template: Template, now: DateTime<Local>) -> Result<(), String> {
+        template.name = template.name.trim().to_string();
+        template.issue_key = template.issue_key.trim().to_uppercase();
+        if template.name.is_empty() || template.issue_key.is_empty() {
+            return Err("Template needs a name and an issue".to_string());
+        }
+        if template.auto.is_some() && template.schedule.is_none() {
+            return Err("Applying a template automatically needs a schedule".to_string());
+        }
+
+        let mut templates = self.templates.lock().map_err(|e| e.to_string())?;
+        let existing = templates.iter().position(|t| t.name.eq_ignore_ascii_case(&template.name));
+        template.last_applied = existing.and_then(|i| templates[i].last_applied);
+        if template.is_due(now) {
+            template.last_applied = Some(now.date_naive());
+        }
+        match existing {
+            Some(i) => templates[i] = template,
+            None => templates.push(template),
+        }
+        self.persist(&templates)
+    }
+
+    pub fn remove(&self, name: &str) -> Result<(), String> {
+        let mut templates = self.templates.lock().map_err(|e| e.to_string())?;
+        let before = templates.len();
+        templates.retain(|t| !t.name.eq_ignore_ascii_case(name));
+        if templates.len() == before {
+            return Err(format!("No template \"{}\"", name));
+        }
+        self.persist(&templates)
+    }
+
+    /// Templates due now, marked as applied today.
+    pub fn take_due(&self, now: DateTime<Local>) -> Result<Vec<Template>, String> {
+        let mut templates = self.templates.lock().map_err(|e| e.to_string())?;
+        let mut due = Vec::new();
+        for template in templates.iter_mut().filter(|t| t.is_due(now)) {
+            template.last_applied = Some(now.date_naive());
+            due.push(template.clone());
+        }
+        if !due.is_empty() {
+            self.persist(&templates)?;
+        }
+        Ok(due)
+    }
+}
+
+async fn apply(app: &AppHandle, request: WorklogRequest, mode: ApplyMode) -> Result<(), String> {
+    match mode {
+        ApplyMode::Draft => {
+            let drafts = app.state::<DraftStore>();
+            drafts.add(vec![request], Utc::now())?;
+            drafts::emit_changed(app, &drafts);
+            Ok(())
+        }
+        ApplyMode::Log => {
+            let client = crate::build_client(&app.state::<ConfigState>())?;
+            client
+                .log_worklog(
+                    &request.issue_key,
+                    request.seconds,
+                    Some(request.started),
+                    request.comment.as_deref(),
+                )
+                .await
+        }
+    }
+}
+
+/// Apply the templates whose time came, once a minute. A scheduled worklog
+/// that fails to log is queued for retry rather than tried again; a draft
+/// that can't be added is only reported.
+pub fn spawn(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(CHECK_INTERVAL).await;
+            let Ok(due) = app.state::<TemplateStore>().take_due(Local::now()) else {
+                continue;
+            };
+            for template in due {
+                let Some(mode) = template.auto else {
+                    continue;
+                };
+                let request = template.request(None, Utc::now());
+                let what = format!("{} on {}", format_hours(request.seconds), request.issue_key);
+                let body = match apply(&app, request.clone(), mode).await {
+                    Ok(()) if mode == ApplyMode::Draft => format!("{}: {} saved as draft", template.name, what),
+                    Ok(()) => format!("{}: logged {}", template.name, what),
+                    Err(e) if mode == ApplyMode::Log => {
+                        let _ = app.state::<WorklogQueue>().push(request, &e);
+                        format!("{}: {}, queued for retry", template.name, e)
+                    }
+                    // A draft that can't be added has nothing to retry against Jira
+                    Err(e) => format!("{}: {}", template.name, e),
+                };
+                notify(&app, &body);
+            }
+        }
+    });
+}
+
+#[tauri::command]
+pub fn get_templates(store: tauri::State<'_, TemplateStore>) -> Result<Vec<Template>, String> {
+    store.list()
+}
+
+#[tauri::command]
+pub fn save_template(
+    store: tauri::State<'_, TemplateStore>,
+    name: String,
+    issue_key: String,
+    duration: String,
+    comment: Option<String>,
+    schedule: Option<String>,
+    auto: Option<ApplyMode>,
+) -> Result<(), String> {
+    let (schedule, scheduled_seconds) = match schedule.as_deref().map(str::trim).filter(|s| !s.is_empty()) {
+        Some(value) => {
+            let (schedule, seconds) = parse_schedule_with_duration(value)?;
+            (Some(schedule), seconds)
+        }
+        None => (None, None),
+    };
+    // The duration may come with the schedule instead of its own field
+    let seconds = match (duration.trim(), scheduled_seconds) {
+        ("", Some(seconds)) => seconds,
+        (duration, Some(seconds)) if parse_duration(duration)? != seconds => {
+            return Err("The schedule and the duration disagree".to_string());
+        }
+        (duration, _) => parse_duration(duration)?,
+    };
+    store.save(Template {
+        name,
+        issue_key,
+        seconds,
+        comment: comment.filter(|c| !c.trim().is_empty()),
+        schedule,
+        auto,
+        last_applied: None,
+    }, Local::now())
+}
+
+#[tauri::command]
+pub fn delete_template(store: tauri::State<'_, TemplateStore>, name: String) -> Result<(), String> {
+    store.remove(&name)
+}
+
+/// Apply a template by hand, at `started` or its usual time today.
+#[tauri::command]
+pub async fn apply_template(
+    app: AppHandle,
+    store: tauri::State<'_, TemplateStore>,
+    name: String,
+    started: Option<DateTime<Utc>>,
+    mode: ApplyMode,
+) -> Result<WorklogRequest, String> {
+    let request = store.get(&name)?.request(started, Utc::now());
+    apply(&app, request.clone(), mode).await?;
+    Ok(request)
+}
@@ -0,0 +1,98 @@
+use crate::timer::{Timer, TimerState};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::{watch, Notify};
+
+/// Emitted once per second with the elapsed time of running timers.
+pub const TIMERS_TICK: &str = "timers://tick";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TimerTick {
+    pub id: u32,
+    pub elapsed_seconds: u64,
+}
+
+/// Drives the per-second tick. The task sleeps on these until the window is
+/// visible and at least one timer is running, so a hidden app does no work.
+pub struct TickerState {
+    visible: watch::Sender<bool>,
+    changed: Notify,
+}
+
+impl TickerState {
+    pub fn new() -> Self {
+        Self {
+            visible: watch::Sender::new(true),
+            changed: Notify::new(),
+        }
+    }
+}
+
+impl Default for TickerState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub fn set_visible(app: &AppHandle, visible: bool) {
+    app.state::<TickerState>().visible.send_replace(visible);
+}
+
+/// Wake the tick task after timers were started, paused or stopped.
+pub fn notify_changed(app: &AppHandle) {
+    app.state::<TickerState>().changed.notify_one();
+}
+
+/// The tick payload: running timers only, and none while the window is
+/// hidden. Empty means nothing is emitted and the task goes idle.
+pub fn ticks(timers: &[Timer], visible: bool, now: DateTime<Utc>) -> Vec<TimerTick> {
+    if !visible {
+        return Vec::new();
+    }
+    timers
+        .iter()
+        .filter(|t| t.is_running())
+        .map(|t| TimerTick {
+            id: t.id,
+            elapsed_seconds: t.elapsed_at(now),
+        })
+        .collect()
+}
+
+fn current_ticks(app: &AppHandle, visible: bool) -> Vec<TimerTick> {
+    let timer_state = app.state::<TimerState>();
+    let Ok(timers) = timer_state.timers.lock() else {
+        return Vec::new();
+    };
+    ticks(&timers, visible, Utc::now())
+}
+
+pub fn spawn(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let ticker = app.state::<TickerState>();
+        let mut visible = ticker.visible.subscribe();
+
+        loop {
+            let ticks = current_ticks(&app, *visible.borrow_and_update());
+
+            if ticks.is_empty() {
+                // Idle until something could make ticking useful again
+                tokio::select! {
+                    _ = visible.changed() => {}
+                    _ = ticker.changed.notified() => {}
+                }
+                continue;
+            }
+
+            let _ = app.emit(TIMERS_TICK, ticks);
+
+            tokio::select! {
+                _ = tokio::time::sleep(Duration::from_secs(1)) => {}
+                _ = visible.changed() => {}
+                _ = ticker.changed.notified() => {}
+            }
+        }
+    });
+}
@@ -1,6 +1,18 @@
-use chrono::{DateTime, Utc};
+use crate::config::ConfigState;
+use crate::idle::IdleSpan;
+use crate::{ticker, tray};
+use chrono::{DateTime, Local, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
-use std::sync::Mutex;
+use std::env;
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+/// Emitted with the full timer list whenever a timer is started, paused,
+/// resumed, stopped or edited.
+pub const TIMERS_CHANGED: &str = "timers://changed";
+
+const ROLLOVER_INTERVAL: Duration = Duration::from_secs(60);
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Timer {
@@ -8,19 +20,309 @@ pub struct Timer {
     pub issue_key: String,
     pub summary: String,
     pub started_at: DateTime<Utc>,
+    /// Run intervals, oldest first. Only the last one is open, while the timer runs.
+    pub segments: Vec<TimerSegment>,
+    /// Manual corrections on top of the segments' total, detailed in `adjustments`.
+    #[serde(default)]
+    pub offset_seconds: i64,
+    /// Manual corrections of the elapsed time, oldest first.
+    #[serde(default)]
+    pub adjustments: Vec<TimerAdjustment>,
+    /// Idle periods detected while the timer was running.
+    #[serde(default)]
+    pub idle_spans: Vec<IdleSpan>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimerSegment {
+    pub start: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end: Option<DateTime<Utc>>,
+}
+
+impl TimerSegment {
+    pub fn seconds_at(&self, now: DateTime<Utc>) -> u64 {
+        (self.end.unwrap_or(now) - self.start).num_seconds().max(0) as u64
+    }
+
+    /// Seconds of the segment between `from` and `to`, an open one running
+    /// until `to`.
+    pub fn seconds_between(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> u64 {
+        let start = self.start.max(from);
+        let end = self.end.unwrap_or(to).min(to);
+        (end - start).num_seconds().max(0) as u64
+    }
+}
+
+/// One Jira worklog to create when a timer is logged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WorklogPart {
+    pub started: DateTime<Utc>,
+    pub seconds: u64,
+}
+
+impl Timer {
+    pub fn new(id: u32, issue_key: String, summary: String, now: DateTime<Utc>) -> Self {
+        Self {
+            id,
+            issue_key,
+            summary,
+            started_at: now,
+            segments: vec![TimerSegment {
+                start: now,
+                end: None,
+            }],
+            offset_seconds: 0,
+            adjustments: Vec::new(),
+            idle_spans: Vec::new(),
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.segments.last().is_some_and(|s| s.end.is_none())
+    }
+
+    /// Elapsed time including the current running segment, if any.
+    pub fn elapsed_at(&self, now: DateTime<Utc>) -> u64 {
+        let segments: u64 = self.segments.iter().map(|s| s.seconds_at(now)).sum();
+        (segments as i64 + self.offset_seconds).max(0) as u64
+    }
+
+    /// Seconds the timer was running between `from` and `to`.
+    pub fn running_between(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> u64 {
+        self.segments.iter().map(|s| s.seconds_between(from, to)).sum()
+    }
+
+    /// Close the running segment.
+    fn pause(&mut self, now: DateTime<Utc>) {
+        if let Some(segment) = self.segments.last_mut().filter(|s| s.end.is_none()) {
+            segment.end = Some(now);
+        }
+    }
+
+    fn resume(&mut self, now: DateTime<Utc>) {
+        self.segments.push(TimerSegment {
+            start: now,
+            end: None,
+        });
+    }
+
+    /// Replace the elapsed time and record the change in `adjustments`.
+    /// Returns false when the value is unchanged.
+    pub fn adjust_elapsed(&mut self, elapsed_seconds: u64, now: DateTime<Utc>) -> bool {
+        let before_seconds = self.elapsed_at(now);
+        if before_seconds == elapsed_seconds {
+            return false;
+        }
+
+        self.offset_seconds += elapsed_seconds as i64 - before_seconds as i64;
+        self.adjustments.push(TimerAdjustment {
+            before_seconds,
+            after_seconds: elapsed_seconds,
+            adjusted_at: now,
+        });
+        true
+    }
+
+    /// Take over the time of another timer on the same issue, e.g. one that
+    /// was stopped to be logged while this one was started.
+    fn absorb(&mut self, other: Timer) {
+        self.started_at = self.started_at.min(other.started_at);
+        self.segments.extend(other.segments);
+        self.segments.sort_by_key(|s| s.start);
+        self.offset_seconds += other.offset_seconds;
+        self.adjustments.extend(other.adjustments);
+        self.adjustments.sort_by_key(|a| a.adjusted_at);
+        self.idle_spans.extend(other.idle_spans);
+    }
+
+    /// Split segments running over local midnight, so that each day's time
+    /// is logged on that day. Returns false when nothing had to be split.
+    pub fn split_at_midnight(&mut self, now: DateTime<Utc>) -> bool {
+        let mut segments = Vec::with_capacity(self.segments.len());
+        for segment in &self.segments {
+            let end = segment.end.unwrap_or(now);
+            let mut start = segment.start;
+            while let Some(midnight) = next_local_midnight(start).filter(|m| *m < end) {
+                segments.push(TimerSegment {
+                    start,
+                    end: Some(midnight),
+                });
+                start = midnight;
+            }
+            segments.push(TimerSegment {
+                start,
+                end: segment.end,
+            });
+        }
+
+        let split = segments.len() != self.segments.len();
+        self.segments = segments;
+        split
+    }
+
+    /// The worklogs to create for this timer: one per day starting with that
+    /// day's first segment, or one per segment. Manual corrections apply to
+    /// the latest segments, and parts under a minute (which Jira rejects) are
+    /// folded into the next one.
+    pub fn worklog_parts(&self, per_segment: bool, now: DateTime<Utc>) -> Vec<WorklogPart> {
+        let mut days = self.clone();
+        days.split_at_midnight(now);
+
+        let mut parts: Vec<WorklogPart> = Vec::new();
+        for segment in &days.segments {
+            let seconds = segment.seconds_at(now);
+            match parts.last_mut() {
+                Some(last) if !per_segment && local_date(last.started) == local_date(segment.start) => {
+                    last.seconds += seconds;
+                }
+                _ => parts.push(WorklogPart {
+                    started: segment.start,
+                    seconds,
+                }),
+            }
+        }
+
+        if self.offset_seconds > 0 {
+            if let Some(last) = parts.last_mut() {
+                last.seconds += self.offset_seconds as u64;
+            }
+        } else {
+            let mut to_remove = self.offset_seconds.unsigned_abs();
+            for part in parts.iter_mut().rev() {
+                let removed = part.seconds.min(to_remove);
+                part.seconds -= removed;
+                to_remove -= removed;
+            }
+        }
+
+        let mut merged: Vec<WorklogPart> = Vec::new();
+        let mut carry = 0;
+        for part in parts {
+            let seconds = part.seconds + carry;
+            if seconds < 60 {
+                carry = seconds;
+                continue;
+            }
+            carry = 0;
+            merged.push(WorklogPart {
+                started: part.started,
+                seconds,
+            });
+        }
+        if let Some(last) = merged.last_mut() {
+            last.seconds += carry;
+        }
+        merged
+    }
+}
+
+fn local_date(at: DateTime<Utc>) -> NaiveDate {
+    at.with_timezone(&Local).date_naive()
+}
+
+/// The first local midnight after `at`.
+fn next_local_midnight(at: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    local_date(at)
+        .succ_opt()?
+        .and_hms_opt(0, 0, 0)?
+        .and_local_timezone(Local)
+        .earliest()
+        .map(|m| m.with_timezone(&Utc))
+}
+
+/// A timer as sent to the frontend, with its totals at the time of the snapshot.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimerView {
+    #[serde(flatten)]
+    pub timer: Timer,
     pub elapsed_seconds: u64,
     pub paused: bool,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub pause_start: Option<DateTime<Utc>>,
+}
+
+/// A manual edit of a timer's elapsed time, kept so corrected time can be audited.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimerAdjustment {
+    pub before_seconds: u64,
+    pub after_seconds: u64,
+    pub adjusted_at: DateTime<Utc>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HistoryEntry {
     pub issue_key: String,
     pub summary: String,
+    /// Raw tracked time.
     pub elapsed_seconds: u64,
     pub logged: bool,
+    /// Time sent to Jira after rounding, when logged.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub logged_seconds: Option<u64>,
     pub stopped_at: DateTime<Utc>,
+    #[serde(default)]
+    pub adjustments: Vec<TimerAdjustment>,
+    #[serde(default)]
+    pub segments: Vec<TimerSegment>,
+}
+
+/// One run interval of a timer, for the day's timeline.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TimelineEntry {
+    pub issue_key: String,
+    pub summary: String,
+    pub start: DateTime<Utc>,
+    /// `None` while the timer is still running.
+    pub end: Option<DateTime<Utc>>,
+    /// Whether the time was logged to Jira; `None` for active timers.
+    pub logged: Option<bool>,
+}
+
+/// Segments of active and stopped timers that ran after `since`, in start order.
+pub fn timeline(
+    timers: &[Timer],
+    history: &[HistoryEntry],
+    since: DateTime<Utc>,
+) -> Vec<TimelineEntry> {
+    let active = timers
+        .iter()
+        .flat_map(|t| t.segments.iter().map(move |s| (&t.issue_key, &t.summary, s, None)));
+    let stopped = history
+        .iter()
+        .flat_map(|h| h.segments.iter().map(move |s| (&h.issue_key, &h.summary, s, Some(h.logged))));
+
+    let mut entries: Vec<TimelineEntry> = active
+        .chain(stopped)
+        .filter(|(_, _, s, _)| s.end.is_none_or(|end| end > since))
+        .map(|(issue_key, summary, s, logged)| TimelineEntry {
+            issue_key: issue_key.clone(),
+            summary: summary.clone(),
+            start: s.start,
+            end: s.end,
+            logged,
+        })
+        .collect();
+    entries.sort_by_key(|e| e.start);
+    entries
+}
+
+/// Whether several timers may run at the same time.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TimerPolicy {
+    /// Any number of timers can run at once.
+    #[default]
+    Parallel,
+    /// Starting or resuming a timer pauses all the others.
+    Exclusive,
+}
+
+impl TimerPolicy {
+    fn from_env() -> Self {
+        match env::var("TIMER_POLICY").as_deref() {
+            Ok("exclusive") => TimerPolicy::Exclusive,
+            _ => TimerPolicy::Parallel,
+        }
+    }
 }
 
 #[derive(Default)]
@@ -28,6 +330,7 @@ pub struct TimerState {
     pub timers: Mutex<Vec<Timer>>,
     pub history: Mutex<Vec<HistoryEntry>>,
     pub next_id: Mutex<u32>,
+    pub policy: Mutex<TimerPolicy>,
 }
 
 impl TimerState {
@@ -36,132 +339,467 @@ impl TimerState {
             timers: Mutex::new(Vec::new()),
             history: Mutex::new(Vec::new()),
             next_id: Mutex::new(1),
+            policy: Mutex::new(TimerPolicy::from_env()),
+        }
+    }
+
+    fn policy(&self) -> Result<TimerPolicy, String> {
+        self.policy.lock().map(|p| *p).map_err(|e| e.to_string())
+    }
+
+    /// The policy and the timers, locked together so that `set_policy` can't
+    /// change the policy halfway through starting or resuming a timer.
+    fn lock_with_policy(&self) -> Result<(MutexGuard<'_, TimerPolicy>, MutexGuard<'_, Vec<Timer>>), String> {
+        let policy = self.policy.lock().map_err(|e| e.to_string())?;
+        let timers = self.timers.lock().map_err(|e| e.to_string())?;
+        Ok((policy, timers))
+    }
+
+    pub fn start(
+        &self,
+        issue_key: String,
+        summary: String,
+        now: DateTime<Utc>,
+    ) -> Result<Timer, String> {
+        let (policy, mut timers) = self.lock_with_policy()?;
+        let mut next_id = self.next_id.lock().map_err(|e| e.to_string())?;
+
+        // Don't start duplicate timer for same ticket
+        if timers.iter().any(|t| same_issue(&t.issue_key, &issue_key)) {
+            return Err(format!("Timer already running for {}", issue_key));
+        }
+
+        if *policy == TimerPolicy::Exclusive {
+            pause_running(&mut timers, None, now);
         }
+
+        let timer = Timer::new(*next_id, issue_key, summary, now);
+
+        *next_id += 1;
+        timers.push(timer.clone());
+        Ok(timer)
+    }
+
+    pub fn pause(&self, timer_id: u32, now: DateTime<Utc>) -> Result<(), String> {
+        let mut timers = self.timers.lock().map_err(|e| e.to_string())?;
+
+        let timer = timers
+            .iter_mut()
+            .find(|t| t.id == timer_id)
+            .ok_or("Timer not found")?;
+
+        if !timer.is_running() {
+            return Err("Timer already paused".to_string());
+        }
+
+        timer.pause(now);
+        Ok(())
+    }
+
+    pub fn resume(&self, timer_id: u32, now: DateTime<Utc>) -> Result<(), String> {
+        let (policy, mut timers) = self.lock_with_policy()?;
+
+        let timer = timers
+            .iter_mut()
+            .find(|t| t.id == timer_id)
+            .ok_or("Timer not found")?;
+
+        if timer.is_running() {
+            return Err("Timer is not paused".to_string());
+        }
+
+        timer.resume(now);
+
+        if *policy == TimerPolicy::Exclusive {
+            pause_running(&mut timers, Some(timer_id), now);
+        }
+        Ok(())
+    }
+
+    /// A copy of the timer, e.g. to work out its worklogs before stopping it.
+    pub fn get(&self, timer_id: u32) -> Result<Timer, String> {
+        let timers = self.timers.lock().map_err(|e| e.to_string())?;
+        timers
+            .iter()
+            .find(|t| t.id == timer_id)
+            .cloned()
+            .ok_or_else(|| "Timer not found".to_string())
+    }
+
+    pub fn stop(&self, timer_id: u32, now: DateTime<Utc>) -> Result<Timer, String> {
+        let mut timers = self.timers.lock().map_err(|e| e.to_string())?;
+
+        let pos = timers
+            .iter()
+            .position(|t| t.id == timer_id)
+            .ok_or("Timer not found")?;
+
+        let mut timer = timers.remove(pos);
+        timer.pause(now);
+        Ok(timer)
+    }
+
+    /// Fails if the timer is gone or another timer already tracks `issue_key`.
+    pub fn check_reassign(&self, timer_id: u32, issue_key: &str) -> Result<(), String> {
+        let timers = self.timers.lock().map_err(|e| e.to_string())?;
+        check_reassign(&timers, timer_id, issue_key)
+    }
+
+    /// Move a timer to another ticket, keeping its segments and corrections.
+    pub fn reassign(&self, timer_id: u32, issue_key: String, summary: String) -> Result<(), String> {
+        let mut timers = self.timers.lock().map_err(|e| e.to_string())?;
+        // Checked again: another timer may have been started meanwhile
+        check_reassign(&timers, timer_id, &issue_key)?;
+
+        let timer = timers
+            .iter_mut()
+            .find(|t| t.id == timer_id)
+            .ok_or("Timer not found")?;
+
+        timer.issue_key = issue_key;
+        timer.summary = summary;
+        Ok(())
+    }
+
+    /// Returns false when the timer already had that elapsed time.
+    pub fn set_elapsed(
+        &self,
+        timer_id: u32,
+        elapsed_seconds: i64,
+        now: DateTime<Utc>,
+    ) -> Result<bool, String> {
+        let elapsed_seconds =
+            u64::try_from(elapsed_seconds).map_err(|_| "Elapsed time cannot be negative".to_string())?;
+        let mut timers = self.timers.lock().map_err(|e| e.to_string())?;
+
+        let timer = timers
+            .iter_mut()
+            .find(|t| t.id == timer_id)
+            .ok_or("Timer not found")?;
+
+        Ok(timer.adjust_elapsed(elapsed_seconds, now))
+    }
+
+    /// Switching to exclusive keeps only the most recently started or resumed
+    /// timer running.
+    pub fn set_policy(&self, policy: TimerPolicy, now: DateTime<Utc>) -> Result<(), String> {
+        let (mut current, mut timers) = self.lock_with_policy()?;
+        *current = policy;
+
+        if policy == TimerPolicy::Exclusive {
+            let latest = timers
+                .iter()
+                .filter(|t| t.is_running())
+                .max_by_key(|t| t.segments.last().map(|s| s.start))
+                .map(|t| t.id);
+            pause_running(&mut timers, latest, now);
+        }
+        Ok(())
+    }
+
+    /// `logged_seconds` is the time sent to Jira after rounding, `None` if discarded.
+    pub fn record_history(&self, timer: &Timer, logged_seconds: Option<u64>, now: DateTime<Utc>) {
+        if let Ok(mut history) = self.history.lock() {
+            history.push(HistoryEntry {
+                issue_key: timer.issue_key.clone(),
+                summary: timer.summary.clone(),
+                elapsed_seconds: timer.elapsed_at(now),
+                logged: logged_seconds.is_some(),
+                logged_seconds,
+                stopped_at: now,
+                adjustments: timer.adjustments.clone(),
+                segments: timer.segments.clone(),
+            });
+        }
+    }
+
+    /// Split every timer at the midnights it ran over. Returns false when
+    /// nothing changed.
+    pub fn split_at_midnight(&self, now: DateTime<Utc>) -> Result<bool, String> {
+        let mut timers = self.timers.lock().map_err(|e| e.to_string())?;
+        let mut split = false;
+        for timer in timers.iter_mut() {
+            split |= timer.split_at_midnight(now);
+        }
+        Ok(split)
+    }
+
+    /// Stop every timer, e.g. when quitting.
+    pub fn stop_all(&self, now: DateTime<Utc>) -> Result<Vec<Timer>, String> {
+        let mut timers = self.timers.lock().map_err(|e| e.to_string())?;
+        let mut stopped: Vec<Timer> = timers.drain(..).collect();
+        for timer in &mut stopped {
+            timer.pause(now);
+        }
+        Ok(stopped)
+    }
+
+    /// Pause every running timer, e.g. before keeping them across a restart.
+    pub fn pause_all(&self, now: DateTime<Utc>) -> Result<(), String> {
+        let mut timers = self.timers.lock().map_err(|e| e.to_string())?;
+        pause_running(&mut timers, None, now);
+        Ok(())
+    }
+
+    /// Pause every running timer or, when none runs, resume the ones paused
+    /// last (only one of them with the exclusive policy).
+    pub fn pause_or_resume_all(&self, now: DateTime<Utc>) -> Result<(), String> {
+        let (policy, mut timers) = self.lock_with_policy()?;
+
+        if timers.iter().any(|t| t.is_running()) {
+            pause_running(&mut timers, None, now);
+            return Ok(());
+        }
+
+        let paused_at = timers
+            .iter()
+            .filter_map(|t| t.segments.last().and_then(|s| s.end))
+            .max()
+            .ok_or("No timer to resume")?;
+        for timer in timers
+            .iter_mut()
+            .filter(|t| t.segments.last().and_then(|s| s.end) == Some(paused_at))
+        {
+            timer.resume(now);
+            if *policy == TimerPolicy::Exclusive {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// The timer tracking `issue_key`.
+    pub fn find_id(&self, issue_key: &str) -> Result<u32, String> {
+        let timers = self.timers.lock().map_err(|e| e.to_string())?;
+        timers
+            .iter()
+            .find(|t| same_issue(&t.issue_key, issue_key))
+            .map(|t| t.id)
+            .ok_or_else(|| format!("No timer for {}", issue_key))
+    }
+
+    /// The paused timer that was paused last.
+    pub fn last_paused_id(&self) -> Result<Option<u32>, String> {
+        let timers = self.timers.lock().map_err(|e| e.to_string())?;
+        Ok(timers
+            .iter()
+            .filter(|t| !t.is_running())
+            .max_by_key(|t| t.segments.last().and_then(|s| s.end))
+            .map(|t| t.id))
+    }
+
+    /// The running timer that was started or resumed last.
+    pub fn active_id(&self) -> Result<Option<u32>, String> {
+        let timers = self.timers.lock().map_err(|e| e.to_string())?;
+        Ok(timers
+            .iter()
+            .filter(|t| t.is_running())
+            .max_by_key(|t| t.segments.last().map(|s| s.start))
+            .map(|t| t.id))
+    }
+
+    /// Bring back timers kept from a previous run, or stopped by an action
+    /// that failed. Ids are kept, and new ids (timers and idle spans share
+    /// them) continue after the restored ones. A timer whose issue has one
+    /// already is merged into it, so no time is lost.
+    pub fn restore(&self, restored: Vec<Timer>) -> Result<(), String> {
+        let mut timers = self.timers.lock().map_err(|e| e.to_string())?;
+        let mut next_id = self.next_id.lock().map_err(|e| e.to_string())?;
+        for mut timer in restored {
+            let last_id = timer.idle_spans.iter().map(|s| s.id).fold(timer.id, u32::max);
+            *next_id = (*next_id).max(last_id + 1);
+            if let Some(existing) = timers.iter_mut().find(|t| same_issue(&t.issue_key, &timer.issue_key)) {
+                existing.absorb(timer);
+                continue;
+            }
+            if timers.iter().any(|t| t.id == timer.id) {
+                timer.id = *next_id;
+                *next_id += 1;
+            }
+            timers.push(timer);
+        }
+        Ok(())
+    }
+
+    pub fn snapshot(&self, now: DateTime<Utc>) -> Result<Vec<TimerView>, String> {
+        let timers = self.timers.lock().map_err(|e| e.to_string())?;
+        Ok(snapshot(&timers, now))
     }
 }
 
+/// Issue keys are compared ignoring case, as typed keys may be lowercase.
+fn same_issue(a: &str, b: &str) -> bool {
+    a.eq_ignore_ascii_case(b)
+}
+
+fn check_reassign(timers: &[Timer], timer_id: u32, issue_key: &str) -> Result<(), String> {
+    if !timers.iter().any(|t| t.id == timer_id) {
+        return Err("Timer not found".to_string());
+    }
+    if timers.iter().any(|t| t.id != timer_id && same_issue(&t.issue_key, issue_key)) {
+        return Err(format!("Timer already running for {}", issue_key));
+    }
+    Ok(())
+}
+
+/// Pause every running timer except `keep`.
+fn pause_running(timers: &mut [Timer], keep: Option<u32>, now: DateTime<Utc>) {
+    for timer in timers
+        .iter_mut()
+        .filter(|t| t.is_running() && Some(t.id) != keep)
+    {
+        timer.pause(now);
+    }
+}
+
+fn snapshot(timers: &[Timer], now: DateTime<Utc>) -> Vec<TimerView> {
+    timers
+        .iter()
+        .map(|t| TimerView {
+            timer: t.clone(),
+            elapsed_seconds: t.elapsed_at(now),
+            paused: !t.is_running(),
+        })
+        .collect()
+}
+
+/// Push the current timers to the frontend, wake the tick task and rebuild
+/// the tray menu.
+pub fn emit_changed(app: &AppHandle, state: &TimerState) {
+    if let Ok(timers) = state.timers.lock() {
+        let _ = app.emit(TIMERS_CHANGED, snapshot(&timers, Utc::now()));
+    }
+    ticker::notify_changed(app);
+    tray::refresh(app);
+}
+
+/// Split running timers when the day changes, so the timeline and worklogs
+/// follow the calendar.
+pub fn spawn_rollover(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(ROLLOVER_INTERVAL).await;
+            let state = app.state::<TimerState>();
+            if state.split_at_midnight(Utc::now()).unwrap_or(false) {
+                emit_changed(&app, &state);
+            }
+        }
+    });
+}
+
 #[tauri::command]
 pub fn start_timer(
+    app: AppHandle,
     state: tauri::State<'_, TimerState>,
     issue_key: String,
     summary: String,
 ) -> Result<Timer, String> {
-    let mut timers = state.timers.lock().map_err(|e| e.to_string())?;
-    let mut next_id = state.next_id.lock().map_err(|e| e.to_string())?;
-
-    // Don't start duplicate timer for same ticket
-    if timers.iter().any(|t| t.issue_key == issue_key) {
-        return Err(format!("Timer already running for {}", issue_key));
-    }
-
-    let timer = Timer {
-        id: *next_id,
-        issue_key,
-        summary,
-        started_at: Utc::now(),
-        elapsed_seconds: 0,
-        paused: false,
-        pause_start: None,
-    };
-
-    *next_id += 1;
-    timers.push(timer.clone());
+    let timer = state.start(issue_key, summary, Utc::now())?;
+    emit_changed(&app, &state);
     Ok(timer)
 }
 
 #[tauri::command]
 pub fn pause_timer(
+    app: AppHandle,
     state: tauri::State<'_, TimerState>,
     timer_id: u32,
 ) -> Result<(), String> {
-    let mut timers = state.timers.lock().map_err(|e| e.to_string())?;
-
-    let timer = timers
-        .iter_mut()
-        .find(|t| t.id == timer_id)
-        .ok_or("Timer not found")?;
-
-    if timer.paused {
-        return Err("Timer already paused".to_string());
-    }
-
-    // Accumulate elapsed time before pausing
-    let now = Utc::now();
-    let running_since = timer.pause_start.unwrap_or(timer.started_at);
-    let additional = (now - running_since).num_seconds().max(0) as u64;
-    timer.elapsed_seconds += additional;
-    timer.paused = true;
-    timer.pause_start = Some(now);
-
+    state.pause(timer_id, Utc::now())?;
+    emit_changed(&app, &state);
     Ok(())
 }
 
 #[tauri::command]
 pub fn resume_timer(
+    app: AppHandle,
     state: tauri::State<'_, TimerState>,
     timer_id: u32,
 ) -> Result<(), String> {
-    let mut timers = state.timers.lock().map_err(|e| e.to_string())?;
+    state.resume(timer_id, Utc::now())?;
+    emit_changed(&app, &state);
+    Ok(())
+}
 
-    let timer = timers
-        .iter_mut()
-        .find(|t| t.id == timer_id)
-        .ok_or("Timer not found")?;
+#[tauri::command]
+pub fn stop_timer(
+    app: AppHandle,
+    state: tauri::State<'_, TimerState>,
+    timer_id: u32,
+) -> Result<Timer, String> {
+    let timer = state.stop(timer_id, Utc::now())?;
+    emit_changed(&app, &state);
+    Ok(timer)
+}
 
-    if !timer.paused {
-        return Err("Timer is not paused".to_string());
+#[tauri::command]
+pub fn set_timer_elapsed(
+    app: AppHandle,
+    state: tauri::State<'_, TimerState>,
+    timer_id: u32,
+    elapsed_seconds: i64,
+) -> Result<(), String> {
+    if state.set_elapsed(timer_id, elapsed_seconds, Utc::now())? {
+        emit_changed(&app, &state);
     }
-
-    timer.paused = false;
-    timer.pause_start = Some(Utc::now()); // Mark resume time
-
     Ok(())
 }
 
+/// Point a timer at another ticket, e.g. when it was started on the wrong one.
+/// The new key is checked against Jira so the summary can be updated.
 #[tauri::command]
-pub fn stop_timer(
+pub async fn reassign_timer(
+    app: AppHandle,
     state: tauri::State<'_, TimerState>,
+    config_state: tauri::State<'_, ConfigState>,
     timer_id: u32,
-) -> Result<Timer, String> {
-    let mut timers = state.timers.lock().map_err(|e| e.to_string())?;
+    issue_key: String,
+) -> Result<(), String> {
+    let issue_key = issue_key.trim().to_uppercase();
+    // Fail before the Jira round trip when possible
+    state.check_reassign(timer_id, &issue_key)?;
 
-    let pos = timers
-        .iter()
-        .position(|t| t.id == timer_id)
-        .ok_or("Timer not found")?;
+    let client = crate::build_client(&config_state)?;
+    let detail = client.get_issue_detail(&issue_key).await?;
 
-    let mut timer = timers.remove(pos);
+    state.reassign(timer_id, detail.key, detail.summary)?;
+    emit_changed(&app, &state);
+    Ok(())
+}
 
-    // Calculate final elapsed time
-    if !timer.paused {
-        let now = Utc::now();
-        let running_since = timer.pause_start.unwrap_or(timer.started_at);
-        let additional = (now - running_since).num_seconds().max(0) as u64;
-        timer.elapsed_seconds += additional;
-    }
+#[tauri::command]
+pub fn get_timers(state: tauri::State<'_, TimerState>) -> Result<Vec<TimerView>, String> {
+    state.snapshot(Utc::now())
+}
 
-    Ok(timer)
+#[tauri::command]
+pub fn get_timer_policy(state: tauri::State<'_, TimerState>) -> Result<TimerPolicy, String> {
+    state.policy()
 }
 
 #[tauri::command]
-pub fn get_timers(state: tauri::State<'_, TimerState>) -> Result<Vec<Timer>, String> {
-    let timers = state.timers.lock().map_err(|e| e.to_string())?;
+pub fn set_timer_policy(
+    app: AppHandle,
+    state: tauri::State<'_, TimerState>,
+    policy: TimerPolicy,
+) -> Result<(), String> {
+    state.set_policy(policy, Utc::now())?;
+    emit_changed(&app, &state);
+    Ok(())
+}
 
-    let now = Utc::now();
-    let result: Vec<Timer> = timers
-        .iter()
-        .map(|t| {
-            let mut timer = t.clone();
-            if !timer.paused {
-                let running_since = timer.pause_start.unwrap_or(timer.started_at);
-                let additional = (now - running_since).num_seconds().max(0) as u64;
-                timer.elapsed_seconds += additional;
-            }
-            timer
-        })
-        .collect();
+/// Today's run intervals, from midnight local time.
+#[tauri::command]
+pub fn get_timeline(state: tauri::State<'_, TimerState>) -> Result<Vec<TimelineEntry>, String> {
+    let midnight = Local::now()
+        .date_naive()
+        .and_hms_opt(0, 0, 0)
+        .and_then(|t| t.and_local_timezone(Local).earliest())
+        .ok_or("Invalid local midnight")?;
 
-    Ok(result)
+    let timers = state.timers.lock().map_err(|e| e.to_string())?;
+    let history = state.history.lock().map_err(|e| e.to_string())?;
+    Ok(timeline(&timers, &history, midnight.with_timezone(&Utc)))
 }
 
 #[tauri::command]
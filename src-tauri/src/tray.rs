@@ -0,0 +1,238 @@
+use crate::reminders::{format_hours, local_midnight, notify};
+use crate::timer::{self, HistoryEntry, TimerState, TimerView};
+use chrono::{DateTime, Local, Utc};
+use std::time::Duration;
+use tauri::menu::{Menu, MenuBuilder, SubmenuBuilder};
+use tauri::tray::{TrayIconBuilder, TrayIconEvent};
+use tauri::{AppHandle, Manager, Wry};
+
+const TRAY_ID: &str = "main";
+const RECENT_TICKETS: usize = 8;
+/// Elapsed times in the menu are shown to the minute.
+const REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// What a tray menu item does, encoded in its menu id.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TrayAction {
+    Show,
+    Quit,
+    Pause(u32),
+    Resume(u32),
+    Log(u32),
+    Start(String),
+}
+
+impl TrayAction {
+    pub fn id(&self) -> String {
+        match self {
+            TrayAction::Show => "show".to_string(),
+            TrayAction::Quit => "quit".to_string(),
+            TrayAction::Pause(id) => format!("pause:{}", id),
+            TrayAction::Resume(id) => format!("resume:{}", id),
+            TrayAction::Log(id) => format!("log:{}", id),
+            TrayAction::Start(issue_key) => format!("start:{}", issue_key),
+        }
+    }
+
+    pub fn parse(id: &str) -> Option<Self> {
+        match id.split_once(':') {
+            None if id == "show" => Some(TrayAction::Show),
+            None if id == "quit" => Some(TrayAction::Quit),
+            Some(("pause", timer_id)) => timer_id.parse().ok().map(TrayAction::Pause),
+            Some(("resume", timer_id)) => timer_id.parse().ok().map(TrayAction::Resume),
+            Some(("log", timer_id)) => timer_id.parse().ok().map(TrayAction::Log),
+            Some(("start", issue_key)) if !issue_key.is_empty() => {
+                Some(TrayAction::Start(issue_key.to_string()))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Recently stopped tickets as `(issue key, summary)`, newest first, without
+/// the ones that already have a timer.
+pub fn recent_tickets(
+    history: &[HistoryEntry],
+    timers: &[TimerView],
+    limit: usize,
+) -> Vec<(String, String)> {
+    let mut recent: Vec<(String, String)> = Vec::new();
+    for entry in history.iter().rev() {
+        if recent.len() == limit {
+            break;
+        }
+        let tracked = timers.iter().any(|t| t.timer.issue_key == entry.issue_key);
+        if !tracked && !recent.iter().any(|(key, _)| *key == entry.issue_key) {
+            recent.push((entry.issue_key.clone(), entry.summary.clone()));
+        }
+    }
+    recent
+}
+
+/// Time tracked by active and stopped timers between `since` and `now`.
+pub fn tracked_since(
+    timers: &[TimerView],
+    history: &[HistoryEntry],
+    since: DateTime<Utc>,
+    now: DateTime<Utc>,
+) -> u64 {
+    let active: u64 = timers.iter().map(|t| t.timer.running_between(since, now)).sum();
+    let stopped: u64 = history
+        .iter()
+        .flat_map(|h| &h.segments)
+        .map(|s| s.seconds_between(since, now))
+        .sum();
+    active + stopped
+}
+
+pub fn timer_label(view: &TimerView) -> String {
+    let elapsed = format_hours(view.elapsed_seconds);
+    if view.paused {
+        format!("{} — {} (paused)", view.timer.issue_key, elapsed)
+    } else {
+        format!("{} — {}", view.timer.issue_key, elapsed)
+    }
+}
+
+/// The most recently started or resumed running timer, and today's total.
+pub fn tooltip(timers: &[TimerView], today_seconds: u64) -> String {
+    let active = timers
+        .iter()
+        .filter(|t| !t.paused)
+        .max_by_key(|t| t.timer.segments.last().map(|s| s.start));
+
+    let mut lines = vec!["Jira Timesheet".to_string()];
+    if let Some(active) = active {
+        lines.push(timer_label(active));
+    }
+    lines.push(format!("Today: {}", format_hours(today_seconds)));
+    lines.join("\n")
+}
+
+fn build_menu(
+    app: &AppHandle,
+    timers: &[TimerView],
+    recent: &[(String, String)],
+) -> tauri::Result<Menu<Wry>> {
+    let mut menu = MenuBuilder::new(app);
+
+    for view in timers {
+        let toggle = if view.paused {
+            (TrayAction::Resume(view.timer.id).id(), "Resume")
+        } else {
+            (TrayAction::Pause(view.timer.id).id(), "Pause")
+        };
+        let submenu = SubmenuBuilder::new(app, timer_label(view))
+            .text(toggle.0, toggle.1)
+            .text(TrayAction::Log(view.timer.id).id(), "Stop and log")
+            .build()?;
+        menu = menu.item(&submenu);
+    }
+    if !timers.is_empty() {
+        menu = menu.separator();
+    }
+
+    let mut recent_menu = SubmenuBuilder::new(app, "Recent tickets").enabled(!recent.is_empty());
+    for (issue_key, summary) in recent {
+        recent_menu = recent_menu.text(
+            TrayAction::Start(issue_key.clone()).id(),
+            format!("{} {}", issue_key, summary),
+        );
+    }
+    let recent_menu = recent_menu.build()?;
+
+    menu.item(&recent_menu)
+        .separator()
+        .text(TrayAction::Show.id(), "Show")
+        .text(TrayAction::Quit.id(), "Quit")
+        .build()
+}
+
+/// Menu and tooltip from the current timers and history.
+fn contents(app: &AppHandle) -> tauri::Result<(Menu<Wry>, String)> {
+    let state = app.state::<TimerState>();
+    let now = Local::now();
+    let timers = state.snapshot(now.with_timezone(&Utc)).unwrap_or_default();
+    let history = state.history.lock().map(|h| h.clone()).unwrap_or_default();
+
+    let recent = recent_tickets(&history, &timers, RECENT_TICKETS);
+    let today = tracked_since(&timers, &history, local_midnight(now), now.with_timezone(&Utc));
+    Ok((build_menu(app, &timers, &recent)?, tooltip(&timers, today)))
+}
+
+/// Rebuild the tray menu and tooltip, e.g. after timers changed.
+pub fn refresh(app: &AppHandle) {
+    let Some(tray) = app.tray_by_id(TRAY_ID) else {
+        return;
+    };
+    if let Ok((menu, tooltip)) = contents(app) {
+        let _ = tray.set_menu(Some(menu));
+        let _ = tray.set_tooltip(Some(tooltip));
+    }
+}
+
+fn handle_action(app: &AppHandle, action: TrayAction) {
+    let state = app.state::<TimerState>();
+    let now = Utc::now();
+    let result = match action {
+        TrayAction::Show => {
+            crate::show_main_window(app);
+            return;
+        }
+        TrayAction::Quit => {
+            crate::request_quit(app);
+            return;
+        }
+        TrayAction::Pause(id) => state.pause(id, now),
+        TrayAction::Resume(id) => state.resume(id, now),
+        TrayAction::Start(issue_key) => {
+            let summary = state
+                .history
+                .lock()
+                .ok()
+                .and_then(|h| h.iter().rev().find(|e| e.issue_key == issue_key).map(|e| e.summary.clone()))
+                .unwrap_or_default();
+            state.start(issue_key, summary, now).map(|_| ())
+        }
+        TrayAction::Log(id) => {
+            crate::spawn_stop_and_log(app, id);
+            return;
+        }
+    };
+
+    match result {
+        Ok(()) => timer::emit_changed(app, &state),
+        Err(e) => notify(app, &e),
+    }
+}
+
+/// Set up the tray icon declared in `tauri.conf.json` and keep its menu up
+/// to date.
+pub fn create(app: &AppHandle) -> tauri::Result<()> {
+    let (menu, tooltip) = contents(app)?;
+    let tray = match app.tray_by_id(TRAY_ID) {
+        Some(tray) => tray,
+        None => TrayIconBuilder::with_id(TRAY_ID).build(app)?,
+    };
+    tray.set_menu(Some(menu))?;
+    tray.set_tooltip(Some(tooltip))?;
+    tray.on_menu_event(|app, event| {
+        if let Some(action) = TrayAction::parse(event.id.as_ref()) {
+            handle_action(app, action);
+        }
+    });
+    tray.on_tray_icon_event(|tray, event| {
+        if let TrayIconEvent::Click { .. } = event {
+            crate::show_main_window(tray.app_handle());
+        }
+    });
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(REFRESH_INTERVAL).await;
+            refresh(&app);
+        }
+    });
+    Ok(())
+}
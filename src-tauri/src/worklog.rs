@@ -0,0 +1,336 @@
+use crate::jira::JiraClient;
+use crate::rounding::RoundingConfig;
+use crate::timer::{Timer, WorklogPart};
+use chrono::{DateTime, Duration, Local, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+
+/// How far the allocated total may be from the timer's elapsed time, since
+/// the timer keeps running while the user fills in the split.
+const SPLIT_TOLERANCE_SECONDS: u64 = 60;
+
+/// A worklog ready to be posted to Jira.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WorklogRequest {
+    pub issue_key: String,
+    pub seconds: u64,
+    pub started: DateTime<Utc>,
+    pub comment: Option<String>,
+}
+
+/// A worklog that could not be posted, kept for a later retry.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingWorklog {
+    pub id: u32,
+    #[serde(flatten)]
+    pub request: WorklogRequest,
+    pub error: String,
+    pub queued_at: DateTime<Utc>,
+}
+
+#[derive(Default)]
+pub struct WorklogQueue {
+    pub pending: Mutex<Vec<PendingWorklog>>,
+    pub next_id: Mutex<u32>,
+}
+
+impl WorklogQueue {
+    pub fn new() -> Self {
+        Self {
+            pending: Mutex::new(Vec::new()),
+            next_id: Mutex::new(1),
+        }
+    }
+
+    pub fn push(&self, request: WorklogRequest, error: &str) -> Result<(), String> {
+        let mut pending = self.pending.lock().map_err(|e| e.to_string())?;
+        let mut next_id = self.next_id.lock().map_err(|e| e.to_string())?;
+        pending.push(PendingWorklog {
+            id: *next_id,
+            request,
+            error: error.to_string(),
+            queued_at: Utc::now(),
+        });
+        *next_id += 1;
+        Ok(())
+    }
+
+    /// Bring back worklogs still queued when the app last quit.
+    pub fn restore(&self, restored: Vec<PendingWorklog>) -> Result<(), String> {
+        let mut pending = self.pending.lock().map_err(|e| e.to_string())?;
+        let mut next_id = self.next_id.lock().map_err(|e| e.to_string())?;
+        for mut worklog in restored {
+            worklog.id = *next_id;
+            *next_id += 1;
+            pending.push(worklog);
+        }
+        Ok(())
+    }
+
+    pub fn list(&self) -> Result<Vec<PendingWorklog>, String> {
+        let pending = self.pending.lock().map_err(|e| e.to_string())?;
+        Ok(pending.clone())
+    }
+
+    pub fn remove(&self, id: u32) -> Result<(), String> {
+        let mut pending = self.pending.lock().map_err(|e| e.to_string())?;
+        let pos = pending
+            .iter()
+            .position(|w| w.id == id)
+            .ok_or("Pending worklog not found")?;
+        pending.remove(pos);
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct LogOutcome {
+    pub logged: usize,
+    /// Worklogs left in the queue after a failure part way through.
+    pub queued: usize,
+}
+
+/// Post the worklogs in order. If the first one fails nothing was logged and
+/// the error is returned; a later failure queues it and the rest, so the
+/// time is either not logged at all or eventually logged in full.
+pub async fn log_all(
+    client: &JiraClient,
+    queue: &WorklogQueue,
+    requests: Vec<WorklogRequest>,
+) -> Result<LogOutcome, String> {
+    let total = requests.len();
+    let mut requests = requests.into_iter();
+    let mut logged = 0;
+
+    while let Some(request) = requests.next() {
+        let result = client
+            .log_worklog(
+                &request.issue_key,
+                request.seconds,
+                Some(request.started),
+                request.comment.as_deref(),
+            )
+            .await;
+
+        if let Err(e) = result {
+            if logged == 0 {
+                return Err(e);
+            }
+            queue.push(request, &e)?;
+            for rest in requests {
+                queue.push(rest, &e)?;
+            }
+            return Ok(LogOutcome {
+                logged,
+                queued: total - logged,
+            });
+        }
+        logged += 1;
+    }
+
+    Ok(LogOutcome { logged, queued: 0 })
+}
+
+/// Retry every queued worklog, keeping the ones that fail again.
+pub async fn retry_all(client: &JiraClient, queue: &WorklogQueue) -> Result<LogOutcome, String> {
+    let mut outcome = LogOutcome {
+        logged: 0,
+        queued: 0,
+    };
+
+    for worklog in queue.list()? {
+        let request = &worklog.request;
+        let result = client
+            .log_worklog(
+                &request.issue_key,
+                request.seconds,
+                Some(request.started),
+                request.comment.as_deref(),
+            )
+            .await;
+
+        match result {
+            Ok(()) => {
+                queue.remove(worklog.id)?;
+                outcome.logged += 1;
+            }
+            Err(e) => {
+                if let Ok(mut pending) = queue.pending.lock() {
+                    if let Some(item) = pending.iter_mut().find(|w| w.id == worklog.id) {
+                        item.error = e;
+                    }
+                }
+                outcome.queued += 1;
+            }
+        }
+    }
+
+    Ok(outcome)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AllocationAmount {
+    Seconds(u64),
+    Percent(f64),
+}
+
+/// Part of a timer to log on a given ticket, e.g. `{"issue_key": "PROJ-1", "percent": 60}`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Allocation {
+    pub issue_key: String,
+    #[serde(flatten)]
+    pub amount: AllocationAmount,
+}
+
+/// Turn allocations into worklogs covering exactly the timer's `parts` (from
+/// `Timer::worklog_parts` per segment). The allocations follow each other
+/// through the parts, skipping pauses, with one worklog per allocation and
+/// day. The last allocation absorbs rounding and the time elapsed since the
+/// split was entered.
+pub fn split_worklogs(
+    allocations: &[Allocation],
+    parts: &[WorklogPart],
+    comment: Option<&str>,
+) -> Result<Vec<WorklogRequest>, String> {
+    if allocations.is_empty() {
+        return Err("No allocations given".to_string());
+    }
+    let Some(first) = parts.first() else {
+        return Err("Worklog must be at least 1 minute".to_string());
+    };
+    let elapsed_seconds: u64 = parts.iter().map(|p| p.seconds).sum();
+
+    let mut seconds: Vec<u64> = allocations
+        .iter()
+        .map(|a| match a.amount {
+            AllocationAmount::Seconds(s) => Ok(s),
+            AllocationAmount::Percent(p) if (0.0..=100.0).contains(&p) => {
+                Ok((elapsed_seconds as f64 * p / 100.0).round() as u64)
+            }
+            AllocationAmount::Percent(p) => Err(format!("Invalid percentage {} for {}", p, a.issue_key)),
+        })
+        .collect::<Result<_, _>>()?;
+
+    let allocated: u64 = seconds.iter().sum();
+    if allocated.abs_diff(elapsed_seconds) > SPLIT_TOLERANCE_SECONDS {
+        return Err(format!(
+            "Allocations add up to {}s but the timer has {}s",
+            allocated, elapsed_seconds
+        ));
+    }
+    let last = seconds.len() - 1;
+    seconds[last] = (seconds[last] + elapsed_seconds).saturating_sub(allocated);
+
+    if let Some((allocation, _)) = allocations.iter().zip(&seconds).find(|(_, s)| **s < 60) {
+        return Err(format!(
+            "Worklog for {} must be at least 1 minute",
+            allocation.issue_key
+        ));
+    }
+
+    let mut parts = parts.iter().copied();
+    let mut current = parts.next();
+    // End of the time handed out so far
+    let mut end = first.started;
+    let mut requests = Vec::new();
+    for (allocation, seconds) in allocations.iter().zip(seconds) {
+        // Where this allocation's time falls, one piece per day
+        let mut pieces: Vec<WorklogPart> = Vec::new();
+        let mut remaining = seconds;
+        while remaining > 0 {
+            let Some(part) = current.as_mut() else {
+                break;
+            };
+            let taken = remaining.min(part.seconds);
+            match pieces.last_mut() {
+                Some(last) if local_date(last.started) == local_date(part.started) => last.seconds += taken,
+                _ => pieces.push(WorklogPart {
+                    started: part.started,
+                    seconds: taken,
+                }),
+            }
+            remaining -= taken;
+            part.started += Duration::seconds(taken as i64);
+            part.seconds -= taken;
+            end = part.started;
+            if part.seconds == 0 {
+                current = parts.next();
+            }
+        }
+
+        // A piece under a minute, which Jira rejects, goes to the next one
+        let mut carry = 0;
+        let mut merged: Vec<WorklogPart> = Vec::new();
+        for piece in pieces {
+            let seconds = piece.seconds + carry;
+            if seconds < 60 {
+                carry = seconds;
+                continue;
+            }
+            carry = 0;
+            merged.push(WorklogPart { seconds, ..piece });
+        }
+        carry += remaining;
+        match merged.last_mut() {
+            Some(last) => last.seconds += carry,
+            // Allocations a little over the timer's time run past its end
+            None => merged.push(WorklogPart {
+                started: end,
+                seconds: carry,
+            }),
+        }
+
+        requests.extend(merged.into_iter().map(|piece| WorklogRequest {
+            issue_key: allocation.issue_key.clone(),
+            seconds: piece.seconds,
+            started: piece.started,
+            comment: comment.map(str::to_string),
+        }));
+    }
+    Ok(requests)
+}
+
+fn local_date(at: DateTime<Utc>) -> chrono::NaiveDate {
+    at.with_timezone(&Local).date_naive()
+}
+
+/// Worklogs for a timer, one per part from `Timer::worklog_parts`.
+pub fn timer_worklogs(
+    timer: &Timer,
+    per_segment: bool,
+    comment: Option<&str>,
+    now: DateTime<Utc>,
+) -> Vec<WorklogRequest> {
+    timer
+        .worklog_parts(per_segment, now)
+        .into_iter()
+        .map(|part| WorklogRequest {
+            issue_key: timer.issue_key.clone(),
+            seconds: part.seconds,
+            started: part.started,
+            comment: comment.map(str::to_string),
+        })
+        .collect()
+}
+
+/// A stopped timer's worklogs after rounding. Fails when a worklog would be
+/// under a minute.
+pub fn timer_requests(
+    rounding: &RoundingConfig,
+    timer: &Timer,
+    per_segment: bool,
+    comment: Option<&str>,
+    now: DateTime<Utc>,
+) -> Result<Vec<WorklogRequest>, String> {
+    let mut requests = timer_worklogs(timer, per_segment, comment, now);
+    rounding.apply(&mut requests);
+    // Rounding down can leave nothing to log for a short segment
+    requests.retain(|r| r.seconds > 0);
+
+    if requests.is_empty() || requests.iter().any(|r| r.seconds < 60) {
+        return Err("Worklog must be at least 1 minute".to_string());
+    }
+    Ok(requests)
+}
+
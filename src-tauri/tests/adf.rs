@@ -0,0 +1,227 @@
+use jira_timesheet::adf;
+use serde_json::json;
+
+fn rich_doc() -> serde_json::Value {
+    serde_json::from_str(include_str!("fixtures/adf_rich.json")).unwrap()
+}
+
+#[test]
+fn html_keeps_links_mentions_and_emphasis() {
+    let html = adf::to_html(&rich_doc());
+
+    assert!(html.starts_with(
+        "<p>Ping <span class=\"adf-mention\">@Alex Martin</span>, see \
+         <a href=\"https://wiki.example.com/runbook\" target=\"_blank\" rel=\"noopener noreferrer\">the runbook</a> \
+         for <strong>very</strong> <em>important</em> steps.</p>"
+    ), "{}", html);
+}
+
+#[test]
+fn html_renders_lists_code_and_tables() {
+    let html = adf::to_html(&rich_doc());
+
+    assert!(html.contains(
+        "<ol start=\"3\"><li><p>Drain the node</p><ul><li><p><code>kubectl drain</code></p></li></ul></li>\
+         <li><p>Reboot</p></li></ol>"
+    ), "{}", html);
+    assert!(html.contains(
+        "<pre><code class=\"language-bash\">systemctl restart app\necho &quot;&lt;done&gt;&quot;</code></pre>"
+    ), "{}", html);
+    assert!(html.contains(
+        "<table><tbody><tr><th><p>Env</p></th><th><p>Owner</p></th></tr>\
+         <tr><td><p>prod</p></td><td><p>ops|team</p></td></tr></tbody></table>"
+    ), "{}", html);
+}
+
+#[test]
+fn html_escapes_text_and_drops_unsafe_links() {
+    let doc = json!({
+        "type": "doc",
+        "content": [{
+            "type": "paragraph",
+            "content": [
+                { "type": "text", "text": "<img src=x onerror=alert(1)>" },
+                {
+                    "type": "text",
+                    "text": "click",
+                    "marks": [{ "type": "link", "attrs": { "href": "javascript:alert(1)" } }]
+                },
+                { "type": "inlineCard", "attrs": { "url": "data:text/html,<script>" } }
+            ]
+        }]
+    });
+
+    let html = adf::to_html(&doc);
+
+    assert_eq!(html, "<p>&lt;img src=x onerror=alert(1)&gt;clickdata:text/html,&lt;script&gt;</p>");
+}
+
+#[test]
+fn markdown_round_trips_structure() {
+    let md = adf::to_markdown(&rich_doc());
+
+    assert_eq!(
+        md,
+        "Ping @Alex Martin, see [the runbook](https://wiki.example.com/runbook) for **very** *important* steps.\n\
+         \n\
+         3. Drain the node\n   - `kubectl drain`\n4. Reboot\n\
+         \n\
+         ```bash\nsystemctl restart app\necho \"<done>\"\n```\n\
+         \n\
+         | Env | Owner |\n| --- | --- |\n| prod | ops\\|team |"
+    );
+}
+
+#[test]
+fn markdown_handles_quotes_breaks_and_tasks() {
+    let doc = json!({
+        "type": "doc",
+        "content": [
+            {
+                "type": "blockquote",
+                "content": [
+                    { "type": "paragraph", "content": [
+                        { "type": "text", "text": "line one" },
+                        { "type": "hardBreak" },
+                        { "type": "text", "text": "line two" }
+                    ]}
+                ]
+            },
+            {
+                "type": "taskList",
+                "content": [
+                    { "type": "taskItem", "attrs": { "state": "DONE" }, "content": [{ "type": "text", "text": "Deploy" }] },
+                    { "type": "taskItem", "attrs": { "state": "TODO" }, "content": [{ "type": "text", "text": "Announce" }] }
+                ]
+            }
+        ]
+    });
+
+    assert_eq!(
+        adf::to_markdown(&doc),
+        "> line one\\\n> line two\n\n- [x] Deploy\n- [ ] Announce"
+    );
+}
+
+#[test]
+fn markdown_to_adf_paragraphs_marks_and_links() {
+    let doc = adf::from_markdown("Fixed **the** *redirect*, see [PR](https://git.example.com/pr/1) and ~~old~~ `code`.");
+
+    assert_eq!(
+        doc,
+        json!({
+            "type": "doc",
+            "version": 1,
+            "content": [{
+                "type": "paragraph",
+                "content": [
+                    { "type": "text", "text": "Fixed " },
+                    { "type": "text", "text": "the", "marks": [{ "type": "strong" }] },
+                    { "type": "text", "text": " " },
+                    { "type": "text", "text": "redirect", "marks": [{ "type": "em" }] },
+                    { "type": "text", "text": ", see " },
+                    { "type": "text", "text": "PR", "marks": [{ "type": "link", "attrs": { "href": "https://git.example.com/pr/1" } }] },
+                    { "type": "text", "text": " and " },
+                    { "type": "text", "text": "old", "marks": [{ "type": "strike" }] },
+                    { "type": "text", "text": " " },
+                    { "type": "text", "text": "code", "marks": [{ "type": "code" }] },
+                    { "type": "text", "text": "." }
+                ]
+            }]
+        })
+    );
+}
+
+#[test]
+fn markdown_to_adf_lists_wrap_items_in_paragraphs() {
+    let doc = adf::from_markdown("3. first\n4. second\n   - nested\n");
+
+    assert_eq!(
+        doc["content"],
+        json!([{
+            "type": "orderedList",
+            "attrs": { "order": 3 },
+            "content": [
+                {
+                    "type": "listItem",
+                    "content": [{ "type": "paragraph", "content": [{ "type": "text", "text": "first" }] }]
+                },
+                {
+                    "type": "listItem",
+                    "content": [
+                        { "type": "paragraph", "content": [{ "type": "text", "text": "second" }] },
+                        {
+                            "type": "bulletList",
+                            "content": [{
+                                "type": "listItem",
+                                "content": [{ "type": "paragraph", "content": [{ "type": "text", "text": "nested" }] }]
+                            }]
+                        }
+                    ]
+                }
+            ]
+        }])
+    );
+}
+
+#[test]
+fn markdown_to_adf_code_blocks_quotes_and_breaks() {
+    let doc = adf::from_markdown("# Notes\n\n```rust\nfn main() {}\n```\n\n> line one\n> line two\n\n---");
+
+    assert_eq!(
+        doc["content"],
+        json!([
+            { "type": "heading", "attrs": { "level": 1 }, "content": [{ "type": "text", "text": "Notes" }] },
+            { "type": "codeBlock", "attrs": { "language": "rust" }, "content": [{ "type": "text", "text": "fn main() {}" }] },
+            {
+                "type": "blockquote",
+                "content": [{
+                    "type": "paragraph",
+                    "content": [
+                        { "type": "text", "text": "line one" },
+                        { "type": "hardBreak" },
+                        { "type": "text", "text": "line two" }
+                    ]
+                }]
+            },
+            { "type": "rule" }
+        ])
+    );
+}
+
+#[test]
+fn markdown_to_adf_mentions_use_account_ids() {
+    let doc = adf::from_markdown("cc [@Alex Martin](accountid:5b10ac8d82e05b22cc7d4ef5)");
+
+    assert_eq!(
+        doc["content"][0]["content"][1],
+        json!({ "type": "mention", "attrs": { "id": "5b10ac8d82e05b22cc7d4ef5", "text": "@Alex Martin" } })
+    );
+}
+
+#[test]
+fn markdown_round_trip_through_adf() {
+    let markdown = "Deployed **v2** to [staging](https://staging.example.com)\n\n1. migrate\n2. restart\n\n```\nmake deploy\n```";
+
+    assert_eq!(adf::to_markdown(&adf::from_markdown(markdown)), markdown);
+}
+
+#[test]
+fn plain_text_keeps_list_items_on_their_bullet_line() {
+    let doc = json!({
+        "type": "doc",
+        "content": [
+            { "type": "paragraph", "content": [{ "type": "text", "text": "Steps" }] },
+            { "type": "bulletList", "content": [
+                { "type": "listItem", "content": [
+                    { "type": "paragraph", "content": [{ "type": "text", "text": "Open a deep link" }] }
+                ] },
+                { "type": "listItem", "content": [
+                    { "type": "paragraph", "content": [{ "type": "text", "text": "Sign in" }] }
+                ] }
+            ] }
+        ]
+    });
+
+    assert_eq!(adf::to_plain_text(&doc), "Steps\n- Open a deep link\n- Sign in");
+}
@@ -0,0 +1,108 @@
+use chrono::NaiveDate;
+use jira_timesheet::api::{authorized, error_status, generate_token, read_request, route};
+use jira_timesheet::ipc::{Failure, Request};
+
+fn today() -> NaiveDate {
+    NaiveDate::from_ymd_opt(2024, 3, 4).unwrap()
+}
+
+#[tokio::test]
+async fn test_read_request_with_body() {
+    let raw = "POST /timers/log HTTP/1.1\r\nHost: 127.0.0.1\r\nAuthorization: Bearer abc\r\ncontent-length: 20\r\n\r\n{\"comment\": \"Done\"}\n";
+    let request = read_request(&mut raw.as_bytes()).await.unwrap();
+
+    assert_eq!(request.method, "POST");
+    assert_eq!(request.target, "/timers/log");
+    assert_eq!(request.authorization.as_deref(), Some("Bearer abc"));
+    assert_eq!(request.body, "{\"comment\": \"Done\"}\n");
+}
+
+#[tokio::test]
+async fn test_read_request_rejects_incomplete_or_oversized() {
+    assert!(read_request(&mut "GET /timers HTTP/1.1\r\n".as_bytes()).await.is_err());
+
+    let raw = "POST /timers/start HTTP/1.1\r\nContent-Length: 10000000\r\n\r\n";
+    assert!(read_request(&mut raw.as_bytes()).await.is_err());
+
+    // One endless header line is cut at the limit rather than buffered
+    let raw = format!("GET /timers HTTP/1.1\r\nX-Padding: {}\r\n\r\n", "a".repeat(1024 * 1024));
+    assert_eq!(
+        read_request(&mut raw.as_bytes()).await.unwrap_err(),
+        "Request headers too large"
+    );
+}
+
+#[test]
+fn test_authorized_needs_the_exact_token() {
+    assert!(authorized(Some("Bearer s3cret"), "s3cret"));
+    assert!(!authorized(Some("Bearer s3cre"), "s3cret"));
+    assert!(!authorized(Some("s3cret"), "s3cret"));
+    assert!(!authorized(None, "s3cret"));
+}
+
+#[test]
+fn test_route_timer_actions_and_worklogs() {
+    assert_eq!(route("GET", "/timers", "", today()), Ok(Request::Status));
+    assert_eq!(
+        route("POST", "/timers/start", r#"{"issue_key": "proj-1"}"#, today()),
+        Ok(Request::Start {
+            issue_key: "PROJ-1".to_string()
+        })
+    );
+    assert_eq!(
+        route("POST", "/timers/pause", "", today()),
+        Ok(Request::Pause { issue_key: None })
+    );
+    assert_eq!(
+        route("POST", "/timers/log", r#"{"comment": "Review"}"#, today()),
+        Ok(Request::Log {
+            issue_key: None,
+            comment: Some("Review".to_string())
+        })
+    );
+    assert_eq!(
+        route("GET", "/worklogs?from=2024-03-01&to=2024-03-08", "", today()),
+        Ok(Request::Report {
+            from: "2024-03-01".to_string(),
+            to: "2024-03-08".to_string()
+        })
+    );
+    assert_eq!(
+        route("GET", "/worklogs", "", today()),
+        Ok(Request::Report {
+            from: "2024-03-04".to_string(),
+            to: "2024-03-04".to_string()
+        })
+    );
+}
+
+#[test]
+fn test_route_errors() {
+    assert_eq!(route("POST", "/timers/start", "", today()).unwrap_err().0, 400);
+    assert_eq!(route("POST", "/timers/start", "not json", today()).unwrap_err().0, 400);
+    assert_eq!(route("GET", "/worklogs?from=03/01", "", today()).unwrap_err().0, 400);
+    assert_eq!(route("DELETE", "/timers", "", today()).unwrap_err().0, 405);
+    assert_eq!(route("POST", "/timers/transition", "", today()).unwrap_err().0, 404);
+    assert_eq!(route("GET", "/", "", today()).unwrap_err().0, 404);
+}
+
+#[test]
+fn test_error_status_tells_upstream_failures_from_bad_requests() {
+    let message = || "Jira API error 503 Service Unavailable: ".to_string();
+    assert_eq!(error_status(&Failure::Upstream(message())), 502);
+    assert_eq!(error_status(&Failure::NotConfigured(message())), 500);
+    assert_eq!(error_status(&Failure::Invalid(message())), 400);
+    assert_eq!(
+        error_status(&Failure::from("Worklog must be at least 1 minute".to_string())),
+        400
+    );
+}
+
+#[test]
+fn test_generate_token_is_32_random_bytes_in_hex() {
+    let token = generate_token().unwrap();
+
+    assert_eq!(token.len(), 64);
+    assert!(token.chars().all(|c| c.is_ascii_hexdigit()));
+    assert_ne!(token, generate_token().unwrap());
+}
@@ -0,0 +1,223 @@
+use chrono::{Duration, Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use jira_timesheet::calendar::{
+    events_between, parse_duration, parse_rules, propose, CalendarEvent, CalendarRule, CalendarWorklog, ImportedEvent,
+    ImportedEvents, RuleField,
+};
+use std::path::PathBuf;
+
+const CALENDAR: &str = include_str!("fixtures/calendar.ics");
+const CALENDAR_ZONES: &str = include_str!("fixtures/calendar_zones.ics");
+
+fn date(day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2024, 3, day).unwrap()
+}
+
+/// Wall-clock start in `zone`.
+fn start_in(zone: Tz) -> impl Fn(&CalendarEvent) -> NaiveDateTime {
+    move |event| event.start.with_timezone(&zone).naive_local()
+}
+
+fn at(day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+    date(day).and_hms_opt(hour, minute, 0).unwrap()
+}
+
+#[test]
+fn test_events_between_expands_recurrences_and_overrides() {
+    let events = events_between(CALENDAR, date(4), date(8)).unwrap();
+    let standups: Vec<NaiveDateTime> = events
+        .iter()
+        .filter(|e| e.uid == "standup@example.com")
+        .map(start_in(chrono_tz::Europe::Paris))
+        .collect();
+
+    // Wednesday is excluded and Thursday moved to 11:00
+    assert_eq!(
+        standups,
+        vec![at(4, 9, 30), at(5, 9, 30), at(7, 11, 0), at(8, 9, 30)]
+    );
+    let standup = &events[events.iter().position(|e| e.uid == "standup@example.com").unwrap()];
+    assert_eq!(standup.organizer, "Alice Martin <alice@example.com>");
+    assert_eq!(standup.end - standup.start, Duration::minutes(15));
+}
+
+#[test]
+fn test_events_between_skips_cancelled_and_all_day() {
+    let events = events_between(CALENDAR, date(4), date(8)).unwrap();
+    let titles: Vec<&str> = events.iter().map(|e| e.title.as_str()).collect();
+
+    assert!(!titles.contains(&"Cancelled sync"));
+    assert!(!titles.contains(&"Offsite"));
+    assert_eq!(events.len(), 6);
+
+    let review = events.iter().find(|e| e.uid == "review@example.com").unwrap();
+    assert_eq!(review.title, "Client review, sprint 12");
+    assert_eq!(review.categories, vec!["Client A", "Meeting"]);
+    assert_eq!(review.end - review.start, Duration::minutes(90));
+
+    let oncall = events.iter().find(|e| e.uid == "oncall@example.com").unwrap();
+    assert_eq!(oncall.start, Utc.with_ymd_and_hms(2024, 3, 4, 8, 0, 0).unwrap());
+}
+
+#[test]
+fn test_recurrence_interval_count_and_folded_title() {
+    let retros: Vec<CalendarEvent> = events_between(CALENDAR, date(1), date(31))
+        .unwrap()
+        .into_iter()
+        .filter(|e| e.uid == "retro@example.com")
+        .collect();
+
+    assert_eq!(
+        retros.iter().map(start_in(chrono_tz::Europe::Paris)).collect::<Vec<_>>(),
+        vec![at(1, 15, 0), at(15, 15, 0), at(29, 15, 0)]
+    );
+    assert_eq!(
+        retros[0].title,
+        "Sprint retrospective with a very long title that is folded over two lines"
+    );
+    assert!(events_between("not a calendar", date(1), date(2)).is_err());
+}
+
+#[test]
+fn test_daily_rule_by_day_skips_weekends_in_its_own_time_zone() {
+    let events = events_between(CALENDAR_ZONES, date(1), date(31)).unwrap();
+    let standups: Vec<&CalendarEvent> = events.iter().filter(|e| e.uid == "weekdays@example.com").collect();
+
+    // Friday 1st, then weekdays only, at 10:00 in New York across its DST change
+    assert_eq!(
+        standups.iter().map(|e| start_in(chrono_tz::America::New_York)(e)).collect::<Vec<_>>(),
+        vec![
+            at(1, 10, 0),
+            at(4, 10, 0),
+            at(5, 10, 0),
+            at(6, 10, 0),
+            at(7, 10, 0),
+            at(8, 10, 0),
+            at(11, 10, 0),
+        ]
+    );
+    assert_eq!(standups[5].start, Utc.with_ymd_and_hms(2024, 3, 8, 15, 0, 0).unwrap());
+    assert_eq!(standups[6].start, Utc.with_ymd_and_hms(2024, 3, 11, 14, 0, 0).unwrap());
+    assert!(standups.iter().all(|e| e.unknown_tzid.is_none()));
+}
+
+#[test]
+fn test_unknown_tzid_is_read_as_local_time_and_flagged() {
+    let events = events_between(CALENDAR_ZONES, date(1), date(31)).unwrap();
+    let proposals = propose(&events, &[]).unwrap();
+
+    let outlook = events.iter().find(|e| e.uid == "outlook@example.com").unwrap();
+    assert_eq!(outlook.start.with_timezone(&Local).naive_local(), at(5, 14, 0));
+    let proposal = proposals.iter().find(|p| p.uid == "outlook@example.com").unwrap();
+    assert_eq!(proposal.unknown_tzid.as_deref(), Some("W. Europe Standard Time"));
+}
+
+#[test]
+fn test_parse_duration() {
+    assert_eq!(parse_duration("PT1H30M"), Some(Duration::minutes(90)));
+    assert_eq!(parse_duration("P1DT2H"), Some(Duration::hours(26)));
+    assert_eq!(parse_duration("P2W"), Some(Duration::weeks(2)));
+    assert_eq!(parse_duration("-PT10M"), Some(Duration::minutes(-10)));
+    assert_eq!(parse_duration("PT1D"), None);
+    assert_eq!(parse_duration("1H"), None);
+}
+
+#[test]
+fn test_parse_rules() {
+    let rules = parse_rules("title:stand-?up=ops-12\norganizer:@client\\.com=CLI-3; category:Client A=CA-1").unwrap();
+    assert_eq!(
+        rules,
+        vec![
+            CalendarRule {
+                field: RuleField::Title,
+                pattern: "stand-?up".to_string(),
+                issue_key: "OPS-12".to_string()
+            },
+            CalendarRule {
+                field: RuleField::Organizer,
+                pattern: "@client\\.com".to_string(),
+                issue_key: "CLI-3".to_string()
+            },
+            CalendarRule {
+                field: RuleField::Category,
+                pattern: "Client A".to_string(),
+                issue_key: "CA-1".to_string()
+            },
+        ]
+    );
+
+    assert!(parse_rules("location:room=OPS-1").is_err());
+    assert!(parse_rules("title:standup").is_err());
+    assert!(parse_rules("title:(unclosed=OPS-1").is_err());
+    assert_eq!(parse_rules("  \n ; ").unwrap(), vec![]);
+}
+
+#[test]
+fn test_propose_uses_first_matching_rule() {
+    let events = events_between(CALENDAR, date(4), date(6)).unwrap();
+    let rules = parse_rules("category:^client a$=CA-1; organizer:client\\.com=CLI-3; title:STAND-?UP=OPS-12").unwrap();
+    let proposals = propose(&events, &rules).unwrap();
+
+    let by_uid = |uid: &str| proposals.iter().find(|p| p.uid == uid).unwrap();
+    assert_eq!(by_uid("review@example.com").issue_key.as_deref(), Some("CA-1"));
+    assert_eq!(by_uid("review@example.com").seconds, 5400);
+    assert_eq!(by_uid("standup@example.com").issue_key.as_deref(), Some("OPS-12"));
+    assert_eq!(by_uid("oncall@example.com").issue_key, None);
+}
+
+fn imported_path(name: &str) -> PathBuf {
+    std::env::temp_dir()
+        .join(format!("jira-timesheet-calendar-{}-{}", name, std::process::id()))
+        .join("calendar-imported.json")
+}
+
+fn imported(uid: &str, hour: u32) -> ImportedEvent {
+    ImportedEvent {
+        uid: uid.to_string(),
+        started: Utc.with_ymd_and_hms(2024, 3, 4, hour, 0, 0).unwrap(),
+    }
+}
+
+#[test]
+fn test_imported_events_are_kept_on_disk_per_occurrence() {
+    let path = imported_path("kept");
+    let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    let store = ImportedEvents::open(path.clone());
+    store.add(vec![imported("standup@example.com", 9)]).unwrap();
+
+    let reopened = ImportedEvents::open(path.clone());
+    let event = imported("standup@example.com", 9);
+    assert!(reopened.contains(&event.uid, event.started).unwrap());
+    let next = imported("standup@example.com", 10);
+    assert!(!reopened.contains(&next.uid, next.started).unwrap());
+
+    std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn test_imported_events_that_cannot_be_saved_are_rolled_back() {
+    let path = imported_path("rollback");
+    let _ = std::fs::remove_file(path.parent().unwrap());
+    let store = ImportedEvents::open(path.clone());
+    std::fs::write(path.parent().unwrap(), "").unwrap();
+
+    assert!(store.add(vec![imported("review@example.com", 14)]).is_err());
+    let event = imported("review@example.com", 14);
+    assert!(!store.contains(&event.uid, event.started).unwrap());
+
+    std::fs::remove_file(path.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn test_calendar_worklog_reads_the_uid_next_to_the_request() {
+    let json = concat!(
+        r#"{"uid":"review@example.com","issue_key":"CA-1","seconds":5400,"#,
+        r#""started":"2024-03-04T14:00:00Z","comment":"Review"}"#,
+    );
+    let worklog: CalendarWorklog = serde_json::from_str(json).unwrap();
+
+    assert_eq!(worklog.uid, "review@example.com");
+    assert_eq!(worklog.request.issue_key, "CA-1");
+    assert_eq!(worklog.request.seconds, 5400);
+    assert_eq!(worklog.request.comment.as_deref(), Some("Review"));
+}
@@ -0,0 +1,180 @@
+use chrono::{NaiveDate, TimeZone, Utc};
+use jira_timesheet::cli::{format_reply, parse_args};
+use jira_timesheet::config::AppConfig;
+use jira_timesheet::drafts::DraftStore;
+use jira_timesheet::ipc::{Core, Failure, Reply, Request};
+use jira_timesheet::jira::TimesheetEntry;
+use jira_timesheet::timer::{Timer, TimerState, TimerView};
+use jira_timesheet::worklog::WorklogQueue;
+
+fn args(line: &str) -> Vec<String> {
+    line.split_whitespace().map(String::from).collect()
+}
+
+fn today() -> NaiveDate {
+    NaiveDate::from_ymd_opt(2024, 3, 4).unwrap()
+}
+
+#[test]
+fn test_parse_timer_commands() {
+    assert_eq!(parse_args(&args("status"), today()), Ok(Request::Status));
+    assert_eq!(
+        parse_args(&args("start proj-1"), today()),
+        Ok(Request::Start {
+            issue_key: "PROJ-1".to_string()
+        })
+    );
+    assert_eq!(
+        parse_args(&args("pause"), today()),
+        Ok(Request::Pause { issue_key: None })
+    );
+    assert_eq!(
+        parse_args(&args("resume PROJ-2"), today()),
+        Ok(Request::Resume {
+            issue_key: Some("PROJ-2".to_string())
+        })
+    );
+
+    let log = vec!["log".to_string(), "-m".to_string(), "Code review".to_string()];
+    assert_eq!(
+        parse_args(&log, today()),
+        Ok(Request::Log {
+            issue_key: None,
+            comment: Some("Code review".to_string())
+        })
+    );
+}
+
+#[test]
+fn test_parse_report_and_transition() {
+    assert_eq!(
+        parse_args(&args("report"), today()),
+        Ok(Request::Report {
+            from: "2024-03-04".to_string(),
+            to: "2024-03-04".to_string()
+        })
+    );
+    assert_eq!(
+        parse_args(&args("report 2024-03-01 2024-03-08"), today()),
+        Ok(Request::Report {
+            from: "2024-03-01".to_string(),
+            to: "2024-03-08".to_string()
+        })
+    );
+    assert_eq!(
+        parse_args(&args("transition PROJ-1 In Progress"), today()),
+        Ok(Request::Transition {
+            issue_key: "PROJ-1".to_string(),
+            status: "In Progress".to_string()
+        })
+    );
+}
+
+#[test]
+fn test_parse_errors() {
+    assert!(parse_args(&args("start"), today()).is_err());
+    assert!(parse_args(&args("pause PROJ-1 PROJ-2"), today()).is_err());
+    assert!(parse_args(&args("log -m"), today()).is_err());
+    assert!(parse_args(&args("report 04/03/2024"), today()).is_err());
+    assert!(parse_args(&args("report 2024-03-08 2024-03-01"), today()).is_err());
+    assert!(parse_args(&args("transition PROJ-1"), today()).is_err());
+    assert!(parse_args(&args("frobnicate"), today()).is_err());
+}
+
+#[test]
+fn test_request_wire_format() {
+    let json = serde_json::to_value(Request::Pause { issue_key: None }).unwrap();
+    assert_eq!(json, serde_json::json!({"command": "pause", "issue_key": null}));
+
+    let reply: Result<Reply, String> = Ok(Reply::Transitioned {
+        issue_key: "PROJ-1".to_string(),
+        status: "Done".to_string(),
+    });
+    let json = serde_json::to_string(&reply).unwrap();
+    let back: Result<Reply, String> = serde_json::from_str(&json).unwrap();
+    assert_eq!(format_reply(&back.unwrap()), "PROJ-1 moved to Done");
+}
+
+#[test]
+fn test_format_reply() {
+    let start = Utc.with_ymd_and_hms(2024, 3, 4, 9, 0, 0).unwrap();
+    let timers = vec![TimerView {
+        timer: Timer::new(1, "PROJ-1".to_string(), "Fix login".to_string(), start),
+        elapsed_seconds: 5400,
+        paused: true,
+    }];
+    assert_eq!(
+        format_reply(&Reply::Timers { timers }),
+        "PROJ-1\t1h30\tpaused\tFix login"
+    );
+    assert_eq!(format_reply(&Reply::Timers { timers: vec![] }), "No timers");
+
+    assert_eq!(
+        format_reply(&Reply::Logged {
+            issue_key: "PROJ-1".to_string(),
+            seconds: 3600,
+            queued: 1
+        }),
+        "Logged 1h on PROJ-1, 1 worklog(s) queued for retry"
+    );
+
+    let entries = vec![
+        TimesheetEntry {
+            issue_key: "PROJ-1".to_string(),
+            summary: "Fix login".to_string(),
+            date: "2024-03-04".to_string(),
+            time_spent_seconds: 3600,
+            started: None,
+        },
+        TimesheetEntry {
+            issue_key: "PROJ-2".to_string(),
+            summary: "Review".to_string(),
+            date: "2024-03-04".to_string(),
+            time_spent_seconds: 1800,
+            started: None,
+        },
+    ];
+    assert_eq!(
+        format_reply(&Reply::Worklogs { entries }),
+        "2024-03-04\tPROJ-1\t1h\tFix login\n2024-03-04\tPROJ-2\t0h30\tReview\nTotal\t1h30"
+    );
+}
+
+#[tokio::test]
+async fn test_log_keeps_the_timer_running_until_its_time_is_saved() {
+    let start = Utc.with_ymd_and_hms(2024, 3, 4, 9, 0, 0).unwrap();
+    let (timers, queue, drafts) = (TimerState::new(), WorklogQueue::new(), DraftStore::new());
+    let mut config = AppConfig::default();
+    let log = || Request::Log {
+        issue_key: None,
+        comment: None,
+    };
+    timers.start("PROJ-1".to_string(), "Fix login".to_string(), start).unwrap();
+
+    let core = Core {
+        timers: &timers,
+        queue: &queue,
+        drafts: &drafts,
+        config: &config,
+    };
+    let err = core.dispatch(log(), start + chrono::Duration::minutes(10)).await.unwrap_err();
+    assert!(matches!(err, Failure::NotConfigured(_)));
+    assert!(!timers.snapshot(start).unwrap()[0].paused);
+
+    config.draft_mode = true;
+    let core = Core {
+        timers: &timers,
+        queue: &queue,
+        drafts: &drafts,
+        config: &config,
+    };
+    let err = core.dispatch(log(), start + chrono::Duration::seconds(30)).await.unwrap_err();
+    assert_eq!(err, Failure::Invalid("Worklog must be at least 1 minute".to_string()));
+    assert!(!timers.snapshot(start).unwrap()[0].paused);
+    assert!(drafts.list().unwrap().is_empty());
+
+    let reply = core.dispatch(log(), start + chrono::Duration::minutes(10)).await.unwrap();
+    assert!(matches!(reply, Reply::Drafted { seconds: 600, .. }));
+    assert!(timers.snapshot(start).unwrap().is_empty());
+    assert_eq!(drafts.list().unwrap().len(), 1);
+}
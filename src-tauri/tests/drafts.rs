@@ -0,0 +1,212 @@
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use jira_timesheet::drafts::{submit, DraftStatus, DraftStore};
+use jira_timesheet::jira::JiraClient;
+use jira_timesheet::worklog::WorklogRequest;
+use std::path::PathBuf;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, Request, Respond, ResponseTemplate};
+
+fn at(minutes: i64) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, 3, 4, 9, 0, 0).unwrap() + Duration::minutes(minutes)
+}
+
+fn request(issue_key: &str, seconds: u64, started: DateTime<Utc>) -> WorklogRequest {
+    WorklogRequest {
+        issue_key: issue_key.to_string(),
+        seconds,
+        started,
+        comment: None,
+    }
+}
+
+fn drafts_path(name: &str) -> PathBuf {
+    std::env::temp_dir()
+        .join(format!("jira-timesheet-drafts-{}-{}", name, std::process::id()))
+        .join("drafts.json")
+}
+
+#[test]
+fn test_drafts_are_edited_and_kept_on_disk() {
+    let path = drafts_path("edit");
+    let _ = std::fs::remove_file(&path);
+    let store = DraftStore::open(path.clone());
+
+    let ids = store
+        .add(vec![request("PROJ-1", 1800, at(0)), request("PROJ-2", 900, at(30))], at(60))
+        .unwrap();
+    assert_eq!(ids, vec![1, 2]);
+
+    let mut edited = request("PROJ-3", 2700, at(0));
+    edited.comment = Some("Review".to_string());
+    store.update(1, edited.clone()).unwrap();
+    store.remove(2).unwrap();
+
+    let err = store.update(1, request("PROJ-3", 30, at(0))).unwrap_err();
+    assert_eq!(err, "Worklog must be at least 1 minute");
+    assert!(store.update(1, request(" ", 600, at(0))).is_err());
+
+    let reopened = DraftStore::open(path.clone());
+    let drafts = reopened.list().unwrap();
+    assert_eq!(drafts.len(), 1);
+    assert_eq!(drafts[0].request, edited);
+    assert_eq!(drafts[0].status, DraftStatus::Draft);
+    // Ids keep going after a restart
+    assert_eq!(reopened.add(vec![request("PROJ-4", 600, at(0))], at(60)).unwrap(), vec![2]);
+
+    std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn test_draft_interrupted_while_submitting_is_failed_on_open() {
+    let path = drafts_path("interrupted");
+    let _ = std::fs::remove_file(&path);
+    let store = DraftStore::open(path.clone());
+    store.add(vec![request("PROJ-1", 600, at(0))], at(10)).unwrap();
+
+    let claimed = store.claim(None).unwrap();
+    assert_eq!(claimed.len(), 1);
+    // Already submitting, so not claimed twice
+    assert!(store.claim(None).unwrap().is_empty());
+    assert!(store.remove(1).is_err());
+
+    let drafts = DraftStore::open(path.clone()).list().unwrap();
+    assert_eq!(drafts[0].status, DraftStatus::Failed);
+    assert!(drafts[0].error.as_deref().unwrap().starts_with("Interrupted"));
+
+    std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+}
+
+/// Make the drafts directory unwritable by putting a file in its place.
+fn break_storage(path: &std::path::Path) {
+    let dir = path.parent().unwrap();
+    std::fs::remove_dir_all(dir).unwrap();
+    std::fs::write(dir, "").unwrap();
+}
+
+fn client(server: &MockServer) -> JiraClient {
+    JiraClient::with_http_client(reqwest::Client::new(), &server.uri(), "alex@example.com", "secret")
+}
+
+#[test]
+fn test_changes_that_cannot_be_saved_are_rolled_back() {
+    let path = drafts_path("rollback");
+    let _ = std::fs::remove_file(path.parent().unwrap());
+    let store = DraftStore::open(path.clone());
+    store
+        .add(vec![request("PROJ-1", 600, at(0)), request("PROJ-2", 600, at(10))], at(20))
+        .unwrap();
+    break_storage(&path);
+
+    assert!(store.claim(None).is_err());
+    assert!(store.add(vec![request("PROJ-3", 600, at(30))], at(40)).is_err());
+
+    let drafts = store.list().unwrap();
+    assert_eq!(drafts.len(), 2);
+    assert!(drafts.iter().all(|d| d.status == DraftStatus::Draft));
+
+    std::fs::remove_file(path.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn test_edits_that_cannot_be_saved_are_rolled_back() {
+    let path = drafts_path("edit-rollback");
+    let _ = std::fs::remove_file(path.parent().unwrap());
+    let store = DraftStore::open(path.clone());
+    store
+        .add(vec![request("PROJ-1", 600, at(0)), request("PROJ-2", 600, at(10))], at(20))
+        .unwrap();
+    store.finish(2, Ok(()), at(30)).unwrap();
+    break_storage(&path);
+
+    assert!(store.update(1, request("PROJ-9", 1200, at(5))).is_err());
+    assert!(store.remove(1).is_err());
+    assert!(store.clear_submitted().is_err());
+
+    let drafts = store.list().unwrap();
+    assert_eq!(drafts.len(), 2);
+    assert_eq!(drafts[0].request, request("PROJ-1", 600, at(0)));
+    assert_eq!(drafts[0].status, DraftStatus::Draft);
+    assert_eq!(drafts[1].status, DraftStatus::Submitted);
+
+    std::fs::remove_file(path.parent().unwrap()).unwrap();
+}
+
+/// Accepts the worklog, then breaks the drafts storage.
+struct AcceptThenBreak(PathBuf);
+
+impl Respond for AcceptThenBreak {
+    fn respond(&self, _: &Request) -> ResponseTemplate {
+        break_storage(&self.0);
+        ResponseTemplate::new(201)
+    }
+}
+
+#[tokio::test]
+async fn test_submit_gives_back_unposted_drafts_when_results_cannot_be_saved() {
+    let server = MockServer::start().await;
+    let file = drafts_path("submit-unsaved");
+    let _ = std::fs::remove_file(file.parent().unwrap());
+    let store = DraftStore::open(file.clone());
+    store
+        .add(vec![request("PROJ-1", 600, at(0)), request("PROJ-2", 900, at(10))], at(30))
+        .unwrap();
+    Mock::given(method("POST"))
+        .and(path("/rest/api/3/issue/PROJ-1/worklog"))
+        .respond_with(AcceptThenBreak(file.clone()))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let results = submit(&client(&server), &store, None).await.unwrap();
+
+    assert_eq!(results.len(), 2);
+    assert!(results[0].error.is_none());
+    assert!(results[1].error.as_deref().unwrap().starts_with("Not submitted"));
+    let drafts = store.list().unwrap();
+    assert_eq!(drafts[0].status, DraftStatus::Submitted);
+    assert_eq!(drafts[1].status, DraftStatus::Draft);
+
+    std::fs::remove_file(file.parent().unwrap()).unwrap();
+}
+
+async fn mount_worklog(server: &MockServer, issue_key: &str, status: u16, times: u64) {
+    Mock::given(method("POST"))
+        .and(path(format!("/rest/api/3/issue/{}/worklog", issue_key)))
+        .respond_with(ResponseTemplate::new(status))
+        .up_to_n_times(times)
+        .expect(times)
+        .mount(server)
+        .await;
+}
+
+#[tokio::test]
+async fn test_submit_again_only_posts_the_failed_drafts() {
+    let server = MockServer::start().await;
+    let client = client(&server);
+    mount_worklog(&server, "PROJ-1", 201, 1).await;
+    mount_worklog(&server, "PROJ-2", 503, 1).await;
+    let store = DraftStore::new();
+    store
+        .add(vec![request("PROJ-1", 600, at(0)), request("PROJ-2", 900, at(10))], at(30))
+        .unwrap();
+
+    let results = submit(&client, &store, None).await.unwrap();
+
+    assert_eq!(results.len(), 2);
+    assert!(results[0].error.is_none());
+    assert!(results[1].error.as_deref().unwrap().starts_with("Worklog error 503"));
+    let drafts = store.list().unwrap();
+    assert_eq!(drafts[0].status, DraftStatus::Submitted);
+    assert_eq!(drafts[1].status, DraftStatus::Failed);
+
+    mount_worklog(&server, "PROJ-2", 201, 1).await;
+    let results = submit(&client, &store, None).await.unwrap();
+
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].issue_key, "PROJ-2");
+    assert!(results[0].error.is_none());
+    assert!(store.update(1, request("PROJ-1", 600, at(0))).is_err());
+
+    store.clear_submitted().unwrap();
+    assert!(store.list().unwrap().is_empty());
+}
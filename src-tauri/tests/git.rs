@@ -0,0 +1,82 @@
+use jira_timesheet::git::{issue_key_in, poll, read_branch};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+fn repo(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("jira-timesheet-git-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join(".git")).unwrap();
+    dir
+}
+
+fn checkout(repo: &Path, head: &str) {
+    fs::write(repo.join(".git/HEAD"), format!("{}\n", head)).unwrap();
+}
+
+#[test]
+fn test_issue_key_in_branch_names() {
+    assert_eq!(issue_key_in("feature/PROJ-123-foo"), Some("PROJ-123".to_string()));
+    assert_eq!(issue_key_in("bugfix/PROJ-7_login"), Some("PROJ-7".to_string()));
+    assert_eq!(issue_key_in("OPS2-45"), Some("OPS2-45".to_string()));
+    assert_eq!(issue_key_in("fix/utf-8-paths/OPS-3"), Some("OPS-3".to_string()));
+    assert_eq!(issue_key_in("bugfix/proj-7_login"), None);
+    assert_eq!(issue_key_in("release/v2-3"), None);
+    assert_eq!(issue_key_in("fix/utf-8-paths"), None);
+    assert_eq!(issue_key_in("feature/ProJ-8"), None);
+    assert_eq!(issue_key_in("main"), None);
+    assert_eq!(issue_key_in("feature/x-12"), None);
+    assert_eq!(issue_key_in("feature/PROJ-"), None);
+    assert_eq!(issue_key_in("feature/PROJ-12a"), None);
+}
+
+#[test]
+fn test_read_branch_and_worktree_file() {
+    let main = repo("main");
+    checkout(&main, "ref: refs/heads/feature/PROJ-1-login");
+    assert_eq!(read_branch(&main).unwrap(), Some("feature/PROJ-1-login".to_string()));
+
+    checkout(&main, "3f786850e387550fdab836ed7e6dc881de23001b");
+    assert_eq!(read_branch(&main).unwrap(), None);
+
+    let worktree = std::env::temp_dir().join(format!("jira-timesheet-git-worktree-{}", std::process::id()));
+    fs::create_dir_all(&worktree).unwrap();
+    let gitdir = main.join(".git/worktrees/wt");
+    fs::create_dir_all(&gitdir).unwrap();
+    fs::write(worktree.join(".git"), format!("gitdir: {}\n", gitdir.display())).unwrap();
+    fs::write(gitdir.join("HEAD"), "ref: refs/heads/PROJ-2\n").unwrap();
+    assert_eq!(read_branch(&worktree).unwrap(), Some("PROJ-2".to_string()));
+
+    assert!(read_branch(&std::env::temp_dir().join("jira-timesheet-no-repo")).is_err());
+    let _ = fs::remove_dir_all(&main);
+    let _ = fs::remove_dir_all(&worktree);
+}
+
+#[test]
+fn test_poll_reports_switches_to_another_issue() {
+    let dir = repo("poll");
+    let repos = vec![dir.clone()];
+    let mut seen = HashMap::new();
+
+    checkout(&dir, "ref: refs/heads/feature/PROJ-1-login");
+    // First poll only records the current branch
+    assert!(poll(&repos, &mut seen).is_empty());
+
+    checkout(&dir, "ref: refs/heads/feature/PROJ-2-export");
+    let switches = poll(&repos, &mut seen);
+    assert_eq!(
+        switches,
+        vec![(dir.clone(), "feature/PROJ-2-export".to_string(), "PROJ-2".to_string())]
+    );
+    assert!(poll(&repos, &mut seen).is_empty());
+
+    // Same issue on another branch, or no issue at all
+    checkout(&dir, "ref: refs/heads/fix/PROJ-2-tests");
+    assert!(poll(&repos, &mut seen).is_empty());
+    checkout(&dir, "ref: refs/heads/main");
+    assert!(poll(&repos, &mut seen).is_empty());
+
+    checkout(&dir, "ref: refs/heads/fix/PROJ-2-tests");
+    assert_eq!(poll(&repos, &mut seen).len(), 1);
+    let _ = fs::remove_dir_all(&dir);
+}
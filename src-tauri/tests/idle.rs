@@ -0,0 +1,225 @@
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use jira_timesheet::idle::{
+    record_idle, resolve_idle, IdlePeriod, IdleResolution, IdleSample, IdleSource, IdleTracker,
+};
+use jira_timesheet::timer::{Timer, TimerSegment};
+
+const THRESHOLD: u64 = 300;
+
+fn at(minutes: i64) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, 3, 4, 9, 0, 0).unwrap() + Duration::minutes(minutes)
+}
+
+fn running_timer(id: u32, started_minutes: i64) -> Timer {
+    Timer::new(
+        id,
+        format!("PROJ-{}", id),
+        "Some ticket".to_string(),
+        at(started_minutes),
+    )
+}
+
+fn segment(start: i64, end: Option<i64>) -> TimerSegment {
+    TimerSegment {
+        start: at(start),
+        end: end.map(at),
+    }
+}
+
+fn sample(idle_minutes: u64, locked: bool) -> IdleSample {
+    IdleSample {
+        idle_seconds: idle_minutes * 60,
+        locked,
+    }
+}
+
+/// Replays a fixed list of samples, one per poll.
+struct FakeSource(Vec<Option<IdleSample>>);
+
+impl IdleSource for FakeSource {
+    fn sample(&mut self) -> Option<IdleSample> {
+        if self.0.is_empty() {
+            None
+        } else {
+            self.0.remove(0)
+        }
+    }
+}
+
+fn run(source: &mut impl IdleSource, polls: &[i64]) -> Vec<IdlePeriod> {
+    let mut tracker = IdleTracker::default();
+    polls
+        .iter()
+        .filter_map(|&minute| {
+            let sample = source.sample()?;
+            tracker.observe(at(minute), sample, THRESHOLD)
+        })
+        .collect()
+}
+
+#[test]
+fn test_tracker_reports_idle_period_when_user_returns() {
+    let mut source = FakeSource(vec![
+        Some(sample(1, false)),
+        Some(sample(6, false)),
+        Some(sample(20, false)),
+        Some(sample(0, false)),
+    ]);
+
+    let periods = run(&mut source, &[1, 16, 30, 31]);
+
+    assert_eq!(
+        periods,
+        vec![IdlePeriod {
+            started_at: at(10),
+            ended_at: at(31),
+        }]
+    );
+}
+
+#[test]
+fn test_tracker_ignores_short_breaks_and_missing_samples() {
+    let mut source = FakeSource(vec![
+        Some(sample(4, false)),
+        None,
+        Some(sample(0, false)),
+        Some(sample(0, true)),
+        Some(sample(0, false)),
+    ]);
+
+    assert!(run(&mut source, &[4, 5, 6, 10, 12]).is_empty());
+}
+
+#[test]
+fn test_tracker_counts_locked_session_from_first_lock_sample() {
+    // logind reports the lock while the desktop's idle counter may be reset
+    let mut source = FakeSource(vec![
+        Some(sample(0, true)),
+        Some(sample(0, true)),
+        Some(sample(0, true)),
+        Some(sample(0, false)),
+    ]);
+
+    let periods = run(&mut source, &[10, 20, 40, 41]);
+
+    assert_eq!(
+        periods,
+        vec![IdlePeriod {
+            started_at: at(10),
+            ended_at: at(41),
+        }]
+    );
+}
+
+#[test]
+fn test_record_idle_counts_running_segments_only() {
+    let mut late = running_timer(2, 20);
+    late.segments = vec![segment(20, Some(22)), segment(25, None)];
+    let mut paused_during = running_timer(3, 0);
+    paused_during.segments = vec![segment(0, Some(15))];
+    let mut paused_before = running_timer(4, 0);
+    paused_before.segments = vec![segment(0, Some(5))];
+    let mut timers = vec![running_timer(1, 0), late, paused_during, paused_before];
+
+    let period = IdlePeriod {
+        started_at: at(10),
+        ended_at: at(40),
+    };
+    assert!(record_idle(&mut timers, 7, period));
+
+    assert_eq!(timers[0].idle_spans[0].seconds, 30 * 60);
+    assert_eq!(timers[0].idle_spans[0].id, 7);
+    // Only the parts of the period the timer was running count
+    assert_eq!(timers[1].idle_spans[0].seconds, 17 * 60);
+    assert_eq!(timers[2].idle_spans[0].seconds, 5 * 60);
+    assert!(timers[3].idle_spans.is_empty());
+
+    let mut only_paused = vec![timers.remove(3)];
+    assert!(!record_idle(&mut only_paused, 8, period));
+}
+
+fn timers_with_idle() -> Vec<Timer> {
+    let mut timers = vec![running_timer(1, 0), running_timer(2, 45)];
+    let period = IdlePeriod {
+        started_at: at(10),
+        ended_at: at(40),
+    };
+    record_idle(&mut timers, 7, period);
+    timers
+}
+
+#[test]
+fn test_resolve_idle_keep_leaves_time() {
+    let mut timers = timers_with_idle();
+
+    resolve_idle(&mut timers, 7, &IdleResolution::Keep, at(60)).unwrap();
+
+    assert_eq!(timers[0].elapsed_at(at(60)), 60 * 60);
+    assert!(timers[0].adjustments.is_empty());
+    assert_eq!(
+        timers[0].idle_spans[0].resolution,
+        Some(IdleResolution::Keep)
+    );
+}
+
+#[test]
+fn test_resolve_idle_discard_removes_time_with_adjustment() {
+    let mut timers = timers_with_idle();
+
+    resolve_idle(&mut timers, 7, &IdleResolution::Discard, at(60)).unwrap();
+
+    assert_eq!(timers[0].elapsed_at(at(60)), 30 * 60);
+    assert_eq!(timers[0].elapsed_at(at(61)), 31 * 60);
+    assert_eq!(timers[0].adjustments[0].before_seconds, 60 * 60);
+    assert_eq!(timers[0].adjustments[0].after_seconds, 30 * 60);
+    // Timer 2 started after the period and is untouched
+    assert_eq!(timers[1].elapsed_at(at(60)), 15 * 60);
+
+    let err = resolve_idle(&mut timers, 7, &IdleResolution::Discard, at(61)).unwrap_err();
+    assert_eq!(err, "Idle period not found");
+}
+
+#[test]
+fn test_resolve_idle_reassign_moves_time_to_target() {
+    let mut timers = timers_with_idle();
+
+    resolve_idle(
+        &mut timers,
+        7,
+        &IdleResolution::Reassign { timer_id: 2 },
+        at(60),
+    )
+    .unwrap();
+
+    assert_eq!(timers[0].elapsed_at(at(60)), 30 * 60);
+    assert_eq!(timers[1].elapsed_at(at(60)), 45 * 60);
+    assert_eq!(timers[1].adjustments.len(), 1);
+}
+
+#[test]
+fn test_resolve_idle_reassign_to_unknown_timer_changes_nothing() {
+    let mut timers = timers_with_idle();
+
+    let err = resolve_idle(
+        &mut timers,
+        7,
+        &IdleResolution::Reassign { timer_id: 99 },
+        at(60),
+    )
+    .unwrap_err();
+
+    assert_eq!(err, "Timer not found");
+    assert!(timers[0].idle_spans[0].resolution.is_none());
+    assert_eq!(timers[0].elapsed_at(at(60)), 60 * 60);
+}
+
+#[test]
+fn test_idle_resolution_serde_shape() {
+    let resolution: IdleResolution =
+        serde_json::from_str(r#"{"action":"reassign","timer_id":3}"#).unwrap();
+    assert_eq!(resolution, IdleResolution::Reassign { timer_id: 3 });
+    assert_eq!(
+        serde_json::to_string(&IdleResolution::Discard).unwrap(),
+        r#"{"action":"discard"}"#
+    );
+}
@@ -0,0 +1,647 @@
+//! Integration tests for `JiraClient` against a local stub of the Jira Cloud REST API.
+//!
+//! Responses are recorded fixtures under `tests/fixtures`, so the suite runs offline.
+
+use chrono::{Local, TimeZone, Utc};
+use jira_timesheet::jira::JiraClient;
+use serde_json::json;
+use wiremock::matchers::{body_json, header, method, path, query_param};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const EMAIL: &str = "alex@example.com";
+const TOKEN: &str = "secret";
+// base64("alex@example.com:secret")
+const AUTH: &str = "Basic YWxleEBleGFtcGxlLmNvbTpzZWNyZXQ=";
+
+macro_rules! fixture {
+    ($name:literal) => {
+        serde_json::from_str::<serde_json::Value>(include_str!(concat!("fixtures/", $name)))
+            .expect("invalid fixture")
+    };
+}
+
+async fn setup() -> (MockServer, JiraClient) {
+    let server = MockServer::start().await;
+    // Trailing slash must be tolerated, as users paste it from the browser.
+    let base_url = format!("{}/", server.uri());
+    let client = JiraClient::with_http_client(reqwest::Client::new(), &base_url, EMAIL, TOKEN);
+    (server, client)
+}
+
+#[tokio::test]
+async fn list_projects_sends_basic_auth_and_parses_values() {
+    let (server, client) = setup().await;
+    Mock::given(method("GET"))
+        .and(path("/rest/api/3/project/search"))
+        .and(query_param("maxResults", "50"))
+        .and(header("authorization", AUTH))
+        .respond_with(ResponseTemplate::new(200).set_body_json(fixture!("project_search.json")))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let projects = client.list_projects().await.unwrap();
+
+    let keys: Vec<_> = projects.iter().map(|p| (p.key.as_str(), p.name.as_str())).collect();
+    assert_eq!(keys, vec![("PROJ", "Project Alpha"), ("OPS", "Operations")]);
+}
+
+#[tokio::test]
+async fn search_project_tickets_builds_jql_and_reads_time_tracking() {
+    let (server, client) = setup().await;
+    Mock::given(method("GET"))
+        .and(path("/rest/api/3/search/jql"))
+        .and(query_param(
+            "jql",
+            "project=PROJ AND assignee=currentUser() AND status!=Done",
+        ))
+        .and(query_param("fields", "summary,status,timetracking"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(fixture!("search_jql.json")))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let tickets = client.search_project_tickets("PROJ").await.unwrap();
+
+    assert_eq!(tickets.len(), 2);
+    assert_eq!(tickets[0].key, "PROJ-42");
+    assert_eq!(tickets[0].status, "In Progress");
+    assert_eq!(tickets[0].time_spent_seconds, 14400);
+    assert_eq!(tickets[1].key, "PROJ-43");
+    assert_eq!(tickets[1].time_spent_seconds, 0);
+}
+
+#[tokio::test]
+async fn get_transitions_lists_id_and_name() {
+    let (server, client) = setup().await;
+    Mock::given(method("GET"))
+        .and(path("/rest/api/3/issue/PROJ-42/transitions"))
+        .and(query_param("expand", "transitions.fields"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(fixture!("transitions.json")))
+        .mount(&server)
+        .await;
+
+    let transitions = client.get_transitions("PROJ-42").await.unwrap();
+
+    let names: Vec<_> = transitions.iter().map(|t| (t.id.as_str(), t.name.as_str())).collect();
+    assert_eq!(names, vec![("11", "To Do"), ("31", "Done")]);
+    assert!(!transitions[0].has_screen);
+    assert!(transitions[0].fields.is_empty());
+}
+
+#[tokio::test]
+async fn get_transitions_returns_screen_field_metadata() {
+    let (server, client) = setup().await;
+    Mock::given(method("GET"))
+        .and(path("/rest/api/3/issue/PROJ-42/transitions"))
+        .and(query_param("expand", "transitions.fields"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(fixture!("transitions.json")))
+        .mount(&server)
+        .await;
+
+    let transitions = client.get_transitions("PROJ-42").await.unwrap();
+    let done = &transitions[1];
+
+    assert!(done.has_screen);
+    let fields: Vec<_> = done
+        .fields
+        .iter()
+        .map(|f| (f.key.as_str(), f.required, f.field_type.as_str(), f.items_type.as_deref()))
+        .collect();
+    assert_eq!(
+        fields,
+        vec![
+            ("resolution", true, "resolution", None),
+            ("customfield_10060", true, "number", None),
+            ("customfield_10050", false, "array", Some("option")),
+        ]
+    );
+
+    let resolutions: Vec<_> = done.fields[0]
+        .allowed_values
+        .iter()
+        .map(|v| (v.id.as_str(), v.name.as_str()))
+        .collect();
+    assert_eq!(resolutions, vec![("10000", "Done"), ("10001", "Won't Do")]);
+    // Custom field options expose `value` instead of `name`
+    assert_eq!(done.fields[2].allowed_values[1].name, "production");
+    assert!(done.fields[1].allowed_values.is_empty());
+}
+
+#[tokio::test]
+async fn transition_issue_posts_transition_id() {
+    let (server, client) = setup().await;
+    Mock::given(method("POST"))
+        .and(path("/rest/api/3/issue/PROJ-42/transitions"))
+        .and(body_json(json!({ "transition": { "id": "31" } })))
+        .respond_with(ResponseTemplate::new(204))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    client.transition_issue("PROJ-42", "31", None, None).await.unwrap();
+}
+
+#[tokio::test]
+async fn transition_issue_sends_fields_and_comment() {
+    let (server, client) = setup().await;
+    Mock::given(method("POST"))
+        .and(path("/rest/api/3/issue/PROJ-42/transitions"))
+        .and(body_json(json!({
+            "transition": { "id": "31" },
+            "fields": {
+                "resolution": { "id": "10000" },
+                "customfield_10060": 3
+            },
+            "update": {
+                "comment": [{
+                    "add": {
+                        "body": {
+                            "type": "doc",
+                            "version": 1,
+                            "content": [{
+                                "type": "paragraph",
+                                "content": [{ "type": "text", "text": "Shipped in 2.4" }]
+                            }]
+                        }
+                    }
+                }]
+            }
+        })))
+        .respond_with(ResponseTemplate::new(204))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let fields = json!({ "resolution": { "id": "10000" }, "customfield_10060": 3 });
+    client
+        .transition_issue("PROJ-42", "31", fields.as_object(), Some("Shipped in 2.4"))
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn transition_errors_list_missing_fields() {
+    let (server, client) = setup().await;
+    Mock::given(method("POST"))
+        .and(path("/rest/api/3/issue/PROJ-42/transitions"))
+        .respond_with(ResponseTemplate::new(400).set_body_json(json!({
+            "errorMessages": [],
+            "errors": {
+                "resolution": "Resolution is required.",
+                "customfield_10060": "Story points is required."
+            }
+        })))
+        .mount(&server)
+        .await;
+
+    let err = client.transition_issue("PROJ-42", "31", None, None).await.unwrap_err();
+
+    assert_eq!(
+        err,
+        "Transition error 400 Bad Request: customfield_10060: Story points is required.; resolution: Resolution is required."
+    );
+}
+
+#[tokio::test]
+async fn get_issue_detail_flattens_adf_description() {
+    let (server, client) = setup().await;
+    Mock::given(method("GET"))
+        .and(path("/rest/api/3/issue/PROJ-42"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(fixture!("issue_detail.json")))
+        .mount(&server)
+        .await;
+
+    let detail = client.get_issue_detail("PROJ-42").await.unwrap();
+
+    assert_eq!(detail.key, "PROJ-42");
+    assert_eq!(detail.summary, "Fix login redirect");
+    assert_eq!(detail.priority, "High");
+    assert_eq!(detail.assignee, "Alex Martin");
+    assert_eq!(detail.reporter, "Sam Leroy");
+    assert_eq!(detail.issue_type, "Bug");
+    assert_eq!(detail.labels, vec!["frontend", "auth"]);
+    assert_eq!(detail.time_estimate_seconds, 28800);
+    assert_eq!(detail.time_remaining_seconds, 14400);
+    assert_eq!(
+        detail.description,
+        "After login the user lands on /home instead of the original page.\nSteps\n- Open a deep link\n- Sign in"
+    );
+    assert_eq!(
+        detail.description_html,
+        "<p>After login the user lands on <code>/home</code> instead of the original page.</p>\
+         <h3>Steps</h3><ul><li><p>Open a deep link</p></li><li><p>Sign in</p></li></ul>"
+    );
+    assert_eq!(
+        detail.description_markdown,
+        "After login the user lands on `/home` instead of the original page.\n\n### Steps\n\n- Open a deep link\n- Sign in"
+    );
+}
+
+#[tokio::test]
+async fn get_issue_detail_tolerates_missing_optional_fields() {
+    let (server, client) = setup().await;
+    Mock::given(method("GET"))
+        .and(path("/rest/api/3/issue/PROJ-1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "key": "PROJ-1",
+            "fields": {
+                "summary": "Bare issue",
+                "status": { "name": "To Do" },
+                "description": null,
+                "priority": null,
+                "assignee": null,
+                "reporter": null,
+                "issuetype": null
+            }
+        })))
+        .mount(&server)
+        .await;
+
+    let detail = client.get_issue_detail("PROJ-1").await.unwrap();
+
+    assert_eq!(detail.description, "");
+    assert_eq!(detail.assignee, "");
+    assert!(detail.labels.is_empty());
+    assert_eq!(detail.time_spent_seconds, 0);
+}
+
+#[tokio::test]
+async fn get_issue_comments_returns_page_with_rendered_bodies() {
+    let (server, client) = setup().await;
+    Mock::given(method("GET"))
+        .and(path("/rest/api/3/issue/PROJ-42/comment"))
+        .and(query_param("orderBy", "-created"))
+        .and(query_param("startAt", "0"))
+        .and(query_param("maxResults", "2"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(fixture!("comments_page1.json")))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let page = client.get_issue_comments("PROJ-42", 0, 2).await.unwrap();
+
+    assert_eq!(page.start_at, 0);
+    assert_eq!(page.total, 3);
+    assert_eq!(page.comments.len(), 2);
+
+    let latest = &page.comments[0];
+    assert_eq!(latest.id, "30003");
+    assert_eq!(latest.author, "Sam Leroy");
+    assert_eq!(latest.created, "2024-01-16T10:20:00.000+0100");
+    assert_eq!(latest.updated, "2024-01-16T10:25:00.000+0100");
+    assert_eq!(latest.body, "Reproduced on staging");
+    assert_eq!(latest.body_html, "<p>Reproduced on <strong>staging</strong></p>");
+    let visibility = latest.visibility.as_ref().unwrap();
+    assert_eq!((visibility.kind.as_str(), visibility.value.as_str()), ("role", "Developers"));
+
+    assert!(page.comments[1].visibility.is_none());
+}
+
+#[tokio::test]
+async fn get_my_worklogs_paginates_and_filters_by_author_and_date() {
+    let (server, client) = setup().await;
+    Mock::given(method("GET"))
+        .and(path("/rest/api/3/myself"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(fixture!("myself.json")))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/rest/api/3/search"))
+        .and(query_param(
+            "jql",
+            "worklogAuthor=currentUser() AND worklogDate >= \"2024-01-15\" AND worklogDate <= \"2024-01-16\"",
+        ))
+        .and(query_param("startAt", "0"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(fixture!("worklog_search_page1.json")))
+        .expect(1)
+        .mount(&server)
+        .await;
+    // The server capped the first page at 2 issues, so the next page starts at 2.
+    Mock::given(method("GET"))
+        .and(path("/rest/api/3/search"))
+        .and(query_param("startAt", "2"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(fixture!("worklog_search_page2.json")))
+        .expect(1)
+        .mount(&server)
+        .await;
+    // PROJ-43 embeds fewer worklogs than it has, so they are fetched separately.
+    Mock::given(method("GET"))
+        .and(path("/rest/api/3/issue/PROJ-43/worklog"))
+        .and(query_param("startedAfter", "1705276800000"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(fixture!("issue_worklogs.json")))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let entries = client.get_my_worklogs("2024-01-15", "2024-01-16").await.unwrap();
+
+    let got: Vec<_> = entries
+        .iter()
+        .map(|e| (e.date.as_str(), e.issue_key.as_str(), e.time_spent_seconds))
+        .collect();
+    assert_eq!(
+        got,
+        vec![
+            ("2024-01-15", "PROJ-42", 5400),
+            ("2024-01-16", "OPS-7", 2700),
+            ("2024-01-16", "PROJ-43", 1800),
+        ]
+    );
+    assert_eq!(entries[1].summary, "Rotate certificates");
+    assert_eq!(
+        entries[0].started,
+        Some(Utc.with_ymd_and_hms(2024, 1, 15, 8, 0, 0).unwrap())
+    );
+}
+
+#[tokio::test]
+async fn get_my_worklogs_keeps_paginating_without_total() {
+    let (server, client) = setup().await;
+    Mock::given(method("GET"))
+        .and(path("/rest/api/3/myself"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(fixture!("myself.json")))
+        .mount(&server)
+        .await;
+    let issue = |i: u32| {
+        json!({
+            "key": format!("PROJ-{}", i),
+            "fields": { "summary": "Task", "worklog": { "total": 0, "maxResults": 20, "worklogs": [] } }
+        })
+    };
+    // A full page and no total: the next page must still be requested.
+    Mock::given(method("GET"))
+        .and(path("/rest/api/3/search"))
+        .and(query_param("startAt", "0"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(json!({ "issues": (0..100).map(issue).collect::<Vec<_>>() })),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/rest/api/3/search"))
+        .and(query_param("startAt", "100"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "issues": [issue(100)] })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let entries = client.get_my_worklogs("2024-01-15", "2024-01-16").await.unwrap();
+
+    assert!(entries.is_empty());
+}
+
+#[tokio::test]
+async fn get_my_worklogs_stops_at_the_reported_total() {
+    let (server, client) = setup().await;
+    Mock::given(method("GET"))
+        .and(path("/rest/api/3/myself"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(fixture!("myself.json")))
+        .mount(&server)
+        .await;
+    let issue = |i: u32| {
+        json!({
+            "key": format!("PROJ-{}", i),
+            "fields": { "summary": "Task", "worklog": { "total": 0, "maxResults": 20, "worklogs": [] } }
+        })
+    };
+    // Two capped pages, then the total is reached: no third request.
+    Mock::given(method("GET"))
+        .and(path("/rest/api/3/search"))
+        .and(query_param("startAt", "0"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "total": 4, "issues": [issue(0), issue(1)] })))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/rest/api/3/search"))
+        .and(query_param("startAt", "2"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "total": 4, "issues": [issue(2), issue(3)] })))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/rest/api/3/search"))
+        .and(query_param("startAt", "4"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "total": 4, "issues": [] })))
+        .expect(0)
+        .mount(&server)
+        .await;
+
+    let entries = client.get_my_worklogs("2024-01-15", "2024-01-16").await.unwrap();
+
+    assert!(entries.is_empty());
+}
+
+#[tokio::test]
+async fn get_my_worklogs_rejects_invalid_dates() {
+    let (server, client) = setup().await;
+    Mock::given(method("GET"))
+        .and(path("/rest/api/3/myself"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(fixture!("myself.json")))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/rest/api/3/search"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "total": 0, "issues": [] })))
+        .mount(&server)
+        .await;
+
+    let err = client.get_my_worklogs("15/01/2024", "2024-01-16").await.unwrap_err();
+
+    assert!(err.starts_with("Invalid start_date"), "{}", err);
+}
+
+#[tokio::test]
+async fn get_report_worklogs_reads_grouping_fields() {
+    let (server, client) = setup().await;
+    Mock::given(method("GET"))
+        .and(path("/rest/api/3/myself"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(fixture!("myself.json")))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/rest/api/3/search"))
+        .and(query_param(
+            "fields",
+            "summary,worklog,project,issuetype,parent,components,labels",
+        ))
+        .respond_with(ResponseTemplate::new(200).set_body_json(fixture!("report_search.json")))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let worklogs = client.get_report_worklogs("2024-02-01", "2024-02-29").await.unwrap();
+
+    assert_eq!(worklogs.len(), 2);
+    let bug = &worklogs[0];
+    assert_eq!((bug.issue_key.as_str(), bug.seconds), ("PROJ-42", 7200));
+    assert_eq!((bug.project_key.as_str(), bug.project_name.as_str()), ("PROJ", "Customer Portal"));
+    assert_eq!(bug.issue_type, "Bug");
+    assert_eq!(
+        bug.parent,
+        Some(("PROJ-40".to_string(), "Single sign-on".to_string()))
+    );
+    assert_eq!(bug.components, vec!["Auth", "Web"]);
+    assert_eq!(bug.labels, vec!["client-acme"]);
+    assert!(worklogs[1].parent.is_none());
+    assert!(worklogs[1].components.is_empty());
+}
+
+#[tokio::test]
+async fn log_worklog_posts_time_spent() {
+    let (server, client) = setup().await;
+    Mock::given(method("POST"))
+        .and(path("/rest/api/3/issue/PROJ-42/worklog"))
+        .and(header("content-type", "application/json"))
+        .and(body_json(json!({ "timeSpentSeconds": 1500 })))
+        .respond_with(ResponseTemplate::new(201).set_body_json(fixture!("worklog_created.json")))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    client.log_worklog("PROJ-42", 1500, None, None).await.unwrap();
+}
+
+#[tokio::test]
+async fn log_worklog_sends_started_in_local_time() {
+    let (server, client) = setup().await;
+    let started = Utc.with_ymd_and_hms(2024, 3, 4, 8, 30, 0).unwrap();
+    let expected = started
+        .with_timezone(&Local)
+        .format("%Y-%m-%dT%H:%M:%S.000%z")
+        .to_string();
+    Mock::given(method("POST"))
+        .and(path("/rest/api/3/issue/PROJ-42/worklog"))
+        .and(body_json(json!({ "timeSpentSeconds": 900, "started": expected })))
+        .respond_with(ResponseTemplate::new(201).set_body_json(fixture!("worklog_created.json")))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    client
+        .log_worklog("PROJ-42", 900, Some(started), None)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn log_worklog_converts_comment_to_adf() {
+    let (server, client) = setup().await;
+    Mock::given(method("POST"))
+        .and(path("/rest/api/3/issue/PROJ-42/worklog"))
+        .and(body_json(json!({
+            "timeSpentSeconds": 1500,
+            "comment": {
+                "type": "doc",
+                "version": 1,
+                "content": [{
+                    "type": "paragraph",
+                    "content": [
+                        { "type": "text", "text": "Fixed " },
+                        { "type": "text", "text": "redirect", "marks": [{ "type": "code" }] }
+                    ]
+                }]
+            }
+        })))
+        .respond_with(ResponseTemplate::new(201).set_body_json(fixture!("worklog_created.json")))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    client
+        .log_worklog("PROJ-42", 1500, None, Some("Fixed `redirect`"))
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn add_comment_posts_adf_body() {
+    let (server, client) = setup().await;
+    Mock::given(method("POST"))
+        .and(path("/rest/api/3/issue/PROJ-42/comment"))
+        .and(body_json(json!({
+            "body": {
+                "type": "doc",
+                "version": 1,
+                "content": [{
+                    "type": "paragraph",
+                    "content": [
+                        { "type": "text", "text": "Done, thanks " },
+                        { "type": "mention", "attrs": { "id": "5b10a2844c20165700ede21g", "text": "@Sam" } }
+                    ]
+                }]
+            }
+        })))
+        .respond_with(ResponseTemplate::new(201).set_body_json(json!({ "id": "10100" })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    client
+        .add_comment("PROJ-42", "Done, thanks [@Sam](accountid:5b10a2844c20165700ede21g)")
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn add_comment_rejects_blank_markdown() {
+    let (_server, client) = setup().await;
+
+    let err = client.add_comment("PROJ-42", "  \n").await.unwrap_err();
+
+    assert_eq!(err, "Comment is empty");
+}
+
+#[tokio::test]
+async fn api_errors_include_status_and_body() {
+    let (server, client) = setup().await;
+    Mock::given(method("GET"))
+        .and(path("/rest/api/3/issue/NOPE-1"))
+        .respond_with(ResponseTemplate::new(404).set_body_json(fixture!("error_not_found.json")))
+        .mount(&server)
+        .await;
+
+    let err = client.get_issue_detail("NOPE-1").await.unwrap_err();
+
+    assert!(err.starts_with("Jira API error 404 Not Found: "), "{}", err);
+    assert!(err.contains("Issue does not exist"), "{}", err);
+}
+
+#[tokio::test]
+async fn worklog_and_transition_errors_are_labelled() {
+    let (server, client) = setup().await;
+    Mock::given(method("POST"))
+        .and(path("/rest/api/3/issue/PROJ-42/worklog"))
+        .respond_with(ResponseTemplate::new(400).set_body_json(fixture!("error_worklog.json")))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/rest/api/3/issue/PROJ-42/transitions"))
+        .respond_with(ResponseTemplate::new(400).set_body_string("Transition 99 is not valid"))
+        .mount(&server)
+        .await;
+
+    let err = client.log_worklog("PROJ-42", 60, None, None).await.unwrap_err();
+    assert!(err.starts_with("Worklog error 400 Bad Request: "), "{}", err);
+    assert!(err.contains("timeLogged"), "{}", err);
+
+    let err = client.transition_issue("PROJ-42", "99", None, None).await.unwrap_err();
+    assert_eq!(err, "Transition error 400 Bad Request: Transition 99 is not valid");
+}
+
+#[tokio::test]
+async fn unparseable_bodies_are_reported() {
+    let (server, client) = setup().await;
+    Mock::given(method("GET"))
+        .and(path("/rest/api/3/issue/PROJ-42/transitions"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("<html>maintenance</html>"))
+        .mount(&server)
+        .await;
+
+    let err = client.get_transitions("PROJ-42").await.unwrap_err();
+
+    assert!(err.starts_with("Parse error: "), "{}", err);
+}
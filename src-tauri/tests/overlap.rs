@@ -0,0 +1,98 @@
+use chrono::{DateTime, Duration, Local, TimeZone, Utc};
+use jira_timesheet::jira::TimesheetEntry;
+use jira_timesheet::overlap::{days_to_fetch, find_conflicts, ConflictKind, ConflictWith};
+use jira_timesheet::worklog::WorklogRequest;
+
+fn at(minutes: i64) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, 3, 4, 9, 0, 0).unwrap() + Duration::minutes(minutes)
+}
+
+fn request(issue_key: &str, seconds: u64, started: DateTime<Utc>) -> WorklogRequest {
+    WorklogRequest {
+        issue_key: issue_key.to_string(),
+        seconds,
+        started,
+        comment: None,
+    }
+}
+
+fn logged(issue_key: &str, seconds: u64, started: Option<DateTime<Utc>>) -> TimesheetEntry {
+    TimesheetEntry {
+        issue_key: issue_key.to_string(),
+        summary: "Fix login".to_string(),
+        date: "2024-03-04".to_string(),
+        time_spent_seconds: seconds,
+        started,
+    }
+}
+
+#[test]
+fn test_duplicate_and_overlap_with_jira_worklogs() {
+    let worklogs = [request("PROJ-1", 3600, at(0)), request("PROJ-2", 1800, at(120))];
+    let existing = [
+        // Same worklog, Jira dropped the seconds
+        logged("PROJ-1", 3600, Some(at(0) + Duration::seconds(20))),
+        logged("PROJ-3", 1800, Some(at(45))),
+        // Ends right when PROJ-2 starts
+        logged("PROJ-3", 1800, Some(at(90))),
+        logged("PROJ-4", 7200, None),
+    ];
+
+    let conflicts = find_conflicts(&worklogs, &existing, &[]);
+
+    assert_eq!(conflicts.len(), 2);
+    assert_eq!(conflicts[0].index, 0);
+    assert_eq!(conflicts[0].kind, ConflictKind::Duplicate);
+    assert_eq!(
+        conflicts[0].with,
+        ConflictWith::Logged {
+            summary: "Fix login".to_string()
+        }
+    );
+    assert_eq!(conflicts[0].overlap_seconds, 3580);
+    assert_eq!(conflicts[1].kind, ConflictKind::Overlap);
+    assert_eq!(conflicts[1].issue_key, "PROJ-3");
+    assert_eq!(conflicts[1].overlap_seconds, 15 * 60);
+}
+
+#[test]
+fn test_conflicts_between_checked_and_pending_worklogs() {
+    let worklogs = [
+        request("PROJ-1", 3600, at(0)),
+        request("PROJ-2", 1800, at(30)),
+        request("PROJ-1", 3600, at(0)),
+    ];
+    let pending = [request("PROJ-5", 600, at(55))];
+
+    let conflicts = find_conflicts(&worklogs, &[], &pending);
+
+    let got: Vec<_> = conflicts
+        .iter()
+        .map(|c| (c.index, c.kind, c.with.clone(), c.overlap_seconds))
+        .collect();
+    assert_eq!(
+        got,
+        vec![
+            (0, ConflictKind::Overlap, ConflictWith::Pending, 300),
+            (1, ConflictKind::Overlap, ConflictWith::Checked { other_index: 0 }, 1800),
+            (1, ConflictKind::Overlap, ConflictWith::Pending, 300),
+            (2, ConflictKind::Duplicate, ConflictWith::Checked { other_index: 0 }, 3600),
+            (2, ConflictKind::Overlap, ConflictWith::Checked { other_index: 1 }, 1800),
+            (2, ConflictKind::Overlap, ConflictWith::Pending, 300),
+        ]
+    );
+
+    let json = serde_json::to_value(&conflicts[1]).unwrap();
+    assert_eq!(json["source"], "checked");
+    assert_eq!(json["other_index"], 0);
+    assert_eq!(json["kind"], "overlap");
+}
+
+#[test]
+fn test_days_to_fetch_spans_a_day_on_each_side() {
+    assert_eq!(days_to_fetch(&[]), None);
+
+    let (from, to) = days_to_fetch(&[request("PROJ-1", 3600, at(0)), request("PROJ-2", 600, at(24 * 60))]).unwrap();
+    assert_eq!(Some(from), at(0).with_timezone(&Local).date_naive().pred_opt());
+    assert_eq!((to - from).num_days(), 3);
+}
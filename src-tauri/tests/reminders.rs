@@ -0,0 +1,89 @@
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveTime, TimeZone, Utc, Weekday};
+use jira_timesheet::reminders::{daily_message, long_running_timers, ReminderConfig};
+use jira_timesheet::timer::{Timer, TimerSegment};
+
+fn local(day: u32, hour: u32, minute: u32) -> DateTime<Local> {
+    // March 2024: the 4th is a Monday, the 9th a Saturday
+    Local
+        .with_ymd_and_hms(2024, 3, day, hour, minute, 0)
+        .earliest()
+        .unwrap()
+}
+
+#[test]
+fn test_daily_reminder_due_after_end_of_day_on_working_days_once() {
+    let config = ReminderConfig::default();
+    let monday = NaiveDate::from_ymd_opt(2024, 3, 4).unwrap();
+
+    assert!(!config.daily_reminder_due(local(4, 17, 29), None));
+    assert!(config.daily_reminder_due(local(4, 17, 30), None));
+    assert!(config.daily_reminder_due(local(4, 18, 0), monday.pred_opt()));
+    assert!(!config.daily_reminder_due(local(4, 18, 0), Some(monday)));
+    // Saturday
+    assert!(!config.daily_reminder_due(local(9, 18, 0), None));
+}
+
+#[test]
+fn test_daily_reminder_disabled_or_custom_days() {
+    let config = ReminderConfig {
+        daily_target_hours: 0.0,
+        ..ReminderConfig::default()
+    };
+    assert!(!config.daily_reminder_due(local(4, 18, 0), None));
+
+    let config = ReminderConfig {
+        end_of_day: NaiveTime::from_hms_opt(12, 0, 0).unwrap(),
+        working_days: vec![Weekday::Sat],
+        ..ReminderConfig::default()
+    };
+    assert!(config.daily_reminder_due(local(9, 12, 0), None));
+    assert!(!config.daily_reminder_due(local(4, 18, 0), None));
+}
+
+#[test]
+fn test_daily_message_formats_hours() {
+    assert_eq!(
+        daily_message(5 * 3600 + 12 * 60 + 40, 7.0),
+        "You've logged 5h12 of 7h today"
+    );
+    assert_eq!(daily_message(0, 7.5), "You've logged 0h of 7h30 today");
+}
+
+#[test]
+fn test_reminder_config_serde_shape() {
+    let config: ReminderConfig = serde_json::from_str(
+        r#"{"daily_target_hours": 6, "end_of_day": "16:45", "working_days": ["Mon", "Tue"]}"#,
+    )
+    .unwrap();
+
+    assert_eq!(config.end_of_day, NaiveTime::from_hms_opt(16, 45, 0).unwrap());
+    assert_eq!(config.working_days, vec![Weekday::Mon, Weekday::Tue]);
+    assert_eq!(config.long_timer_hours, 3);
+}
+
+#[test]
+fn test_long_running_timers_uses_current_segment() {
+    let start = Utc.with_ymd_and_hms(2024, 3, 4, 8, 0, 0).unwrap();
+    let hours = |h: i64| start + Duration::hours(h);
+
+    let long = Timer::new(1, "PROJ-1".to_string(), "Long".to_string(), start);
+    let mut resumed = Timer::new(2, "PROJ-2".to_string(), "Resumed".to_string(), start);
+    resumed.segments = vec![
+        TimerSegment {
+            start,
+            end: Some(hours(2)),
+        },
+        TimerSegment {
+            start: hours(2),
+            end: None,
+        },
+    ];
+    let mut paused = Timer::new(3, "PROJ-3".to_string(), "Paused".to_string(), start);
+    paused.segments[0].end = Some(hours(4));
+    let timers = [long, resumed, paused];
+
+    let runs = long_running_timers(&timers, 3, hours(4));
+    assert_eq!(runs, vec![(1, start, "PROJ-1".to_string())]);
+
+    assert!(long_running_timers(&timers, 0, hours(4)).is_empty());
+}
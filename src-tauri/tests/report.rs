@@ -0,0 +1,78 @@
+use jira_timesheet::jira::ReportWorklog;
+use jira_timesheet::report::{aggregate, build, Dimension, NONE_LABEL};
+
+fn worklog(issue_key: &str, seconds: u64, project: &str, components: &[&str]) -> ReportWorklog {
+    ReportWorklog {
+        issue_key: issue_key.to_string(),
+        summary: String::new(),
+        date: "2024-02-05".to_string(),
+        seconds,
+        project_key: project.to_string(),
+        project_name: format!("{} project", project),
+        issue_type: "Task".to_string(),
+        parent: None,
+        components: components.iter().map(|c| c.to_string()).collect(),
+        labels: Vec::new(),
+    }
+}
+
+#[test]
+fn test_aggregate_by_project_with_percentages() {
+    let worklogs = [
+        worklog("PROJ-1", 3600, "PROJ", &[]),
+        worklog("PROJ-2", 1800, "PROJ", &[]),
+        worklog("PROJ-1", 1800, "PROJ", &[]),
+        worklog("OPS-7", 2400, "OPS", &[]),
+    ];
+
+    let rows = aggregate(&worklogs, Dimension::Project);
+
+    let got: Vec<_> = rows
+        .iter()
+        .map(|r| (r.key.as_str(), r.label.as_str(), r.seconds, r.issues))
+        .collect();
+    assert_eq!(
+        got,
+        vec![("PROJ", "PROJ project", 7200, 2), ("OPS", "OPS project", 2400, 1)]
+    );
+    assert_eq!(rows[0].percent, 75.0);
+    assert_eq!(rows[1].percent, 25.0);
+}
+
+#[test]
+fn test_multi_valued_and_missing_fields() {
+    let mut epic = worklog("PROJ-1", 3600, "PROJ", &["Auth", "Web"]);
+    epic.parent = Some(("PROJ-40".to_string(), "Single sign-on".to_string()));
+    let worklogs = [epic, worklog("OPS-7", 3600, "OPS", &[])];
+
+    let components = aggregate(&worklogs, Dimension::Component);
+    let got: Vec<_> = components.iter().map(|r| (r.label.as_str(), r.percent)).collect();
+    // The worklog on two components counts in both
+    assert_eq!(got, vec![(NONE_LABEL, 50.0), ("Auth", 50.0), ("Web", 50.0)]);
+
+    let parents = aggregate(&worklogs, Dimension::Parent);
+    assert_eq!(parents[0].key, "");
+    assert_eq!((parents[1].key.as_str(), parents[1].label.as_str()), ("PROJ-40", "Single sign-on"));
+}
+
+#[test]
+fn test_build_report_totals_and_dimensions() {
+    let worklogs = [
+        worklog("PROJ-1", 3600, "PROJ", &[]),
+        worklog("PROJ-1", 900, "PROJ", &[]),
+        worklog("OPS-7", 900, "OPS", &[]),
+    ];
+
+    let report = build("2024-02-01", "2024-02-29", &worklogs, &[Dimension::IssueType, Dimension::Label]);
+
+    assert_eq!(report.total_seconds, 5400);
+    assert_eq!(report.issues, 2);
+    assert_eq!(report.breakdowns.len(), 2);
+    assert_eq!(report.breakdowns[0].dimension, Dimension::IssueType);
+    assert_eq!(report.breakdowns[0].rows[0].percent, 100.0);
+    assert_eq!(report.breakdowns[1].rows[0].label, NONE_LABEL);
+
+    let json = serde_json::to_value(&report).unwrap();
+    assert_eq!(json["breakdowns"][0]["dimension"], "issue_type");
+    assert!(build("2024-02-01", "2024-02-29", &[], &Dimension::ALL).breakdowns[0].rows.is_empty());
+}
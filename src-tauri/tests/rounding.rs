@@ -0,0 +1,96 @@
+use chrono::{TimeZone, Utc};
+use jira_timesheet::rounding::{parse_projects, RoundingConfig, RoundingMode, RoundingRule};
+use jira_timesheet::worklog::WorklogRequest;
+
+fn rule(mode: RoundingMode, increment_minutes: u32, minimum_minutes: u32) -> RoundingRule {
+    RoundingRule {
+        mode,
+        increment_minutes,
+        minimum_minutes,
+    }
+}
+
+const MIN: u64 = 60;
+
+#[test]
+fn test_round_up_nearest_down_to_quarter_hours() {
+    let up = rule(RoundingMode::Up, 15, 0);
+    let nearest = rule(RoundingMode::Nearest, 15, 0);
+    let down = rule(RoundingMode::Down, 15, 0);
+
+    assert_eq!(up.apply(16 * MIN), 30 * MIN);
+    assert_eq!(up.apply(30 * MIN), 30 * MIN);
+    assert_eq!(up.apply(1), 15 * MIN);
+    assert_eq!(nearest.apply(22 * MIN), 15 * MIN);
+    assert_eq!(nearest.apply(22 * MIN + 30), 30 * MIN);
+    assert_eq!(down.apply(29 * MIN + 59), 15 * MIN);
+    assert_eq!(down.apply(10 * MIN), 0);
+}
+
+#[test]
+fn test_minimum_billable_unit_applies_after_rounding() {
+    let rule = rule(RoundingMode::Nearest, 15, 30);
+
+    assert_eq!(rule.apply(5 * MIN), 30 * MIN);
+    assert_eq!(rule.apply(50 * MIN), 45 * MIN);
+    // Nothing tracked stays nothing
+    assert_eq!(rule.apply(0), 0);
+}
+
+#[test]
+fn test_no_rounding_keeps_raw_time() {
+    let rule = RoundingRule::default();
+
+    assert_eq!(rule.mode, RoundingMode::None);
+    assert_eq!(rule.apply(1234), 1234);
+}
+
+#[test]
+fn test_project_overrides_and_parsing() {
+    let projects = parse_projects("ops=up:15:30, INT=none ,").unwrap();
+    let config = RoundingConfig {
+        default: rule(RoundingMode::Nearest, 6, 0),
+        projects,
+    };
+
+    assert_eq!(config.rule_for("OPS-12"), &rule(RoundingMode::Up, 15, 30));
+    assert_eq!(config.rule_for("ops-12"), &rule(RoundingMode::Up, 15, 30));
+    assert_eq!(config.rule_for("INT-3").mode, RoundingMode::None);
+    assert_eq!(config.rule_for("INT-3").increment_minutes, 15);
+    assert_eq!(config.rule_for("WEB-1"), &rule(RoundingMode::Nearest, 6, 0));
+
+    let started = Utc.with_ymd_and_hms(2024, 3, 4, 9, 0, 0).unwrap();
+    let mut requests = vec![
+        WorklogRequest {
+            issue_key: "OPS-12".to_string(),
+            seconds: 10 * MIN,
+            started,
+            comment: None,
+        },
+        WorklogRequest {
+            issue_key: "WEB-1".to_string(),
+            seconds: 10 * MIN,
+            started,
+            comment: None,
+        },
+    ];
+    config.apply(&mut requests);
+    assert_eq!(requests[0].seconds, 30 * MIN);
+    assert_eq!(requests[1].seconds, 12 * MIN);
+}
+
+#[test]
+fn test_parse_projects_errors() {
+    assert_eq!(
+        parse_projects("OPS").unwrap_err(),
+        "Expected PROJECT=mode in \"OPS\""
+    );
+    assert_eq!(
+        parse_projects("OPS=sideways").unwrap_err(),
+        "Unknown rounding mode \"sideways\""
+    );
+    assert_eq!(
+        parse_projects("OPS=up:x").unwrap_err(),
+        "Invalid minutes \"x\" in \"up:x\""
+    );
+}
@@ -0,0 +1,88 @@
+use jira_timesheet::shortcuts::{parse_bindings, ShortcutAction, ShortcutConfig};
+use std::collections::BTreeMap;
+
+#[test]
+fn test_default_toggles_the_window() {
+    let config = ShortcutConfig::default();
+
+    assert_eq!(
+        config.bindings.get(&ShortcutAction::ToggleWindow).map(String::as_str),
+        Some("ctrl+shift+t")
+    );
+    assert!(config.check().is_ok());
+}
+
+#[test]
+fn test_parse_bindings() {
+    let bindings = parse_bindings("log_active = Ctrl+Shift+L, quick_search=alt+space, restart_last=,").unwrap();
+
+    assert_eq!(
+        bindings,
+        BTreeMap::from([
+            (ShortcutAction::LogActive, "ctrl+shift+l".to_string()),
+            (ShortcutAction::QuickSearch, "alt+space".to_string()),
+        ])
+    );
+    assert_eq!(
+        parse_bindings("log_active").unwrap_err(),
+        "Expected action=shortcut in \"log_active\""
+    );
+    assert_eq!(
+        parse_bindings("launch=ctrl+x").unwrap_err(),
+        "Unknown shortcut action \"launch\""
+    );
+}
+
+#[test]
+fn test_same_shortcut_for_two_actions_is_rejected() {
+    let config = ShortcutConfig {
+        bindings: BTreeMap::from([
+            (ShortcutAction::ToggleWindow, "ctrl+shift+t".to_string()),
+            (ShortcutAction::PauseResumeAll, "Ctrl + Shift + T".to_string()),
+        ]),
+    };
+
+    assert_eq!(
+        config.check().unwrap_err(),
+        "Ctrl + Shift + T is bound to both toggle_window and pause_resume_all"
+    );
+}
+
+#[test]
+fn test_modifier_order_does_not_hide_a_conflict() {
+    let config = ShortcutConfig {
+        bindings: BTreeMap::from([
+            (ShortcutAction::ToggleWindow, "ctrl+shift+t".to_string()),
+            (ShortcutAction::LogActive, "shift+ctrl+t".to_string()),
+        ]),
+    };
+
+    assert_eq!(
+        config.check().unwrap_err(),
+        "shift+ctrl+t is bound to both toggle_window and log_active"
+    );
+}
+
+#[test]
+fn test_invalid_shortcut_is_rejected() {
+    let config = ShortcutConfig {
+        bindings: BTreeMap::from([(ShortcutAction::QuickSearch, "ctrl++".to_string())]),
+    };
+
+    assert!(config
+        .check()
+        .unwrap_err()
+        .starts_with("Invalid shortcut \"ctrl++\" for quick_search"));
+}
+
+#[test]
+fn test_shortcut_config_serde_shape() {
+    let config: ShortcutConfig = serde_json::from_str(
+        r#"{"bindings": {"pause_resume_all": "ctrl+shift+p", "log_active": "ctrl+shift+l"}}"#,
+    )
+    .unwrap();
+
+    assert_eq!(config.bindings.len(), 2);
+    assert_eq!(config.bindings[&ShortcutAction::PauseResumeAll], "ctrl+shift+p");
+    assert_eq!(ShortcutAction::LogActive.name(), "log_active");
+}
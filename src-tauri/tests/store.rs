@@ -0,0 +1,86 @@
+use chrono::{TimeZone, Utc};
+use jira_timesheet::store::{clear, load, save, SavedSession};
+use jira_timesheet::timer::Timer;
+use jira_timesheet::worklog::{WorklogQueue, WorklogRequest};
+use std::path::PathBuf;
+
+fn session_path(name: &str) -> PathBuf {
+    std::env::temp_dir()
+        .join(format!("jira-timesheet-{}-{}", name, std::process::id()))
+        .join("session.json")
+}
+
+#[test]
+fn test_session_round_trip_and_clear() {
+    let path = session_path("round-trip");
+    let started = Utc.with_ymd_and_hms(2024, 3, 4, 9, 0, 0).unwrap();
+    let queue = WorklogQueue::new();
+    queue
+        .push(
+            WorklogRequest {
+                issue_key: "PROJ-2".to_string(),
+                seconds: 600,
+                started,
+                comment: None,
+            },
+            "Worklog error 503",
+        )
+        .unwrap();
+
+    assert!(load(&path).unwrap().is_none());
+
+    let session = SavedSession {
+        timers: vec![Timer::new(3, "PROJ-1".to_string(), "Some ticket".to_string(), started)],
+        pending: queue.list().unwrap(),
+        saved_at: started,
+    };
+    save(&path, &session).unwrap();
+
+    let loaded = load(&path).unwrap().unwrap();
+    assert_eq!(loaded.timers[0].id, 3);
+    assert_eq!(loaded.timers[0].segments, session.timers[0].segments);
+    assert_eq!(loaded.pending[0].request.issue_key, "PROJ-2");
+
+    let restored = WorklogQueue::new();
+    restored.restore(loaded.pending).unwrap();
+    assert_eq!(restored.list().unwrap()[0].error, "Worklog error 503");
+
+    clear(&path).unwrap();
+    assert!(load(&path).unwrap().is_none());
+    // Already gone is fine
+    clear(&path).unwrap();
+}
+
+#[test]
+fn test_saving_an_empty_session_removes_the_file() {
+    let path = session_path("empty");
+    let started = Utc.with_ymd_and_hms(2024, 3, 4, 9, 0, 0).unwrap();
+    let session = SavedSession {
+        timers: vec![Timer::new(1, "PROJ-1".to_string(), "Some ticket".to_string(), started)],
+        pending: Vec::new(),
+        saved_at: started,
+    };
+    save(&path, &session).unwrap();
+    assert!(path.exists());
+
+    save(
+        &path,
+        &SavedSession {
+            timers: Vec::new(),
+            ..session
+        },
+    )
+    .unwrap();
+    assert!(!path.exists());
+}
+
+#[test]
+fn test_invalid_session_file_is_an_error() {
+    let path = session_path("invalid");
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(&path, "{ not json").unwrap();
+
+    let err = load(&path).unwrap_err();
+    assert!(err.starts_with("Invalid session file"), "{}", err);
+    clear(&path).unwrap();
+}
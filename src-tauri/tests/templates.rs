@@ -0,0 +1,110 @@
+use chrono::{Local, NaiveDate, NaiveTime, TimeZone, Utc, Weekday};
+use jira_timesheet::templates::{
+    parse_duration, parse_schedule, parse_schedule_with_duration, ApplyMode, Schedule, Template, TemplateStore,
+};
+
+fn standup() -> Template {
+    Template {
+        name: "Stand-up".to_string(),
+        issue_key: "OPS-12".to_string(),
+        seconds: 900,
+        comment: Some("Daily".to_string()),
+        schedule: Some(parse_schedule("weekdays 09:30").unwrap()),
+        auto: Some(ApplyMode::Draft),
+        last_applied: None,
+    }
+}
+
+fn local(date: NaiveDate, hour: u32, minute: u32) -> chrono::DateTime<Local> {
+    Local
+        .from_local_datetime(&date.and_hms_opt(hour, minute, 0).unwrap())
+        .earliest()
+        .unwrap()
+}
+
+#[test]
+fn test_parse_schedule_and_duration() {
+    let schedule = parse_schedule("Mon, thu 16:00").unwrap();
+    assert_eq!(
+        schedule,
+        Schedule {
+            days: vec![Weekday::Mon, Weekday::Thu],
+            at: NaiveTime::from_hms_opt(16, 0, 0).unwrap(),
+        }
+    );
+    assert_eq!(parse_schedule("daily 08:00").unwrap().days.len(), 7);
+    assert_eq!(parse_schedule("weekdays 09:30").unwrap().days.len(), 5);
+    assert!(parse_schedule("weekdays").is_err());
+    assert!(parse_schedule("someday 09:30").is_err());
+
+    assert_eq!(parse_duration("15 min").unwrap(), 900);
+    assert_eq!(parse_duration("45m").unwrap(), 2700);
+    assert_eq!(parse_duration("1h30").unwrap(), 5400);
+    assert_eq!(parse_duration("2h").unwrap(), 7200);
+    assert!(parse_duration("soon").is_err());
+    assert!(parse_duration("0m").is_err());
+}
+
+#[test]
+fn test_parse_schedule_with_trailing_duration() {
+    let (schedule, seconds) = parse_schedule_with_duration("weekdays 09:30 15 min").unwrap();
+    assert_eq!(schedule, parse_schedule("weekdays 09:30").unwrap());
+    assert_eq!(seconds, Some(900));
+
+    let (schedule, seconds) = parse_schedule_with_duration("mon, thu 16:00 1h30").unwrap();
+    assert_eq!(schedule.days, vec![Weekday::Mon, Weekday::Thu]);
+    assert_eq!(seconds, Some(5400));
+
+    assert_eq!(parse_schedule_with_duration("daily 08:00").unwrap().1, None);
+    assert!(parse_schedule_with_duration("weekdays 09:30 soon").is_err());
+    assert!(parse_schedule_with_duration("15 min").is_err());
+}
+
+#[test]
+fn test_request_starts_at_scheduled_time_or_given_start() {
+    let template = standup();
+    // A Monday
+    let monday = NaiveDate::from_ymd_opt(2024, 3, 4).unwrap();
+    let now = local(monday, 11, 0).with_timezone(&Utc);
+
+    let request = template.request(None, now);
+    assert_eq!(request.started, local(monday, 9, 30).with_timezone(&Utc));
+    assert_eq!(request.seconds, 900);
+    assert_eq!(request.comment.as_deref(), Some("Daily"));
+
+    let started = Utc.with_ymd_and_hms(2024, 3, 1, 14, 0, 0).unwrap();
+    assert_eq!(template.request(Some(started), now).started, started);
+
+    // Saturday is not scheduled, so the worklog ends now
+    let saturday = local(NaiveDate::from_ymd_opt(2024, 3, 9).unwrap(), 11, 0).with_timezone(&Utc);
+    assert_eq!(template.request(None, saturday).started, saturday - chrono::Duration::minutes(15));
+}
+
+#[test]
+fn test_templates_are_due_once_per_scheduled_day() {
+    let monday = NaiveDate::from_ymd_opt(2024, 3, 4).unwrap();
+    let store = TemplateStore::new();
+    store.save(standup(), local(monday, 8, 0)).unwrap();
+    let mut manual = standup();
+    manual.name = "Retro".to_string();
+    manual.auto = None;
+    store.save(manual, local(monday, 8, 0)).unwrap();
+
+    assert!(store.take_due(local(monday, 9, 0)).unwrap().is_empty());
+    let due = store.take_due(local(monday, 9, 31)).unwrap();
+    assert_eq!(due.len(), 1);
+    assert_eq!(due[0].name, "Stand-up");
+    assert!(store.take_due(local(monday, 10, 0)).unwrap().is_empty());
+    assert_eq!(store.take_due(local(monday.succ_opt().unwrap(), 9, 30)).unwrap().len(), 1);
+
+    // Saved after today's time, it waits for the next day
+    let later = TemplateStore::new();
+    later.save(standup(), local(monday, 10, 0)).unwrap();
+    assert!(later.take_due(local(monday, 10, 1)).unwrap().is_empty());
+
+    let mut unscheduled = standup();
+    unscheduled.schedule = None;
+    assert!(store.save(unscheduled, local(monday, 8, 0)).is_err());
+    store.remove("stand-up").unwrap();
+    assert_eq!(store.list().unwrap().len(), 1);
+}
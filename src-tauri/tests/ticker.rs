@@ -0,0 +1,48 @@
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use jira_timesheet::ticker::{ticks, TimerTick};
+use jira_timesheet::timer::Timer;
+
+fn at(minutes: i64) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, 3, 4, 9, 0, 0).unwrap() + Duration::minutes(minutes)
+}
+
+fn timer(id: u32, started: i64) -> Timer {
+    Timer::new(id, format!("PROJ-{}", id), "Some ticket".to_string(), at(started))
+}
+
+#[test]
+fn test_ticks_carry_running_timers_only() {
+    let mut paused = timer(2, 0);
+    paused.segments[0].end = Some(at(5));
+    let timers = [timer(1, 0), paused, timer(3, 10)];
+
+    let got = ticks(&timers, true, at(20));
+
+    assert_eq!(
+        got,
+        vec![
+            TimerTick {
+                id: 1,
+                elapsed_seconds: 20 * 60
+            },
+            TimerTick {
+                id: 3,
+                elapsed_seconds: 10 * 60
+            },
+        ]
+    );
+    assert_eq!(
+        serde_json::to_value(&got[0]).unwrap(),
+        serde_json::json!({ "id": 1, "elapsed_seconds": 1200 })
+    );
+}
+
+#[test]
+fn test_no_ticks_while_hidden_or_without_running_timers() {
+    let mut paused = timer(1, 0);
+    paused.segments[0].end = Some(at(5));
+
+    assert!(ticks(&[timer(2, 0)], false, at(20)).is_empty());
+    assert!(ticks(&[paused], true, at(20)).is_empty());
+    assert!(ticks(&[], true, at(20)).is_empty());
+}
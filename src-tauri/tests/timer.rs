@@ -0,0 +1,422 @@
+use chrono::{DateTime, Duration, Local, TimeZone, Utc};
+use jira_timesheet::timer::{
+    timeline, HistoryEntry, Timer, TimerPolicy, TimerSegment, TimerState, WorklogPart,
+};
+
+fn at(minutes: i64) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, 3, 4, 9, 0, 0).unwrap() + Duration::minutes(minutes)
+}
+
+fn state(policy: TimerPolicy) -> TimerState {
+    let state = TimerState::new();
+    state.set_policy(policy, at(0)).unwrap();
+    state
+}
+
+fn start(state: &TimerState, key: &str, minutes: i64) -> u32 {
+    state
+        .start(key.to_string(), "Some ticket".to_string(), at(minutes))
+        .unwrap()
+        .id
+}
+
+fn running_ids(state: &TimerState, minutes: i64) -> Vec<u32> {
+    state
+        .snapshot(at(minutes))
+        .unwrap()
+        .iter()
+        .filter(|t| !t.paused)
+        .map(|t| t.timer.id)
+        .collect()
+}
+
+#[test]
+fn test_parallel_policy_runs_timers_concurrently() {
+    let state = state(TimerPolicy::Parallel);
+    let first = start(&state, "PROJ-1", 0);
+    let second = start(&state, "PROJ-2", 10);
+
+    assert_eq!(running_ids(&state, 30), vec![first, second]);
+    let timers = state.snapshot(at(30)).unwrap();
+    assert_eq!(timers[0].elapsed_seconds, 30 * 60);
+    assert_eq!(timers[1].elapsed_seconds, 20 * 60);
+}
+
+#[test]
+fn test_exclusive_start_pauses_running_timers() {
+    let state = state(TimerPolicy::Exclusive);
+    let first = start(&state, "PROJ-1", 0);
+    let second = start(&state, "PROJ-2", 10);
+
+    assert_eq!(running_ids(&state, 30), vec![second]);
+    let timers = state.snapshot(at(30)).unwrap();
+    assert_eq!(timers[0].timer.id, first);
+    assert_eq!(timers[0].elapsed_seconds, 10 * 60);
+    assert_eq!(timers[1].elapsed_seconds, 20 * 60);
+}
+
+#[test]
+fn test_exclusive_resume_pauses_the_other_timer() {
+    let state = state(TimerPolicy::Exclusive);
+    let first = start(&state, "PROJ-1", 0);
+    let second = start(&state, "PROJ-2", 10);
+
+    state.resume(first, at(20)).unwrap();
+
+    assert_eq!(running_ids(&state, 30), vec![first]);
+    let timers = state.snapshot(at(30)).unwrap();
+    assert_eq!(timers[0].elapsed_seconds, 20 * 60);
+    assert_eq!(timers[1].timer.id, second);
+    assert_eq!(timers[1].elapsed_seconds, 10 * 60);
+}
+
+#[test]
+fn test_switching_to_exclusive_keeps_latest_running_timer() {
+    let state = state(TimerPolicy::Parallel);
+    let first = start(&state, "PROJ-1", 0);
+    let second = start(&state, "PROJ-2", 10);
+    state.pause(first, at(15)).unwrap();
+    state.resume(first, at(20)).unwrap();
+
+    state.set_policy(TimerPolicy::Exclusive, at(25)).unwrap();
+
+    assert_eq!(running_ids(&state, 30), vec![first]);
+    let timers = state.snapshot(at(30)).unwrap();
+    assert_eq!(timers[1].timer.id, second);
+    assert_eq!(timers[1].elapsed_seconds, 15 * 60);
+}
+
+#[test]
+fn test_duplicate_issue_rejected_without_pausing_others() {
+    let state = state(TimerPolicy::Exclusive);
+    let first = start(&state, "PROJ-1", 0);
+
+    let err = state
+        .start("PROJ-1".to_string(), "Again".to_string(), at(5))
+        .unwrap_err();
+
+    assert_eq!(err, "Timer already running for PROJ-1");
+    assert_eq!(running_ids(&state, 10), vec![first]);
+}
+
+#[test]
+fn test_issue_keys_match_ignoring_case() {
+    let state = state(TimerPolicy::Parallel);
+    let first = start(&state, "PROJ-1", 0);
+
+    assert!(state.start("proj-1".to_string(), "Again".to_string(), at(5)).is_err());
+    assert_eq!(state.find_id("proj-1").unwrap(), first);
+}
+
+#[test]
+fn test_timer_policy_serde_shape() {
+    assert_eq!(
+        serde_json::to_string(&TimerPolicy::Exclusive).unwrap(),
+        r#""exclusive""#
+    );
+    let policy: TimerPolicy = serde_json::from_str(r#""parallel""#).unwrap();
+    assert_eq!(policy, TimerPolicy::Parallel);
+}
+
+fn segment(start: i64, end: Option<i64>) -> TimerSegment {
+    TimerSegment {
+        start: at(start),
+        end: end.map(at),
+    }
+}
+
+#[test]
+fn test_pause_and_resume_record_segments() {
+    let state = state(TimerPolicy::Parallel);
+    let id = start(&state, "PROJ-1", 0);
+    state.pause(id, at(10)).unwrap();
+    state.resume(id, at(30)).unwrap();
+
+    let timer = state.stop(id, at(45)).unwrap();
+
+    assert_eq!(timer.segments, vec![segment(0, Some(10)), segment(30, Some(45))]);
+    assert_eq!(timer.elapsed_at(at(90)), 25 * 60);
+}
+
+#[test]
+fn test_set_elapsed_keeps_segments_and_counts_on() {
+    let state = state(TimerPolicy::Parallel);
+    let id = start(&state, "PROJ-1", 0);
+
+    assert!(state.set_elapsed(id, 60 * 60, at(20)).unwrap());
+    assert!(!state.set_elapsed(id, 60 * 60, at(20)).unwrap());
+
+    let timers = state.snapshot(at(30)).unwrap();
+    assert_eq!(timers[0].elapsed_seconds, 70 * 60);
+    assert_eq!(timers[0].timer.segments, vec![segment(0, None)]);
+    assert_eq!(timers[0].timer.adjustments[0].before_seconds, 20 * 60);
+}
+
+#[test]
+fn test_set_elapsed_on_paused_timer_records_adjustment() {
+    let state = state(TimerPolicy::Parallel);
+    let id = start(&state, "PROJ-1", 0);
+    state.pause(id, at(10)).unwrap();
+
+    assert!(state.set_elapsed(id, 45 * 60, at(20)).unwrap());
+    assert!(state.set_elapsed(id, 30 * 60, at(25)).unwrap());
+
+    // Paused, so the corrected time does not count on
+    let timer = &state.snapshot(at(60)).unwrap()[0].timer;
+    assert_eq!(timer.elapsed_at(at(60)), 30 * 60);
+    let adjustments: Vec<_> = timer
+        .adjustments
+        .iter()
+        .map(|a| (a.before_seconds, a.after_seconds, a.adjusted_at))
+        .collect();
+    assert_eq!(adjustments, vec![(10 * 60, 45 * 60, at(20)), (45 * 60, 30 * 60, at(25))]);
+}
+
+#[test]
+fn test_set_elapsed_rejects_negative_values_and_unknown_timers() {
+    let state = state(TimerPolicy::Parallel);
+    let id = start(&state, "PROJ-1", 0);
+
+    assert!(state.set_elapsed(id, -60, at(10)).unwrap_err().contains("negative"));
+    assert_eq!(state.set_elapsed(id + 1, 60, at(10)).unwrap_err(), "Timer not found");
+
+    let timer = &state.snapshot(at(10)).unwrap()[0].timer;
+    assert_eq!(timer.elapsed_at(at(10)), 10 * 60);
+    assert!(timer.adjustments.is_empty());
+}
+
+#[test]
+fn test_adjustments_are_kept_in_history() {
+    let state = state(TimerPolicy::Parallel);
+    let id = start(&state, "PROJ-1", 0);
+    state.set_elapsed(id, 0, at(15)).unwrap();
+
+    let timer = state.stop(id, at(20)).unwrap();
+    state.record_history(&timer, Some(5 * 60), at(20));
+
+    let history = state.history.lock().unwrap();
+    assert_eq!(history[0].elapsed_seconds, 5 * 60);
+    assert_eq!(history[0].adjustments.len(), 1);
+    assert_eq!(history[0].adjustments[0].before_seconds, 15 * 60);
+    assert_eq!(history[0].adjustments[0].after_seconds, 0);
+}
+
+fn timer_with_segments(segments: Vec<TimerSegment>, offset_seconds: i64) -> Timer {
+    let mut timer = Timer::new(1, "PROJ-1".to_string(), "Some ticket".to_string(), at(0));
+    timer.segments = segments;
+    timer.offset_seconds = offset_seconds;
+    timer
+}
+
+#[test]
+fn test_worklog_parts_single_worklog_starts_with_first_segment() {
+    let timer = timer_with_segments(vec![segment(5, Some(20)), segment(30, Some(40))], 0);
+
+    assert_eq!(
+        timer.worklog_parts(false, at(60)),
+        vec![WorklogPart {
+            started: at(5),
+            seconds: 25 * 60,
+        }]
+    );
+}
+
+#[test]
+fn test_worklog_parts_one_per_segment_with_corrections() {
+    let timer = timer_with_segments(
+        vec![segment(0, Some(20)), segment(30, Some(40)), segment(50, Some(55))],
+        -8 * 60,
+    );
+
+    assert_eq!(
+        timer.worklog_parts(true, at(60)),
+        vec![
+            WorklogPart {
+                started: at(0),
+                seconds: 20 * 60,
+            },
+            WorklogPart {
+                started: at(30),
+                seconds: 7 * 60,
+            },
+        ]
+    );
+
+    let added = timer_with_segments(vec![segment(0, Some(20)), segment(30, None)], 5 * 60);
+    assert_eq!(added.worklog_parts(true, at(40))[1].seconds, 15 * 60);
+}
+
+#[test]
+fn test_worklog_parts_fold_short_segments_into_next() {
+    let mut timer = timer_with_segments(vec![segment(0, Some(10)), segment(20, Some(50))], 0);
+    timer.segments.insert(
+        1,
+        TimerSegment {
+            start: at(15),
+            end: Some(at(15) + Duration::seconds(30)),
+        },
+    );
+
+    let parts = timer.worklog_parts(true, at(60));
+    assert_eq!(parts.len(), 2);
+    assert_eq!(parts[1].started, at(20));
+    assert_eq!(parts[1].seconds, 30 * 60 + 30);
+}
+
+#[test]
+fn test_timeline_lists_todays_segments_in_order() {
+    let active = timer_with_segments(vec![segment(-120, Some(-100)), segment(30, None)], 0);
+    let stopped = HistoryEntry {
+        issue_key: "PROJ-2".to_string(),
+        summary: "Stopped".to_string(),
+        elapsed_seconds: 20 * 60,
+        logged: true,
+        logged_seconds: Some(30 * 60),
+        stopped_at: at(20),
+        adjustments: Vec::new(),
+        segments: vec![segment(-10, Some(10))],
+    };
+
+    let entries = timeline(&[active], &[stopped], at(0));
+
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].issue_key, "PROJ-2");
+    assert_eq!(entries[0].start, at(-10));
+    assert_eq!(entries[0].logged, Some(true));
+    assert_eq!(entries[1].issue_key, "PROJ-1");
+    assert_eq!(entries[1].end, None);
+    assert_eq!(entries[1].logged, None);
+}
+
+#[test]
+fn test_reassign_keeps_segments_and_rejects_tracked_issue() {
+    let state = state(TimerPolicy::Parallel);
+    let id = start(&state, "PROJ-1", 0);
+    start(&state, "PROJ-2", 5);
+    state.pause(id, at(10)).unwrap();
+    state.resume(id, at(20)).unwrap();
+
+    let err = state.check_reassign(id, "PROJ-2").unwrap_err();
+    assert_eq!(err, "Timer already running for PROJ-2");
+    assert_eq!(state.check_reassign(99, "PROJ-3").unwrap_err(), "Timer not found");
+
+    state
+        .reassign(id, "PROJ-3".to_string(), "Right ticket".to_string())
+        .unwrap();
+
+    let timers = state.snapshot(at(30)).unwrap();
+    assert_eq!(timers[0].timer.issue_key, "PROJ-3");
+    assert_eq!(timers[0].timer.summary, "Right ticket");
+    assert_eq!(timers[0].timer.segments, vec![segment(0, Some(10)), segment(20, None)]);
+    assert_eq!(timers[0].elapsed_seconds, 20 * 60);
+}
+
+fn local(day: u32, hour: u32) -> DateTime<Utc> {
+    Local
+        .with_ymd_and_hms(2024, 3, day, hour, 0, 0)
+        .earliest()
+        .unwrap()
+        .with_timezone(&Utc)
+}
+
+#[test]
+fn test_split_at_midnight_and_log_one_worklog_per_day() {
+    let mut timer = Timer::new(1, "PROJ-1".to_string(), "Some ticket".to_string(), local(4, 22));
+    let midnight = local(5, 0);
+
+    // Nothing to split before midnight
+    assert!(!timer.split_at_midnight(local(4, 23)));
+
+    assert_eq!(
+        timer.worklog_parts(false, local(5, 1)),
+        vec![
+            WorklogPart {
+                started: local(4, 22),
+                seconds: 2 * 3600,
+            },
+            WorklogPart {
+                started: midnight,
+                seconds: 3600,
+            },
+        ]
+    );
+
+    assert!(timer.split_at_midnight(local(5, 1)));
+    assert_eq!(
+        timer.segments,
+        vec![
+            TimerSegment {
+                start: local(4, 22),
+                end: Some(midnight),
+            },
+            TimerSegment {
+                start: midnight,
+                end: None,
+            },
+        ]
+    );
+    assert!(timer.is_running());
+    assert!(!timer.split_at_midnight(local(5, 2)));
+}
+
+#[test]
+fn test_stop_all_and_restore_keep_ids() {
+    let state = state(TimerPolicy::Parallel);
+    start(&state, "PROJ-1", 0);
+    let id = start(&state, "PROJ-2", 5);
+    state.pause_all(at(10)).unwrap();
+    assert!(running_ids(&state, 10).is_empty());
+
+    let stopped = state.stop_all(at(20)).unwrap();
+    assert_eq!(stopped.len(), 2);
+    assert!(state.snapshot(at(20)).unwrap().is_empty());
+
+    let restored = TimerState::new();
+    restored.restore(stopped).unwrap();
+    let timers = restored.snapshot(at(30)).unwrap();
+    assert_eq!(timers[1].timer.id, id);
+    assert_eq!(timers[1].elapsed_seconds, 5 * 60);
+
+    let next = restored
+        .start("PROJ-3".to_string(), "Some ticket".to_string(), at(30))
+        .unwrap();
+    assert_eq!(next.id, id + 1);
+}
+
+#[test]
+fn test_restore_merges_into_a_timer_on_the_same_issue() {
+    let state = state(TimerPolicy::Parallel);
+    let id = start(&state, "PROJ-1", 0);
+    let stopped = state.stop(id, at(30)).unwrap();
+    // Started again while the stopped timer was being logged
+    let again = start(&state, "proj-1", 35);
+
+    state.restore(vec![stopped]).unwrap();
+
+    let timers = state.snapshot(at(45)).unwrap();
+    assert_eq!(timers.len(), 1);
+    assert_eq!(timers[0].timer.id, again);
+    assert_eq!(timers[0].timer.started_at, at(0));
+    assert_eq!(timers[0].timer.segments, vec![segment(0, Some(30)), segment(35, None)]);
+    assert_eq!(timers[0].elapsed_seconds, 40 * 60);
+}
+
+#[test]
+fn test_pause_or_resume_all_resumes_the_timers_paused_last() {
+    let state = state(TimerPolicy::Parallel);
+    let first = start(&state, "PROJ-1", 0);
+    let second = start(&state, "PROJ-2", 5);
+    let third = start(&state, "PROJ-3", 6);
+    state.pause(third, at(8)).unwrap();
+    assert_eq!(state.active_id().unwrap(), Some(second));
+
+    state.pause_or_resume_all(at(10)).unwrap();
+    assert!(running_ids(&state, 10).is_empty());
+    assert_eq!(state.active_id().unwrap(), None);
+
+    state.pause_or_resume_all(at(20)).unwrap();
+    assert_eq!(running_ids(&state, 20), vec![first, second]);
+
+    let empty = TimerState::new();
+    assert_eq!(empty.pause_or_resume_all(at(0)).unwrap_err(), "No timer to resume");
+}
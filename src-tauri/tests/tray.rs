@@ -0,0 +1,95 @@
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use jira_timesheet::timer::{HistoryEntry, Timer, TimerSegment, TimerView};
+use jira_timesheet::tray::{recent_tickets, tooltip, tracked_since, TrayAction};
+
+fn at(minutes: i64) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, 3, 4, 9, 0, 0).unwrap() + Duration::minutes(minutes)
+}
+
+fn segment(start: i64, end: Option<i64>) -> TimerSegment {
+    TimerSegment {
+        start: at(start),
+        end: end.map(at),
+    }
+}
+
+fn view(id: u32, issue_key: &str, segments: Vec<TimerSegment>, now: i64) -> TimerView {
+    let mut timer = Timer::new(id, issue_key.to_string(), "Some ticket".to_string(), segments[0].start);
+    timer.segments = segments;
+    TimerView {
+        elapsed_seconds: timer.elapsed_at(at(now)),
+        paused: !timer.is_running(),
+        timer,
+    }
+}
+
+fn history(issue_key: &str, summary: &str, segments: Vec<TimerSegment>) -> HistoryEntry {
+    HistoryEntry {
+        issue_key: issue_key.to_string(),
+        summary: summary.to_string(),
+        elapsed_seconds: 0,
+        logged: true,
+        logged_seconds: None,
+        stopped_at: at(0),
+        adjustments: Vec::new(),
+        segments,
+    }
+}
+
+#[test]
+fn test_tray_action_ids_round_trip() {
+    let actions = [
+        TrayAction::Show,
+        TrayAction::Quit,
+        TrayAction::Pause(3),
+        TrayAction::Resume(3),
+        TrayAction::Log(12),
+        TrayAction::Start("PROJ-1".to_string()),
+    ];
+    for action in actions {
+        assert_eq!(TrayAction::parse(&action.id()), Some(action));
+    }
+
+    assert_eq!(TrayAction::parse("pause:x"), None);
+    assert_eq!(TrayAction::parse("start:"), None);
+    assert_eq!(TrayAction::parse("other"), None);
+}
+
+#[test]
+fn test_recent_tickets_newest_first_without_active_ones() {
+    let entries = [
+        history("PROJ-1", "Old", Vec::new()),
+        history("PROJ-2", "Active", Vec::new()),
+        history("PROJ-3", "Newer", Vec::new()),
+        history("PROJ-1", "Latest", Vec::new()),
+    ];
+    let timers = [view(1, "PROJ-2", vec![segment(0, None)], 10)];
+
+    assert_eq!(
+        recent_tickets(&entries, &timers, 8),
+        vec![
+            ("PROJ-1".to_string(), "Latest".to_string()),
+            ("PROJ-3".to_string(), "Newer".to_string()),
+        ]
+    );
+    assert_eq!(recent_tickets(&entries, &timers, 1).len(), 1);
+}
+
+#[test]
+fn test_tooltip_shows_active_timer_and_todays_total() {
+    let timers = [
+        view(1, "PROJ-1", vec![segment(-30, Some(20))], 60),
+        view(2, "PROJ-2", vec![segment(40, None)], 60),
+    ];
+    let entries = [history("PROJ-3", "Done", vec![segment(-90, Some(-60))])];
+
+    // Only counted from `since`
+    let today = tracked_since(&timers, &entries, at(-70), at(60));
+    assert_eq!(today, (10 + 50 + 20) * 60);
+
+    assert_eq!(
+        tooltip(&timers, today),
+        "Jira Timesheet\nPROJ-2 — 0h20\nToday: 1h20"
+    );
+    assert_eq!(tooltip(&timers[..1], 0), "Jira Timesheet\nToday: 0h");
+}
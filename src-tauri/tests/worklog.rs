@@ -0,0 +1,209 @@
+use chrono::{DateTime, Duration, Local, NaiveDate, TimeZone, Utc};
+use jira_timesheet::jira::JiraClient;
+use jira_timesheet::timer::WorklogPart;
+use jira_timesheet::worklog::{
+    log_all, retry_all, split_worklogs, Allocation, AllocationAmount, LogOutcome, WorklogQueue,
+    WorklogRequest,
+};
+use serde_json::json;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn at(minutes: i64) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, 3, 4, 9, 0, 0).unwrap() + Duration::minutes(minutes)
+}
+
+fn allocation(issue_key: &str, amount: AllocationAmount) -> Allocation {
+    Allocation {
+        issue_key: issue_key.to_string(),
+        amount,
+    }
+}
+
+fn part(started: DateTime<Utc>, seconds: u64) -> WorklogPart {
+    WorklogPart { started, seconds }
+}
+
+fn request(issue_key: &str, seconds: u64, started: DateTime<Utc>) -> WorklogRequest {
+    WorklogRequest {
+        issue_key: issue_key.to_string(),
+        seconds,
+        started,
+        comment: None,
+    }
+}
+
+#[test]
+fn test_split_by_percentages_follows_on() {
+    let allocations = [
+        allocation("PROJ-1", AllocationAmount::Percent(60.0)),
+        allocation("PROJ-2", AllocationAmount::Percent(40.0)),
+    ];
+
+    let requests = split_worklogs(&allocations, &[part(at(0), 3600)], Some("Pairing")).unwrap();
+
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[0].seconds, 36 * 60);
+    assert_eq!(requests[0].started, at(0));
+    assert_eq!(requests[1].seconds, 24 * 60);
+    assert_eq!(requests[1].started, at(36));
+    assert_eq!(requests[1].comment.as_deref(), Some("Pairing"));
+}
+
+#[test]
+fn test_split_last_allocation_absorbs_remainder() {
+    // The timer ran on for 30s while the split was being entered
+    let allocations = [
+        allocation("PROJ-1", AllocationAmount::Seconds(1200)),
+        allocation("PROJ-2", AllocationAmount::Seconds(600)),
+    ];
+
+    let requests = split_worklogs(&allocations, &[part(at(0), 1830)], None).unwrap();
+
+    assert_eq!(requests[0].seconds, 1200);
+    assert_eq!(requests[1].seconds, 630);
+}
+
+#[test]
+fn test_split_skips_pauses_and_splits_at_midnight() {
+    let allocations = [
+        allocation("PROJ-1", AllocationAmount::Seconds(2700)),
+        allocation("PROJ-2", AllocationAmount::Seconds(900)),
+    ];
+    // Paused from 09:30 to 10:00
+    let parts = [part(at(0), 1800), part(at(60), 1800)];
+
+    let requests = split_worklogs(&allocations, &parts, None).unwrap();
+
+    assert_eq!(requests, vec![request("PROJ-1", 2700, at(0)), request("PROJ-2", 900, at(75))]);
+
+    let local = |day: u32, hour: u32, minute: u32| {
+        Local
+            .from_local_datetime(&NaiveDate::from_ymd_opt(2024, 3, day).unwrap().and_hms_opt(hour, minute, 0).unwrap())
+            .earliest()
+            .unwrap()
+            .with_timezone(&Utc)
+    };
+    // A timer from 23:30 to 00:30, split in two parts at midnight
+    let parts = [part(local(4, 23, 30), 1800), part(local(5, 0, 0), 1800)];
+
+    let allocations = [
+        allocation("PROJ-1", AllocationAmount::Seconds(3000)),
+        allocation("PROJ-2", AllocationAmount::Seconds(600)),
+    ];
+    let requests = split_worklogs(&allocations, &parts, None).unwrap();
+
+    assert_eq!(
+        requests,
+        vec![
+            request("PROJ-1", 1800, local(4, 23, 30)),
+            request("PROJ-1", 1200, local(5, 0, 0)),
+            request("PROJ-2", 600, local(5, 0, 20)),
+        ]
+    );
+}
+
+#[test]
+fn test_split_rejects_allocations_not_matching_elapsed() {
+    let allocations = [
+        allocation("PROJ-1", AllocationAmount::Seconds(1200)),
+        allocation("PROJ-2", AllocationAmount::Percent(10.0)),
+    ];
+
+    let err = split_worklogs(&allocations, &[part(at(0), 3600)], None).unwrap_err();
+    assert_eq!(err, "Allocations add up to 1560s but the timer has 3600s");
+
+    let err = split_worklogs(&[], &[part(at(0), 3600)], None).unwrap_err();
+    assert_eq!(err, "No allocations given");
+}
+
+#[test]
+fn test_split_rejects_worklogs_under_a_minute() {
+    let allocations = [
+        allocation("PROJ-1", AllocationAmount::Percent(99.0)),
+        allocation("PROJ-2", AllocationAmount::Percent(1.0)),
+    ];
+
+    let err = split_worklogs(&allocations, &[part(at(0), 3600)], None).unwrap_err();
+    assert_eq!(err, "Worklog for PROJ-2 must be at least 1 minute");
+}
+
+#[test]
+fn test_allocation_serde_shape() {
+    let allocation: Allocation =
+        serde_json::from_value(json!({ "issue_key": "PROJ-1", "percent": 25 })).unwrap();
+    assert_eq!(allocation.amount, AllocationAmount::Percent(25.0));
+
+    let allocation: Allocation =
+        serde_json::from_value(json!({ "issue_key": "PROJ-1", "seconds": 900 })).unwrap();
+    assert_eq!(allocation.amount, AllocationAmount::Seconds(900));
+}
+
+async fn setup() -> (MockServer, JiraClient) {
+    let server = MockServer::start().await;
+    let client = JiraClient::with_http_client(
+        reqwest::Client::new(),
+        &server.uri(),
+        "alex@example.com",
+        "secret",
+    );
+    (server, client)
+}
+
+async fn mount_worklog(server: &MockServer, issue_key: &str, status: u16, times: u64) {
+    Mock::given(method("POST"))
+        .and(path(format!("/rest/api/3/issue/{}/worklog", issue_key)))
+        .respond_with(ResponseTemplate::new(status))
+        .up_to_n_times(times)
+        .mount(server)
+        .await;
+}
+
+#[tokio::test]
+async fn log_all_fails_without_queueing_when_nothing_was_logged() {
+    let (server, client) = setup().await;
+    mount_worklog(&server, "PROJ-1", 500, 1).await;
+    let queue = WorklogQueue::new();
+
+    let requests = vec![request("PROJ-1", 600, at(0)), request("PROJ-2", 600, at(10))];
+    let err = log_all(&client, &queue, requests).await.unwrap_err();
+
+    assert!(err.starts_with("Worklog error 500"), "{}", err);
+    assert!(queue.list().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn log_all_queues_the_rest_after_a_partial_failure() {
+    let (server, client) = setup().await;
+    mount_worklog(&server, "PROJ-1", 201, 1).await;
+    mount_worklog(&server, "PROJ-2", 503, 1).await;
+    let queue = WorklogQueue::new();
+
+    let requests = vec![
+        request("PROJ-1", 600, at(0)),
+        request("PROJ-2", 600, at(10)),
+        request("PROJ-3", 600, at(20)),
+    ];
+    let outcome = log_all(&client, &queue, requests).await.unwrap();
+
+    assert_eq!(outcome, LogOutcome { logged: 1, queued: 2 });
+    let pending = queue.list().unwrap();
+    assert_eq!(pending.len(), 2);
+    assert_eq!(pending[0].request, request("PROJ-2", 600, at(10)));
+    assert!(pending[0].error.starts_with("Worklog error 503"), "{}", pending[0].error);
+    assert_eq!(pending[1].request.issue_key, "PROJ-3");
+
+    // Jira is back for PROJ-2 only
+    mount_worklog(&server, "PROJ-2", 201, 1).await;
+    mount_worklog(&server, "PROJ-3", 400, 1).await;
+    let outcome = retry_all(&client, &queue).await.unwrap();
+
+    assert_eq!(outcome, LogOutcome { logged: 1, queued: 1 });
+    let pending = queue.list().unwrap();
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0].request.issue_key, "PROJ-3");
+    assert!(pending[0].error.starts_with("Worklog error 400"), "{}", pending[0].error);
+
+    queue.remove(pending[0].id).unwrap();
+    assert!(queue.list().unwrap().is_empty());
+}
@@ -1,4 +1,5 @@
 const { invoke } = window.__TAURI__.core;
+const { listen } = window.__TAURI__.event;
 
 export async function listProjects() {
   return invoke("list_projects");
@@ -12,12 +13,16 @@ export async function getIssueDetail(issueKey) {
   return invoke("get_issue_detail", { issueKey });
 }
 
+export async function getIssueComments(issueKey, startAt = 0) {
+  return invoke("get_issue_comments", { issueKey, startAt });
+}
+
 export async function getTransitions(issueKey) {
   return invoke("get_transitions", { issueKey });
 }
 
-export async function transitionIssue(issueKey, transitionId) {
-  return invoke("transition_issue", { issueKey, transitionId });
+export async function transitionIssue(issueKey, transitionId, fields = null, comment = null) {
+  return invoke("transition_issue", { issueKey, transitionId, fields, comment });
 }
 
 export async function startTimer(issueKey, summary) {
@@ -32,8 +37,92 @@ export async function resumeTimer(timerId) {
   return invoke("resume_timer", { timerId });
 }
 
-export async function stopAndLog(timerId) {
-  return invoke("stop_and_log", { timerId });
+export async function stopAndLog(timerId, comment = null, perSegment = false) {
+  return invoke("stop_and_log", { timerId, comment, perSegment });
+}
+
+export async function reassignTimer(timerId, issueKey) {
+  return invoke("reassign_timer", { timerId, issueKey });
+}
+
+export async function splitAndLog(timerId, allocations, comment = null) {
+  return invoke("split_and_log", { timerId, allocations, comment });
+}
+
+export async function getPendingWorklogs() {
+  return invoke("get_pending_worklogs");
+}
+
+export async function retryPendingWorklogs() {
+  return invoke("retry_pending_worklogs");
+}
+
+export async function dropPendingWorklog(id) {
+  return invoke("drop_pending_worklog", { id });
+}
+
+export async function getDrafts() {
+  return invoke("get_drafts");
+}
+
+export async function addDrafts(worklogs) {
+  return invoke("add_drafts", { worklogs });
+}
+
+export async function updateDraft(id, worklog) {
+  return invoke("update_draft", { id, worklog });
+}
+
+export async function deleteDraft(id) {
+  return invoke("delete_draft", { id });
+}
+
+export async function clearSubmittedDrafts() {
+  return invoke("clear_submitted_drafts");
+}
+
+export async function submitDrafts(ids = null) {
+  return invoke("submit_drafts", { ids });
+}
+
+export async function checkWorklogs(worklogs) {
+  return invoke("check_worklogs", { worklogs });
+}
+
+export async function checkDrafts() {
+  return invoke("check_drafts");
+}
+
+export async function checkTimer(timerId, perSegment = false) {
+  return invoke("check_timer", { timerId, perSegment });
+}
+
+export async function onDraftsChanged(callback) {
+  return listen("drafts://changed", (event) => callback(event.payload));
+}
+
+export async function getTemplates() {
+  return invoke("get_templates");
+}
+
+export async function saveTemplate(template) {
+  return invoke("save_template", template);
+}
+
+export async function deleteTemplate(name) {
+  return invoke("delete_template", { name });
+}
+
+export async function applyTemplate(name, mode, started = null) {
+  return invoke("apply_template", { name, mode, started });
+}
+
+export async function getTimeline() {
+  return invoke("get_timeline");
+}
+
+export async function addComment(issueKey, markdown) {
+  return invoke("add_comment", { issueKey, markdown });
 }
 
 export async function stopTimer(timerId) {
@@ -56,6 +145,102 @@ export async function getTimers() {
   return invoke("get_timers");
 }
 
+export async function onTimersChanged(callback) {
+  return listen("timers://changed", (event) => callback(event.payload));
+}
+
+export async function onTimersTick(callback) {
+  return listen("timers://tick", (event) => callback(event.payload));
+}
+
+export async function onIdleDetected(callback) {
+  return listen("idle://detected", (event) => callback(event.payload));
+}
+
+export async function onQuitRequested(callback) {
+  return listen("app://quit-requested", (event) => callback(event.payload));
+}
+
+export async function quitApp(action) {
+  return invoke("quit_app", { action });
+}
+
+export async function getRounding() {
+  return invoke("get_rounding");
+}
+
+export async function saveRounding(defaultRule, projects) {
+  return invoke("save_rounding", { default: defaultRule, projects });
+}
+
+export async function getReminders() {
+  return invoke("get_reminders");
+}
+
+export async function saveReminders(reminders) {
+  return invoke("save_reminders", { reminders });
+}
+
+export async function getShortcuts() {
+  return invoke("get_shortcuts");
+}
+
+export async function saveShortcuts(bindings) {
+  return invoke("save_shortcuts", { bindings });
+}
+
+export async function onQuickSearch(callback) {
+  return listen("shortcuts://quick-search", () => callback());
+}
+
+export async function getGitWatch() {
+  return invoke("get_git_watch");
+}
+
+export async function saveGitWatch(repos, autoStart) {
+  return invoke("save_git_watch", { repos, autoStart });
+}
+
+export async function getCalendarImport() {
+  return invoke("get_calendar_import");
+}
+
+export async function saveCalendarImport(source, rules) {
+  return invoke("save_calendar_import", { source, rules });
+}
+
+export async function previewCalendar(from, to) {
+  return invoke("preview_calendar", { from, to });
+}
+
+export async function logCalendarWorklogs(worklogs) {
+  return invoke("log_calendar_worklogs", { worklogs });
+}
+
+export async function draftCalendarWorklogs(worklogs) {
+  return invoke("draft_calendar_worklogs", { worklogs });
+}
+
+export async function onBranchIssue(callback) {
+  return listen("git://branch-issue", (event) => callback(event.payload));
+}
+
+export async function getTimerPolicy() {
+  return invoke("get_timer_policy");
+}
+
+export async function setTimerPolicy(policy) {
+  return invoke("set_timer_policy", { policy });
+}
+
+export async function resolveIdleSpan(spanId, resolution) {
+  return invoke("resolve_idle_span", { spanId, resolution });
+}
+
+export async function getReport(from, to, dimensions = null) {
+  return invoke("get_report", { from, to, dimensions });
+}
+
 export async function getMyWorklogs(startDate, endDate) {
   return invoke("get_my_worklogs", { startDate, endDate });
 }
@@ -64,6 +249,6 @@ export async function getConfig() {
   return invoke("get_config");
 }
 
-export async function saveConfig(jiraUrl, email, apiToken) {
-  return invoke("save_config", { jiraUrl, email, apiToken });
+export async function saveConfig(jiraUrl, email, apiToken, draftMode) {
+  return invoke("save_config", { jiraUrl, email, apiToken, draftMode });
 }
@@ -2,16 +2,61 @@ import {
   listProjects,
   searchTickets,
   getIssueDetail,
+  getIssueComments,
   getTransitions,
   transitionIssue,
   startTimer,
   pauseTimer,
   resumeTimer,
   stopAndLog,
+  addComment,
   discardTimer,
   setTimerElapsed,
   getTimers,
+  onTimersChanged,
+  onQuitRequested,
+  onIdleDetected,
+  quitApp,
+  onTimersTick,
+  resolveIdleSpan,
+  getTimerPolicy,
+  getRounding,
+  saveRounding,
+  getReminders,
+  saveReminders,
+  getShortcuts,
+  saveShortcuts,
+  onQuickSearch,
+  getGitWatch,
+  saveGitWatch,
+  onBranchIssue,
+  getCalendarImport,
+  saveCalendarImport,
+  previewCalendar,
+  logCalendarWorklogs,
+  draftCalendarWorklogs,
+  getTimeline,
+  splitAndLog,
+  reassignTimer,
+  getPendingWorklogs,
+  retryPendingWorklogs,
+  dropPendingWorklog,
+  getDrafts,
+  updateDraft,
+  deleteDraft,
+  clearSubmittedDrafts,
+  submitDrafts,
+  onDraftsChanged,
+  checkWorklogs,
+  checkDrafts,
+  checkTimer,
+  getTemplates,
+  saveTemplate,
+  deleteTemplate,
+  applyTemplate,
+  setTimerPolicy,
   getMyWorklogs,
+  getReport,
   getConfig,
   saveConfig,
 } from "./jira.js";
@@ -38,24 +83,49 @@ const timersList = document.getElementById("timers-list");
 const tabBar = document.getElementById("tab-bar");
 const tabProjets = document.getElementById("tab-projets");
 const tabTimesheet = document.getElementById("tab-timesheet");
+const tabCalendar = document.getElementById("tab-calendar");
+const tabTemplates = document.getElementById("tab-templates");
+const tabReports = document.getElementById("tab-reports");
+const calendarContent = document.getElementById("calendar-content");
+const calendarLogBtn = document.getElementById("calendar-log");
+const calendarDraftBtn = document.getElementById("calendar-draft");
 const timesheetTotalValue = document.getElementById("timesheet-total-value");
 const timesheetContent = document.getElementById("timesheet-content");
 const timesheetRefresh = document.getElementById("timesheet-refresh");
 
-let refreshInterval = null;
+let latestTimers = [];
 let cachedProjects = [];
 let cachedTickets = [];
 let jiraBaseUrl = "";
+// Stopped timers become drafts instead of being logged
+let draftMode = false;
+let latestDrafts = [];
+let editingDraftId = null;
 let currentTab = "projets";
 let timesheetMode = "today";
 let timesheetLoading = false;
+let calendarProposals = [];
 
 // --- Init ---
 
+const SHORTCUT_ACTIONS = ["toggle_window", "pause_resume_all", "log_active", "restart_last", "quick_search"];
+
 async function init() {
   await loadConfig();
   await loadProjects();
-  startRefreshLoop();
+  await subscribeTimers();
+  await onQuickSearch(focusSearch);
+  await onBranchIssue(showBranchPrompt);
+}
+
+// Global shortcut: focus the filter of the list currently shown
+function focusSearch() {
+  hideSettings();
+  const input = ticketsSection.classList.contains("hidden")
+    ? document.getElementById("projects-filter")
+    : document.getElementById("tickets-filter");
+  input.focus();
+  input.select();
 }
 
 // --- Config / Settings ---
@@ -64,9 +134,36 @@ async function loadConfig() {
   try {
     const config = await getConfig();
     jiraBaseUrl = (config.jira_url || "").replace(/\/+$/, "");
+    draftMode = !!config.draft_mode;
+    document.getElementById("draft-mode").checked = draftMode;
     document.getElementById("jira-url").value = config.jira_url || "";
     document.getElementById("jira-email").value = config.email || "";
     document.getElementById("jira-token").value = config.api_token || "";
+    document.getElementById("timer-policy").value = await getTimerPolicy();
+    const rounding = await getRounding();
+    document.getElementById("rounding-mode").value = rounding.default.mode;
+    document.getElementById("rounding-increment").value = rounding.default.increment_minutes;
+    document.getElementById("rounding-minimum").value = rounding.default.minimum_minutes;
+    document.getElementById("rounding-projects").value = Object.entries(rounding.projects)
+      .map(([project, r]) => `${project}=${r.mode}:${r.increment_minutes}:${r.minimum_minutes}`)
+      .join(", ");
+    const reminders = await getReminders();
+    document.getElementById("daily-target-hours").value = reminders.daily_target_hours;
+    document.getElementById("end-of-day").value = reminders.end_of_day.slice(0, 5);
+    document.getElementById("long-timer-hours").value = reminders.long_timer_hours;
+    document.getElementById("working-days").value = reminders.working_days.join(", ");
+    const shortcuts = await getShortcuts();
+    for (const action of SHORTCUT_ACTIONS) {
+      document.getElementById(`shortcut-${action}`).value = shortcuts.bindings[action] || "";
+    }
+    const git = await getGitWatch();
+    document.getElementById("git-repos").value = git.repos.join(", ");
+    document.getElementById("git-auto-start").checked = git.auto_start;
+    const calendar = await getCalendarImport();
+    document.getElementById("calendar-source").value = calendar.source;
+    document.getElementById("calendar-rules").value = calendar.rules
+      .map((r) => `${r.field}:${r.pattern}=${r.issue_key}`)
+      .join("\n");
   } catch (_) {
     // Config not yet set
   }
@@ -90,11 +187,51 @@ settingsForm.addEventListener("submit", async (e) => {
   const jiraUrl = document.getElementById("jira-url").value.trim();
   const email = document.getElementById("jira-email").value.trim();
   const apiToken = document.getElementById("jira-token").value.trim();
+  const timerPolicy = document.getElementById("timer-policy").value;
+  const draftModeChecked = document.getElementById("draft-mode").checked;
 
   try {
-    await saveConfig(jiraUrl, email, apiToken);
+    await saveConfig(jiraUrl, email, apiToken, draftModeChecked);
+    draftMode = draftModeChecked;
+    await setTimerPolicy(timerPolicy);
+    await saveRounding(
+      {
+        mode: document.getElementById("rounding-mode").value,
+        increment_minutes: parseInt(document.getElementById("rounding-increment").value, 10) || 15,
+        minimum_minutes: parseInt(document.getElementById("rounding-minimum").value, 10) || 0,
+      },
+      document.getElementById("rounding-projects").value
+    );
+    await saveReminders({
+      daily_target_hours: parseFloat(document.getElementById("daily-target-hours").value) || 0,
+      end_of_day: `${document.getElementById("end-of-day").value || "17:30"}:00`,
+      long_timer_hours: parseInt(document.getElementById("long-timer-hours").value, 10) || 0,
+      working_days: document
+        .getElementById("working-days")
+        .value.split(",")
+        .map((d) => d.trim())
+        .filter(Boolean),
+    });
+    const bindings = {};
+    for (const action of SHORTCUT_ACTIONS) {
+      bindings[action] = document.getElementById(`shortcut-${action}`).value.trim();
+    }
+    const conflicts = await saveShortcuts(bindings);
+    await saveGitWatch(
+      document.getElementById("git-repos").value,
+      document.getElementById("git-auto-start").checked
+    );
+    await saveCalendarImport(
+      document.getElementById("calendar-source").value,
+      document.getElementById("calendar-rules").value
+    );
     jiraBaseUrl = jiraUrl.replace(/\/+$/, "");
-    showToast("Settings saved", "success");
+    if (conflicts.length > 0) {
+      // Saved anyway, the taken shortcuts just don't work until changed
+      showToast(`Raccourci(s) deja utilise(s) : ${conflicts.map((c) => c.shortcut).join(", ")}`, "error");
+    } else {
+      showToast("Settings saved", "success");
+    }
     hideSettings();
     await loadProjects();
   } catch (err) {
@@ -215,7 +352,6 @@ ticketsList.addEventListener("click", async (e) => {
     try {
       await startTimer(startBtn.dataset.key, startBtn.dataset.summary);
       showToast(`Timer started for ${startBtn.dataset.key}`, "success");
-      await refreshTimers();
     } catch (err) {
       showToast(err, "error");
     }
@@ -267,29 +403,15 @@ async function showTransitionMenu(anchor) {
     menu.addEventListener("click", async (e) => {
       const opt = e.target.closest(".transition-option");
       if (!opt) return;
-      closeTransitionMenu();
-      try {
-        await transitionIssue(issueKey, opt.dataset.tid);
-        showToast(`Status updated`, "success");
-        const ticket = cachedTickets.find((t) => t.key === issueKey);
-        if (ticket) {
-          ticket.status = opt.textContent;
-        }
-        renderTickets(
-          ticketsFilter.value
-            ? cachedTickets.filter((t) => {
-                const q = ticketsFilter.value.toLowerCase();
-                return (
-                  t.key.toLowerCase().includes(q) ||
-                  t.summary.toLowerCase().includes(q) ||
-                  t.status.toLowerCase().includes(q)
-                );
-              })
-            : cachedTickets
-        );
-      } catch (err) {
-        showToast(err, "error");
+      const transition = transitions.find((t) => t.id === opt.dataset.tid);
+      if (transition.fields.some((f) => f.required)) {
+        // Keep the menu open to fill in the transition screen
+        e.stopPropagation();
+        renderTransitionForm(menu, issueKey, transition);
+        return;
       }
+      closeTransitionMenu();
+      await applyTransition(issueKey, transition);
     });
 
     document.body.appendChild(menu);
@@ -301,6 +423,102 @@ async function showTransitionMenu(anchor) {
   }
 }
 
+async function applyTransition(issueKey, transition, fields = null, comment = null) {
+  try {
+    await transitionIssue(issueKey, transition.id, fields, comment);
+    showToast(`Status updated`, "success");
+    const ticket = cachedTickets.find((t) => t.key === issueKey);
+    if (ticket) {
+      ticket.status = transition.name;
+    }
+    filterTickets();
+    return true;
+  } catch (err) {
+    showToast(err, "error");
+    return false;
+  }
+}
+
+function transitionFieldInput(field) {
+  const id = `tf-${escapeHtml(field.key)}`;
+  if (field.allowed_values.length > 0) {
+    const multiple = field.field_type === "array" ? " multiple" : "";
+    return `<select id="${id}" data-key="${escapeHtml(field.key)}"${multiple}${field.required ? " required" : ""}>
+      ${field.field_type === "array" ? "" : '<option value="">—</option>'}
+      ${field.allowed_values.map((v) => `<option value="${escapeHtml(v.id)}">${escapeHtml(v.name)}</option>`).join("")}
+    </select>`;
+  }
+  const type = field.field_type === "number" ? "number" : "text";
+  return `<input id="${id}" type="${type}" step="any" data-key="${escapeHtml(field.key)}"${field.required ? " required" : ""}>`;
+}
+
+function transitionFieldValue(field, el) {
+  if (field.allowed_values.length > 0) {
+    if (field.field_type === "array") {
+      const ids = Array.from(el.selectedOptions).map((o) => ({ id: o.value }));
+      return ids.length > 0 ? ids : null;
+    }
+    return el.value ? { id: el.value } : null;
+  }
+  if (!el.value.trim()) return null;
+  return field.field_type === "number" ? Number(el.value) : el.value;
+}
+
+function renderTransitionForm(menu, issueKey, transition) {
+  // Only screen fields we can fill with a plain input are shown
+  const fields = transition.fields.filter(
+    (f) => f.required || f.allowed_values.length > 0 || ["string", "number"].includes(f.field_type)
+  );
+
+  menu.classList.add("transition-form");
+  menu.innerHTML = `
+    <form>
+      <div class="transition-form-title">${escapeHtml(transition.name)}</div>
+      ${fields
+        .map(
+          (f) => `
+        <label for="tf-${escapeHtml(f.key)}">${escapeHtml(f.name)}${f.required ? " *" : ""}</label>
+        ${transitionFieldInput(f)}
+      `
+        )
+        .join("")}
+      <label for="tf-comment">Comment</label>
+      <textarea id="tf-comment" rows="2" placeholder="Optional (Markdown)"></textarea>
+      <div class="btn-row">
+        <button type="submit" class="btn btn-primary btn-sm">${escapeHtml(transition.name)}</button>
+        <button type="button" class="btn btn-sm" data-action="cancel">Cancel</button>
+      </div>
+    </form>
+  `;
+
+  const form = menu.querySelector("form");
+  form.addEventListener("click", (e) => {
+    e.stopPropagation();
+    if (e.target.closest("[data-action='cancel']")) closeTransitionMenu();
+  });
+  form.addEventListener("submit", async (e) => {
+    e.preventDefault();
+    const values = {};
+    for (const field of fields) {
+      const value = transitionFieldValue(field, form.querySelector(`[data-key="${CSS.escape(field.key)}"]`));
+      if (value !== null) values[field.key] = value;
+    }
+    const comment = form.querySelector("#tf-comment").value;
+    if (await applyTransition(issueKey, transition, values, comment || null)) {
+      closeTransitionMenu();
+    }
+  });
+  // The form is wider than the option list, keep it inside the window
+  const rect = menu.getBoundingClientRect();
+  if (rect.right > window.innerWidth - 8) {
+    menu.style.left = `${Math.max(8, window.innerWidth - rect.width - 8)}px`;
+  }
+  if (rect.bottom > window.innerHeight - 8) {
+    menu.style.top = `${Math.max(8, window.innerHeight - rect.height - 8)}px`;
+  }
+  form.querySelector("select, input, textarea").focus();
+}
+
 function closeTransitionMenu() {
   const existing = document.querySelector(".transition-menu");
   if (existing) existing.remove();
@@ -386,22 +604,100 @@ function renderTicketDetail(detail) {
       </div>` : ""}
     </div>
     <div class="detail-summary">${escapeHtml(detail.summary)}</div>
-    ${detail.description ? `<div class="detail-description">${escapeHtml(detail.description)}</div>` : ""}
+    ${detail.description_html ? `<div class="detail-description">${detail.description_html}</div>` : ""}
     <div class="detail-actions">
       <button class="btn btn-primary btn-sm" id="detail-start-btn" data-key="${escapeHtml(detail.key)}" data-summary="${escapeHtml(detail.summary)}">Start Timer</button>
     </div>
+    <form class="detail-comment-form" id="detail-comment-form">
+      <textarea id="detail-comment-input" rows="3" placeholder="Add a comment (Markdown)"></textarea>
+      <button type="submit" class="btn btn-sm">Post comment</button>
+    </form>
+    <div class="detail-comments" id="detail-comments"></div>
   `;
 
+  const commentForm = document.getElementById("detail-comment-form");
+  commentForm.addEventListener("submit", async (e) => {
+    e.preventDefault();
+    const input = document.getElementById("detail-comment-input");
+    if (!input.value.trim()) return;
+    try {
+      await addComment(detail.key, input.value);
+      input.value = "";
+      showToast("Comment posted", "success");
+      await loadComments(detail.key);
+    } catch (err) {
+      showToast(err, "error");
+    }
+  });
+
   const startBtn = document.getElementById("detail-start-btn");
   startBtn.addEventListener("click", async () => {
     try {
       await startTimer(startBtn.dataset.key, startBtn.dataset.summary);
       showToast(`Timer started for ${startBtn.dataset.key}`, "success");
-      await refreshTimers();
     } catch (err) {
       showToast(err, "error");
     }
   });
+
+  loadComments(detail.key);
+}
+
+async function loadComments(issueKey, startAt = 0) {
+  const container = document.getElementById("detail-comments");
+  if (!container) return;
+  const moreBtn = container.querySelector(".detail-comments-more");
+  if (startAt === 0) {
+    container.innerHTML = '<div class="loading">Loading comments...</div>';
+  } else if (moreBtn) {
+    moreBtn.disabled = true;
+  }
+
+  try {
+    const page = await getIssueComments(issueKey, startAt);
+    if (detailTitle.textContent !== issueKey) return; // Navigated away
+    if (startAt === 0) container.innerHTML = "";
+    if (moreBtn) moreBtn.remove();
+
+    if (page.total === 0) {
+      container.innerHTML = '<div class="empty-state">No comments.</div>';
+      return;
+    }
+
+    container.insertAdjacentHTML(
+      "beforeend",
+      page.comments
+        .map(
+          (c) => `
+      <div class="comment">
+        <div class="comment-header">
+          <span class="comment-author">${escapeHtml(c.author || "—")}</span>
+          ${c.visibility ? `<span class="comment-visibility" title="Restricted to ${escapeHtml(c.visibility.kind)}">${escapeHtml(c.visibility.value)}</span>` : ""}
+          <span class="comment-date" title="${c.updated !== c.created ? `Edited ${escapeHtml(formatDate(c.updated))}` : ""}">${formatDate(c.created)}${c.updated !== c.created ? " (edited)" : ""}</span>
+        </div>
+        <div class="comment-body detail-description">${c.body_html}</div>
+      </div>
+    `
+        )
+        .join("")
+    );
+
+    const loaded = page.start_at + page.comments.length;
+    if (loaded < page.total) {
+      const btn = document.createElement("button");
+      btn.className = "btn btn-sm detail-comments-more";
+      btn.textContent = `Load more (${page.total - loaded})`;
+      btn.addEventListener("click", () => loadComments(issueKey, loaded));
+      container.appendChild(btn);
+    }
+  } catch (err) {
+    if (startAt === 0) {
+      container.innerHTML = `<div class="empty-state">Error: ${escapeHtml(String(err))}</div>`;
+    } else {
+      showToast(err, "error");
+      if (moreBtn) moreBtn.disabled = false;
+    }
+  }
 }
 
 detailBackBtn.addEventListener("click", () => {
@@ -418,21 +714,47 @@ backBtn.addEventListener("click", () => {
 
 // --- Timers ---
 
-function startRefreshLoop() {
-  refreshTimers();
-  refreshInterval = setInterval(refreshTimers, 1000);
+// The backend pushes the timer list on every change and a tick with running
+// timers' elapsed time each second while the window is visible.
+async function subscribeTimers() {
+  await onTimersChanged((timers) => {
+    latestTimers = timers;
+    if (editingTimerId === null) renderTimers(timers);
+    refreshTimeline();
+  });
+  await onTimersTick(updateTimerTicks);
+  await onQuitRequested(showQuitPrompt);
+  await onIdleDetected(showIdlePrompt);
+  await refreshTimers();
+  await refreshPendingWorklogs();
+  await onDraftsChanged((drafts) => {
+    latestDrafts = drafts;
+    if (editingDraftId === null) renderDrafts(drafts);
+  });
+  await refreshDrafts();
 }
 
 async function refreshTimers() {
   if (editingTimerId !== null) return;
   try {
-    const timers = await getTimers();
-    renderTimers(timers);
+    latestTimers = await getTimers();
+    renderTimers(latestTimers);
+    refreshTimeline();
   } catch (_) {
     // Ignore transient errors
   }
 }
 
+function updateTimerTicks(ticks) {
+  for (const tick of ticks) {
+    if (tick.id === editingTimerId) continue;
+    const el = timersList.querySelector(`.timer-time-display[data-id="${tick.id}"]`);
+    if (!el) continue;
+    el.dataset.seconds = tick.elapsed_seconds;
+    el.textContent = formatTime(tick.elapsed_seconds);
+  }
+}
+
 function formatTime(totalSeconds) {
   const h = Math.floor(totalSeconds / 3600);
   const m = Math.floor((totalSeconds % 3600) / 60);
@@ -453,10 +775,10 @@ function renderTimers(timers) {
       (t) => `
     <div class="timer-card ${t.paused ? "timer-paused" : ""}" data-id="${t.id}">
       <div class="timer-info">
-        <div class="timer-key">${escapeHtml(t.issue_key)}</div>
+        <div class="timer-key" data-action="reassign" data-id="${t.id}" title="Click to move this timer to another ticket">${escapeHtml(t.issue_key)}</div>
         <div class="timer-summary">${escapeHtml(t.summary)}</div>
       </div>
-      <div class="timer-time timer-time-display" data-action="edit-time" data-id="${t.id}" data-seconds="${t.elapsed_seconds}" title="Click to edit">${formatTime(t.elapsed_seconds)}</div>
+      <div class="timer-time timer-time-display ${t.adjustments.length > 0 ? "timer-adjusted" : ""}" data-action="edit-time" data-id="${t.id}" data-seconds="${t.elapsed_seconds}" title="${escapeHtml(adjustmentsTitle(t.adjustments))}">${formatTime(t.elapsed_seconds)}</div>
       <div class="timer-actions">
         ${
           t.paused
@@ -464,14 +786,379 @@ function renderTimers(timers) {
             : `<button class="timer-btn pause" title="Pause" data-action="pause" data-id="${t.id}">&#9208;</button>`
         }
         <button class="timer-btn stop" title="Stop (discard)" data-action="discard" data-id="${t.id}">&#9632;</button>
-        <button class="timer-btn log" title="Stop & log to Jira" data-action="log" data-id="${t.id}">&#10003;</button>
+        <button class="timer-btn split" title="Split across tickets & log" data-action="split" data-id="${t.id}">&#8916;</button>
+        <button class="timer-btn log" title="Stop & log to Jira (Shift+click to add a comment, Alt+click for one worklog per segment)" data-action="log" data-id="${t.id}">&#10003;</button>
       </div>
+      ${t.idle_spans.filter((s) => !s.resolution).map((s) => idlePromptHtml(t, s, timers)).join("")}
     </div>
   `
     )
     .join("");
 }
 
+async function refreshTimeline() {
+  try {
+    renderTimeline(await getTimeline());
+  } catch (_) {
+    // Ignore transient errors
+  }
+}
+
+function renderTimeline(entries) {
+  const timeline = document.getElementById("timeline");
+  if (entries.length === 0) {
+    timeline.innerHTML = '<div class="empty-state">Nothing tracked today.</div>';
+    return;
+  }
+  const clock = (iso) => new Date(iso).toLocaleTimeString([], { hour: "2-digit", minute: "2-digit" });
+  timeline.innerHTML = entries
+    .map((e) => {
+      const end = e.end ? new Date(e.end) : new Date();
+      const seconds = Math.max(0, Math.round((end - new Date(e.start)) / 1000));
+      const state = !e.end ? "running" : e.logged === null ? "active" : e.logged ? "logged" : "discarded";
+      return `
+    <div class="timeline-row timeline-${state}" title="${escapeHtml(e.summary)}">
+      <span class="timeline-range">${clock(e.start)}–${e.end ? clock(e.end) : "…"}</span>
+      <span class="timeline-key">${escapeHtml(e.issue_key)}</span>
+      <span class="timeline-duration">${formatTime(seconds)}</span>
+    </div>`;
+    })
+    .join("");
+}
+
+// "PROJ-1 60%, PROJ-2 40%" or "PROJ-1 1h30, PROJ-2 20m"
+function parseAllocations(input) {
+  return input
+    .split(",")
+    .map((part) => part.trim())
+    .filter((part) => part)
+    .map((part) => {
+      const match = part.match(/^([A-Z][A-Z0-9_]*-\d+)\s+(?:(\d+(?:\.\d+)?)%|(?:(\d+)h)?(?:(\d+)m?)?)$/i);
+      if (!match || (!match[2] && !match[3] && !match[4])) {
+        throw `Allocation invalide : "${part}"`;
+      }
+      const issue_key = match[1].toUpperCase();
+      if (match[2]) return { issue_key, percent: parseFloat(match[2]) };
+      const seconds = parseInt(match[3] || "0", 10) * 3600 + parseInt(match[4] || "0", 10) * 60;
+      return { issue_key, seconds };
+    });
+}
+
+async function splitTimer(timerId) {
+  const timer = latestTimers.find((t) => t.id === timerId);
+  if (!timer) return;
+  const input = prompt(
+    `Split ${formatTime(timer.elapsed_seconds)} of ${timer.issue_key} (ex: ${timer.issue_key} 60%, PROJ-2 40% ou ${timer.issue_key} 1h, PROJ-2 30m)`
+  );
+  if (!input) return;
+  try {
+    const outcome = await splitAndLog(timerId, parseAllocations(input));
+    if (draftMode) {
+      showToast(`${outcome.logged} brouillon(s) enregistre(s)`, "success");
+    } else if (outcome.queued > 0) {
+      showToast(`${outcome.logged} worklog(s) logges, ${outcome.queued} en attente`, "error");
+    } else {
+      showToast(`Logged ${outcome.logged} worklogs to Jira`, "success");
+    }
+  } finally {
+    refreshPendingWorklogs();
+  }
+}
+
+// Asked by the backend when quitting would drop timers or queued worklogs
+function showQuitPrompt(summary) {
+  const container = document.getElementById("quit-prompt");
+  const parts = [];
+  if (summary.timers > 0) parts.push(`${summary.timers} timer(s) (${summary.running} en cours)`);
+  if (summary.pending > 0) parts.push(`${summary.pending} worklog(s) en attente`);
+  container.innerHTML = `
+    <span class="quit-prompt-text">Quitter avec ${parts.join(" et ")} ?</span>
+    <div class="quit-prompt-actions">
+      <button class="idle-btn" data-quit="log" title="Log timers and retry queued worklogs; failures are kept">Log all</button>
+      <button class="idle-btn" data-quit="keep" title="Timers are paused and restored on next launch">Keep</button>
+      <button class="idle-btn" data-quit="discard" title="Timers are dropped; queued worklogs are kept">Discard</button>
+      <button class="idle-btn" data-quit="cancel">Cancel</button>
+    </div>`;
+  container.classList.remove("hidden");
+}
+
+document.getElementById("quit-prompt").addEventListener("click", async (e) => {
+  const btn = e.target.closest("[data-quit]");
+  if (!btn) return;
+  const container = document.getElementById("quit-prompt");
+  const action = btn.dataset.quit;
+  if (action === "cancel") {
+    container.classList.add("hidden");
+    return;
+  }
+  if (action === "discard" && !confirm("Discard all timers and queued worklogs?")) return;
+  container.querySelectorAll("button").forEach((b) => (b.disabled = true));
+  try {
+    await quitApp(action);
+  } catch (err) {
+    showToast(err, "error");
+    container.classList.add("hidden");
+    refreshTimers();
+    refreshPendingWorklogs();
+  }
+});
+
+// A watched repository switched to a branch naming an issue
+function showBranchPrompt(issue) {
+  const container = document.getElementById("branch-prompt");
+  container.innerHTML = `
+    <span class="branch-prompt-text">Branche ${escapeHtml(issue.branch)} : demarrer un timer sur ${escapeHtml(issue.issue_key)} ${escapeHtml(issue.summary)} ?</span>
+    <div class="branch-prompt-actions">
+      <button class="idle-btn" data-branch="start">Start</button>
+      <button class="idle-btn" data-branch="dismiss">Dismiss</button>
+    </div>`;
+  container.dataset.key = issue.issue_key;
+  container.dataset.summary = issue.summary;
+  container.classList.remove("hidden");
+}
+
+document.getElementById("branch-prompt").addEventListener("click", async (e) => {
+  const btn = e.target.closest("[data-branch]");
+  if (!btn) return;
+  const container = document.getElementById("branch-prompt");
+  container.classList.add("hidden");
+  if (btn.dataset.branch !== "start") return;
+  try {
+    await startTimer(container.dataset.key, container.dataset.summary);
+  } catch (err) {
+    showToast(err, "error");
+  }
+});
+
+async function refreshPendingWorklogs() {
+  const container = document.getElementById("pending-worklogs");
+  let pending = [];
+  try {
+    pending = await getPendingWorklogs();
+  } catch (_) {
+    // Ignore transient errors
+  }
+  container.classList.toggle("hidden", pending.length === 0);
+  container.innerHTML = `
+    <div class="pending-header">
+      <span>${pending.length} worklog(s) en attente</span>
+      <button class="idle-btn" data-action="retry-pending">Retry</button>
+    </div>
+    ${pending
+      .map(
+        (w) => `
+    <div class="pending-row" title="${escapeHtml(w.error)}">
+      <span class="timeline-key">${escapeHtml(w.issue_key)}</span>
+      <span>${formatTime(w.seconds)}</span>
+      <button class="idle-btn" data-action="drop-pending" data-id="${w.id}" title="Drop">&times;</button>
+    </div>`
+      )
+      .join("")}`;
+}
+
+document.getElementById("pending-worklogs").addEventListener("click", async (e) => {
+  const btn = e.target.closest("[data-action]");
+  if (!btn) return;
+  try {
+    if (btn.dataset.action === "retry-pending") {
+      const outcome = await retryPendingWorklogs();
+      showToast(`${outcome.logged} logge(s), ${outcome.queued} en attente`, outcome.queued > 0 ? "error" : "success");
+    } else if (btn.dataset.action === "drop-pending") {
+      if (!confirm("Drop this worklog without logging it?")) return;
+      await dropPendingWorklog(parseInt(btn.dataset.id, 10));
+    }
+  } catch (err) {
+    showToast(err, "error");
+  }
+  refreshPendingWorklogs();
+});
+
+// --- Drafts ---
+
+// One line per conflict found by the backend, `worklogs` being the checked ones
+function describeConflicts(conflicts, worklogs) {
+  const clock = (iso) => new Date(iso).toLocaleTimeString([], { hour: "2-digit", minute: "2-digit" });
+  return conflicts.map((c) => {
+    const w = worklogs[c.index];
+    const other =
+      c.source === "logged"
+        ? `${c.issue_key} deja dans Jira`
+        : c.source === "checked"
+          ? `${c.issue_key} ${clock(c.started)} (meme envoi)`
+          : `${c.issue_key} ${clock(c.started)} (en attente, brouillon ou timer)`;
+    const what = c.kind === "duplicate" ? "doublon de" : `chevauche de ${formatTime(c.overlap_seconds)}`;
+    return `${w.issue_key} ${clock(w.started)} : ${what} ${other}`;
+  });
+}
+
+// Ask before logging worklogs that overlap or duplicate others. A failed check
+// (Jira unreachable) does not block: logging will report the error anyway.
+async function confirmConflicts(check, worklogs) {
+  let conflicts = [];
+  try {
+    conflicts = await check();
+  } catch (_) {
+    return true;
+  }
+  if (conflicts.length === 0) return true;
+  return confirm(`Conflits detectes :\n${describeConflicts(conflicts, worklogs).join("\n")}\n\nLogger quand meme ?`);
+}
+
+async function refreshDrafts() {
+  try {
+    renderDrafts(await getDrafts());
+  } catch (_) {
+    // Ignore transient errors
+  }
+}
+
+// "2024-03-04T09:00" in local time, for datetime-local inputs
+function toLocalInput(iso) {
+  const d = new Date(iso);
+  const pad = (n) => String(n).padStart(2, "0");
+  return `${d.getFullYear()}-${pad(d.getMonth() + 1)}-${pad(d.getDate())}T${pad(d.getHours())}:${pad(d.getMinutes())}`;
+}
+
+function renderDrafts(drafts) {
+  latestDrafts = drafts;
+  const container = document.getElementById("drafts");
+  container.classList.toggle("hidden", drafts.length === 0);
+  const pending = drafts.filter((d) => d.status === "draft" || d.status === "failed");
+  const pendingSeconds = pending.reduce((sum, d) => sum + d.seconds, 0);
+  const submitted = drafts.length - pending.length;
+  container.innerHTML = `
+    <div class="drafts-header">
+      <span>${pending.length} brouillon(s), ${formatTime(pendingSeconds)}</span>
+      ${submitted > 0 ? '<button class="idle-btn" data-action="clear-drafts" title="Remove submitted drafts">Clear</button>' : ""}
+      <button class="idle-btn" data-action="submit-drafts" ${pending.length === 0 ? "disabled" : ""}>Submit all</button>
+    </div>
+    ${drafts.map((d) => (d.id === editingDraftId ? draftFormHtml(d) : draftRowHtml(d))).join("")}`;
+}
+
+function draftRowHtml(d) {
+  const editable = d.status === "draft" || d.status === "failed";
+  const day = new Date(d.started).toLocaleDateString([], { weekday: "short", day: "numeric", month: "short" });
+  return `
+    <div class="draft-row" title="${escapeHtml(d.error || "")}">
+      <span class="timeline-key">${escapeHtml(d.issue_key)}</span>
+      <span>${escapeHtml(day)}</span>
+      <span>${formatTime(d.seconds)}</span>
+      <span class="draft-comment">${escapeHtml(d.comment || "")}</span>
+      <span class="draft-status draft-status-${d.status}">${d.status}</span>
+      ${
+        editable
+          ? `<button class="idle-btn" data-action="edit-draft" data-id="${d.id}" title="Edit">&#9998;</button>
+      <button class="idle-btn" data-action="delete-draft" data-id="${d.id}" title="Delete">&times;</button>`
+          : ""
+      }
+    </div>`;
+}
+
+function draftFormHtml(d) {
+  return `
+    <div class="draft-row draft-form" data-id="${d.id}">
+      <input type="text" class="draft-key" value="${escapeHtml(d.issue_key)}" />
+      <input type="datetime-local" class="draft-started" value="${toLocalInput(d.started)}" />
+      <input type="number" class="draft-minutes" min="1" value="${Math.round(d.seconds / 60)}" title="Minutes" />
+      <input type="text" class="draft-comment" value="${escapeHtml(d.comment || "")}" placeholder="Comment" />
+      <button class="idle-btn" data-action="save-draft" data-id="${d.id}">Save</button>
+      <button class="idle-btn" data-action="cancel-draft">Cancel</button>
+    </div>`;
+}
+
+document.getElementById("drafts").addEventListener("click", async (e) => {
+  const btn = e.target.closest("[data-action]");
+  if (!btn) return;
+  const id = parseInt(btn.dataset.id, 10);
+  try {
+    switch (btn.dataset.action) {
+      case "edit-draft":
+        editingDraftId = id;
+        renderDrafts(latestDrafts);
+        return;
+      case "cancel-draft":
+        editingDraftId = null;
+        renderDrafts(latestDrafts);
+        return;
+      case "save-draft": {
+        const row = btn.closest(".draft-row");
+        const comment = row.querySelector(".draft-comment").value.trim();
+        await updateDraft(id, {
+          issue_key: row.querySelector(".draft-key").value.trim().toUpperCase(),
+          seconds: (parseInt(row.querySelector(".draft-minutes").value, 10) || 0) * 60,
+          started: new Date(row.querySelector(".draft-started").value).toISOString(),
+          comment: comment || null,
+        });
+        editingDraftId = null;
+        break;
+      }
+      case "delete-draft":
+        if (!confirm("Delete this draft?")) return;
+        await deleteDraft(id);
+        break;
+      case "clear-drafts":
+        await clearSubmittedDrafts();
+        break;
+      case "submit-drafts": {
+        btn.disabled = true;
+        editingDraftId = null;
+        const toSubmit = latestDrafts.filter((d) => d.status === "draft" || d.status === "failed");
+        if (!(await confirmConflicts(checkDrafts, toSubmit))) break;
+        const results = await submitDrafts();
+        const failed = results.filter((r) => r.error !== null).length;
+        if (failed > 0) {
+          showToast(`${results.length - failed} worklog(s) logge(s), ${failed} en echec`, "error");
+        } else {
+          showToast(`${results.length} worklog(s) logge(s)`, "success");
+        }
+        break;
+      }
+    }
+  } catch (err) {
+    showToast(err, "error");
+    // Keep the form and what was typed
+    if (btn.dataset.action === "save-draft") return;
+  }
+  refreshDrafts();
+});
+
+// The idle period was recorded on the running timers: make sure its prompts
+// are shown, even over a timer being edited, and point the user to them.
+function showIdlePrompt(period) {
+  const clock = (iso) => new Date(iso).toLocaleTimeString([], { hour: "2-digit", minute: "2-digit" });
+  editingTimerId = null;
+  renderTimers(latestTimers);
+  timersList.querySelector(".idle-prompt")?.scrollIntoView({ block: "nearest" });
+  showToast(`Idle ${clock(period.started_at)}–${clock(period.ended_at)}: keep or discard that time`, "success");
+}
+
+function idlePromptHtml(timer, span, timers) {
+  const clock = (iso) => new Date(iso).toLocaleTimeString([], { hour: "2-digit", minute: "2-digit" });
+  const others = timers.filter((o) => o.id !== timer.id);
+  return `
+      <div class="idle-prompt">
+        <span class="idle-prompt-text">Idle ${clock(span.started_at)}–${clock(span.ended_at)} (${formatTime(span.seconds)})</span>
+        <button class="idle-btn" data-action="idle-keep" data-span="${span.id}">Keep</button>
+        <button class="idle-btn" data-action="idle-discard" data-span="${span.id}">Discard</button>
+        ${
+          others.length > 0
+            ? `<select class="idle-reassign" data-span="${span.id}">
+          <option value="">Move to…</option>
+          ${others.map((o) => `<option value="${o.id}">${escapeHtml(o.issue_key)}</option>`).join("")}
+        </select>`
+            : ""
+        }
+      </div>`;
+}
+
+function adjustmentsTitle(adjustments) {
+  if (adjustments.length === 0) return "Click to edit";
+  const lines = adjustments.map(
+    (a) => `${formatDate(a.adjusted_at)}: ${formatTime(a.before_seconds)} → ${formatTime(a.after_seconds)}`
+  );
+  return `Click to edit\nManual adjustments:\n${lines.join("\n")}`;
+}
+
 let editingTimerId = null;
 
 function parseTimeInput(str) {
@@ -486,14 +1173,9 @@ function parseTimeInput(str) {
 function startEditingTime(el) {
   const timerId = parseInt(el.dataset.id, 10);
   if (editingTimerId !== null) return;
+  // Timer events skip rendering while set, so the input isn't overwritten
   editingTimerId = timerId;
 
-  // Pause refresh loop to prevent re-render overwriting the input
-  if (refreshInterval) {
-    clearInterval(refreshInterval);
-    refreshInterval = null;
-  }
-
   const totalSeconds = parseInt(el.dataset.seconds, 10);
   const hh = String(Math.floor(totalSeconds / 3600)).padStart(2, "0");
   const mm = String(Math.floor((totalSeconds % 3600) / 60)).padStart(2, "0");
@@ -515,21 +1197,19 @@ function startEditingTime(el) {
       if (seconds === null) {
         showToast("4 chiffres requis — HHMM (ex: 0130 = 1h30)", "error");
         editingTimerId = null;
-        startRefreshLoop();
+        refreshTimers();
         return;
       }
       editingTimerId = null; // Clear before async to prevent blur re-entry
-      setTimerElapsed(timerId, seconds)
-        .then(() => startRefreshLoop())
-        .catch((err) => {
-          showToast(err, "error");
-          startRefreshLoop();
-        });
+      setTimerElapsed(timerId, seconds).catch((err) => {
+        showToast(err, "error");
+        refreshTimers();
+      });
       return;
     }
     // Cancel
     editingTimerId = null;
-    startRefreshLoop();
+    refreshTimers();
   }
 
   input.addEventListener("keydown", (e) => {
@@ -567,6 +1247,24 @@ timersList.addEventListener("click", async (e) => {
 
   try {
     switch (action) {
+      case "reassign": {
+        const timer = latestTimers.find((t) => t.id === id);
+        const issueKey = prompt("Move this timer to ticket", timer ? timer.issue_key : "");
+        if (!issueKey || !issueKey.trim()) return;
+        await reassignTimer(id, issueKey);
+        showToast(`Timer moved to ${issueKey.trim().toUpperCase()}`, "success");
+        break;
+      }
+      case "split":
+        await splitTimer(id);
+        break;
+      case "idle-keep":
+        await resolveIdleSpan(parseInt(btn.dataset.span, 10), { action: "keep" });
+        break;
+      case "idle-discard":
+        await resolveIdleSpan(parseInt(btn.dataset.span, 10), { action: "discard" });
+        showToast("Idle time removed", "success");
+        break;
       case "pause":
         await pauseTimer(id);
         break;
@@ -579,13 +1277,46 @@ timersList.addEventListener("click", async (e) => {
         showToast("Timer stopped (not logged)", "success");
         break;
       case "log": {
-        const seconds = await stopAndLog(id);
-        showToast(`Logged ${formatTime(seconds)} to Jira`, "success");
+        // Shift+click to add a worklog comment
+        let comment = null;
+        if (e.shiftKey) {
+          comment = prompt("Worklog comment (Markdown)");
+          if (comment === null) return;
+        }
+        // Alt+click to log each run segment as its own worklog
+        if (!draftMode) {
+          const worklogs = [];
+          const checkTimerWorklogs = async () => {
+            const check = await checkTimer(id, e.altKey);
+            worklogs.push(...check.worklogs);
+            return check.conflicts;
+          };
+          if (!(await confirmConflicts(checkTimerWorklogs, worklogs))) return;
+        }
+        try {
+          const seconds = await stopAndLog(id, comment, e.altKey);
+          showToast(draftMode ? `Saved ${formatTime(seconds)} as draft` : `Logged ${formatTime(seconds)} to Jira`, "success");
+        } finally {
+          refreshPendingWorklogs();
+        }
 
         break;
       }
     }
-    await refreshTimers();
+  } catch (err) {
+    showToast(err, "error");
+  }
+});
+
+timersList.addEventListener("change", async (e) => {
+  const select = e.target.closest(".idle-reassign");
+  if (!select || !select.value) return;
+  try {
+    await resolveIdleSpan(parseInt(select.dataset.span, 10), {
+      action: "reassign",
+      timer_id: parseInt(select.value, 10),
+    });
+    showToast("Idle time moved", "success");
   } catch (err) {
     showToast(err, "error");
   }
@@ -600,9 +1331,24 @@ function switchTab(target) {
   });
   tabProjets.classList.toggle("hidden", target !== "projets");
   tabTimesheet.classList.toggle("hidden", target !== "timesheet");
+  tabCalendar.classList.toggle("hidden", target !== "calendar");
+  tabTemplates.classList.toggle("hidden", target !== "templates");
+  tabReports.classList.toggle("hidden", target !== "reports");
+  if (target === "reports" && !latestReport) {
+    setReportRange("this-month");
+    loadReport();
+  }
+  if (target === "templates") {
+    loadTemplates();
+  }
   if (target === "timesheet") {
     loadTimesheet();
   }
+  if (target === "calendar" && !document.getElementById("calendar-from").value) {
+    const { start, end } = getDateRange("week");
+    document.getElementById("calendar-from").value = start;
+    document.getElementById("calendar-to").value = end;
+  }
 }
 
 tabBar.addEventListener("click", (e) => {
@@ -611,6 +1357,339 @@ tabBar.addEventListener("click", (e) => {
   switchTab(tab.dataset.tab);
 });
 
+// --- Calendar import ---
+
+// Meetings from the ICS calendar become worklogs to review: the issue comes
+// from the first matching rule and can be changed, unmatched ones start unchecked.
+async function loadCalendar() {
+  const from = document.getElementById("calendar-from").value;
+  const to = document.getElementById("calendar-to").value;
+  calendarContent.innerHTML = '<div class="loading">Loading events...</div>';
+  calendarLogBtn.disabled = true;
+  calendarDraftBtn.disabled = true;
+  try {
+    calendarProposals = await previewCalendar(from, to);
+    renderCalendar();
+  } catch (err) {
+    calendarProposals = [];
+    calendarContent.innerHTML = `<div class="empty-state">Error: ${escapeHtml(String(err))}</div>`;
+  }
+}
+
+function renderCalendar() {
+  if (calendarProposals.length === 0) {
+    calendarContent.innerHTML = '<div class="empty-state">No meetings in this range.</div>';
+    return;
+  }
+  calendarContent.innerHTML = calendarProposals
+    .map((p, i) => {
+      const started = new Date(p.started);
+      const day = started.toLocaleDateString([], { weekday: "short", day: "numeric", month: "short" });
+      const time = started.toLocaleTimeString([], { hour: "2-digit", minute: "2-digit" });
+      return `
+    <div class="ts-entry calendar-entry${p.imported ? " logged" : ""}" data-index="${i}"${p.imported ? ' title="Deja importe"' : ""}>
+      <input type="checkbox" class="calendar-check" ${p.issue_key && !p.imported ? "checked" : ""} ${p.imported ? "disabled" : ""} />
+      <input type="text" class="calendar-key" value="${escapeHtml(p.issue_key || "")}" placeholder="PROJ-1" ${p.imported ? "disabled" : ""} />
+      <span class="ts-summary" title="${escapeHtml(p.title)}">${escapeHtml(day)} ${escapeHtml(time)} ${escapeHtml(p.title)}</span>
+      ${
+        p.unknown_tzid
+          ? `<span class="calendar-tz-warning" title="Fuseau inconnu (${escapeHtml(p.unknown_tzid)}), lu en heure locale : verifier l'heure">&#9888;</span>`
+          : ""
+      }
+      <input type="number" class="calendar-minutes" min="1" value="${Math.round(p.seconds / 60)}" title="Minutes" ${p.imported ? "disabled" : ""} />
+    </div>`;
+    })
+    .join("");
+  calendarLogBtn.disabled = false;
+  calendarDraftBtn.disabled = false;
+}
+
+document.getElementById("calendar-preview").addEventListener("click", loadCalendar);
+
+// The checked rows not logged yet, with their worklogs; null when one is incomplete
+function selectedCalendarWorklogs() {
+  const rows = Array.from(calendarContent.querySelectorAll(".calendar-entry:not(.logged)")).filter(
+    (row) => row.querySelector(".calendar-check").checked
+  );
+  const worklogs = [];
+  for (const row of rows) {
+    const proposal = calendarProposals[parseInt(row.dataset.index, 10)];
+    const issueKey = row.querySelector(".calendar-key").value.trim().toUpperCase();
+    const minutes = parseInt(row.querySelector(".calendar-minutes").value, 10) || 0;
+    if (!issueKey || minutes <= 0) {
+      showToast(`Issue et duree requis pour "${proposal.title}"`, "error");
+      return null;
+    }
+    worklogs.push({
+      uid: proposal.uid,
+      issue_key: issueKey,
+      seconds: minutes * 60,
+      started: proposal.started,
+      comment: proposal.title,
+    });
+  }
+  return { rows, worklogs };
+}
+
+function markCalendarLogged(row) {
+  row.classList.add("logged");
+  row.querySelectorAll("input").forEach((input) => (input.disabled = true));
+}
+
+calendarDraftBtn.addEventListener("click", async () => {
+  const selected = selectedCalendarWorklogs();
+  if (!selected || selected.worklogs.length === 0) return;
+  try {
+    const ids = await draftCalendarWorklogs(selected.worklogs);
+    selected.rows.forEach(markCalendarLogged);
+    showToast(`${ids.length} brouillon(s) enregistre(s)`, "success");
+  } catch (err) {
+    showToast(err, "error");
+  }
+});
+
+calendarLogBtn.addEventListener("click", async () => {
+  const selected = selectedCalendarWorklogs();
+  if (!selected || selected.worklogs.length === 0) return;
+  const { rows, worklogs } = selected;
+  if (!(await confirmConflicts(() => checkWorklogs(worklogs), worklogs))) return;
+
+  calendarLogBtn.disabled = true;
+  try {
+    const errors = await logCalendarWorklogs(worklogs);
+    rows.forEach((row, i) => {
+      row.querySelector(".calendar-error")?.remove();
+      if (errors[i] === null) {
+        markCalendarLogged(row);
+      } else {
+        row.insertAdjacentHTML("beforeend", `<span class="calendar-error" title="${escapeHtml(errors[i])}">echec</span>`);
+      }
+    });
+    const failed = errors.filter((e) => e !== null).length;
+    if (failed > 0) {
+      showToast(`${worklogs.length - failed} worklog(s) logge(s), ${failed} en echec`, "error");
+    } else {
+      showToast(`${worklogs.length} worklog(s) logge(s)`, "success");
+    }
+  } catch (err) {
+    showToast(err, "error");
+  } finally {
+    calendarLogBtn.disabled = false;
+  }
+});
+
+// --- Templates ---
+
+let latestTemplates = [];
+
+// "weekdays 09:30" or "Mon,Thu 16:00", as typed in the form
+function formatSchedule(schedule) {
+  if (!schedule) return "";
+  const days = schedule.days.join(",");
+  const label = days === "Mon,Tue,Wed,Thu,Fri" ? "weekdays" : schedule.days.length === 7 ? "daily" : days;
+  return `${label} ${schedule.at.slice(0, 5)}`;
+}
+
+async function loadTemplates() {
+  try {
+    latestTemplates = await getTemplates();
+    renderTemplates();
+  } catch (err) {
+    showToast(err, "error");
+  }
+}
+
+function renderTemplates() {
+  const list = document.getElementById("templates-list");
+  if (latestTemplates.length === 0) {
+    list.innerHTML = '<div class="empty-state">No templates yet.<br>Add the worklogs that come back every time below.</div>';
+    return;
+  }
+  const autoLabel = { draft: "auto draft", log: "auto log" };
+  list.innerHTML = latestTemplates
+    .map(
+      (t, i) => `
+    <div class="ts-entry template-entry">
+      <span class="ts-key">${escapeHtml(t.issue_key)}</span>
+      <span class="ts-summary" title="${escapeHtml(t.comment || "")}">${escapeHtml(t.name)}</span>
+      <span class="template-schedule">${escapeHtml(formatSchedule(t.schedule))}${t.auto ? ` (${autoLabel[t.auto]})` : ""}</span>
+      <span class="ts-time">${formatTime(t.seconds)}</span>
+      <button class="idle-btn" data-action="template-draft" data-index="${i}" title="Add as draft (Shift+click to pick the start)">Draft</button>
+      <button class="idle-btn" data-action="template-log" data-index="${i}" title="Log to Jira (Shift+click to pick the start)">Log</button>
+      <button class="idle-btn" data-action="template-edit" data-index="${i}" title="Edit">&#9998;</button>
+      <button class="idle-btn" data-action="template-delete" data-index="${i}" title="Delete">&times;</button>
+    </div>`
+    )
+    .join("");
+}
+
+document.getElementById("templates-list").addEventListener("click", async (e) => {
+  const btn = e.target.closest("[data-action]");
+  if (!btn) return;
+  const template = latestTemplates[parseInt(btn.dataset.index, 10)];
+  try {
+    switch (btn.dataset.action) {
+      case "template-draft":
+      case "template-log": {
+        const mode = btn.dataset.action === "template-draft" ? "draft" : "log";
+        // Shift+click to log another day than today
+        let started = null;
+        if (e.shiftKey) {
+          const input = prompt("Start (YYYY-MM-DD HH:MM)");
+          if (!input) return;
+          const date = new Date(input.trim().replace(" ", "T"));
+          if (isNaN(date)) throw `Date invalide : "${input}"`;
+          started = date.toISOString();
+        }
+        const request = await applyTemplate(template.name, mode, started);
+        const what = `${formatTime(request.seconds)} sur ${request.issue_key}`;
+        showToast(mode === "draft" ? `${what} en brouillon` : `${what} logge`, "success");
+        break;
+      }
+      case "template-edit":
+        document.getElementById("template-name").value = template.name;
+        document.getElementById("template-issue").value = template.issue_key;
+        document.getElementById("template-duration").value = `${Math.round(template.seconds / 60)} min`;
+        document.getElementById("template-schedule").value = formatSchedule(template.schedule);
+        document.getElementById("template-comment").value = template.comment || "";
+        document.getElementById("template-auto").value = template.auto || "";
+        break;
+      case "template-delete":
+        if (!confirm(`Delete template "${template.name}"?`)) return;
+        await deleteTemplate(template.name);
+        await loadTemplates();
+        break;
+    }
+  } catch (err) {
+    showToast(err, "error");
+  }
+});
+
+document.getElementById("template-form").addEventListener("submit", async (e) => {
+  e.preventDefault();
+  try {
+    await saveTemplate({
+      name: document.getElementById("template-name").value,
+      issueKey: document.getElementById("template-issue").value,
+      duration: document.getElementById("template-duration").value,
+      schedule: document.getElementById("template-schedule").value,
+      comment: document.getElementById("template-comment").value,
+      auto: document.getElementById("template-auto").value || null,
+    });
+    e.target.reset();
+    await loadTemplates();
+    showToast("Template saved", "success");
+  } catch (err) {
+    showToast(err, "error");
+  }
+});
+
+// --- Reports ---
+
+let latestReport = null;
+let reportLoading = false;
+
+function monthRange(offset) {
+  const now = new Date();
+  const first = new Date(now.getFullYear(), now.getMonth() + offset, 1);
+  const last = new Date(now.getFullYear(), now.getMonth() + offset + 1, 0);
+  const fmt = (d) =>
+    `${d.getFullYear()}-${String(d.getMonth() + 1).padStart(2, "0")}-${String(d.getDate()).padStart(2, "0")}`;
+  return { start: fmt(first), end: fmt(last) };
+}
+
+function setReportRange(range) {
+  const { start, end } = monthRange(range === "last-month" ? -1 : 0);
+  document.getElementById("report-from").value = start;
+  document.getElementById("report-to").value = end;
+}
+
+async function loadReport() {
+  const from = document.getElementById("report-from").value;
+  const to = document.getElementById("report-to").value;
+  if (!from || !to || reportLoading) return;
+  reportLoading = true;
+  const content = document.getElementById("report-content");
+  content.innerHTML = '<div class="loading">Loading worklogs...</div>';
+  document.getElementById("report-total-value").textContent = "...";
+  try {
+    latestReport = await getReport(from, to);
+    renderReport();
+  } catch (err) {
+    latestReport = null;
+    content.innerHTML = `<div class="empty-state">Error: ${escapeHtml(String(err))}</div>`;
+    document.getElementById("report-total-value").textContent = "0:00:00";
+  } finally {
+    reportLoading = false;
+    document.getElementById("report-copy").disabled = !latestReport;
+  }
+}
+
+function currentBreakdown() {
+  const dimension = document.getElementById("report-dimension").value;
+  return latestReport?.breakdowns.find((b) => b.dimension === dimension);
+}
+
+function renderReport() {
+  const content = document.getElementById("report-content");
+  document.getElementById("report-total-value").textContent = formatTime(latestReport.total_seconds);
+  const breakdown = currentBreakdown();
+  if (latestReport.total_seconds === 0 || !breakdown) {
+    content.innerHTML = '<div class="empty-state">No worklogs found for this period.</div>';
+    return;
+  }
+  content.innerHTML = breakdown.rows
+    .map(
+      (row) => `
+    <div class="ts-entry report-row">
+      <div class="report-bar" style="width: ${Math.min(row.percent, 100).toFixed(1)}%"></div>
+      ${row.key && row.key !== row.label ? `<span class="ts-key">${escapeHtml(row.key)}</span>` : ""}
+      <span class="ts-summary" title="${row.issues} issue(s)">${escapeHtml(row.label)}</span>
+      <span class="report-percent">${row.percent.toFixed(1)}%</span>
+      <span class="ts-time">${formatTime(row.seconds)}</span>
+    </div>`
+    )
+    .join("");
+}
+
+function reportCsv() {
+  const breakdown = currentBreakdown();
+  const cell = (value) => `"${String(value).replace(/"/g, '""')}"`;
+  const lines = [["Key", "Name", "Hours", "Percent", "Issues"].join(",")];
+  for (const row of breakdown.rows) {
+    lines.push(
+      [cell(row.key), cell(row.label), (row.seconds / 3600).toFixed(2), row.percent.toFixed(1), row.issues].join(",")
+    );
+  }
+  lines.push(["", cell("Total"), (latestReport.total_seconds / 3600).toFixed(2), "100.0", latestReport.issues].join(","));
+  return lines.join("\n");
+}
+
+document.querySelector(".report-controls").addEventListener("click", (e) => {
+  const btn = e.target.closest("[data-range]");
+  if (!btn) return;
+  setReportRange(btn.dataset.range);
+  loadReport();
+});
+
+document.getElementById("report-refresh").addEventListener("click", loadReport);
+document.getElementById("report-from").addEventListener("change", loadReport);
+document.getElementById("report-to").addEventListener("change", loadReport);
+
+document.getElementById("report-dimension").addEventListener("change", () => {
+  if (latestReport) renderReport();
+});
+
+document.getElementById("report-copy").addEventListener("click", async () => {
+  if (!latestReport) return;
+  try {
+    await navigator.clipboard.writeText(reportCsv());
+    showToast("Report copied as CSV", "success");
+  } catch (err) {
+    showToast(err, "error");
+  }
+});
+
 // --- Timesheet ---
 
 function getDateRange(mode) {